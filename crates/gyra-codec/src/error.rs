@@ -23,6 +23,11 @@ pub enum CodecError {
         source: Box<CodecError>,
     },
 
+    #[error("Unknown NBT tag: {0}")]
+    UnknownNbtTag(u8),
+    #[error("NBT nested deeper than {0} tags")]
+    NbtTooDeep(usize),
+
     #[error("Illegal packet: 0x{0:02X} on {1:?}")]
     IllegalPacket(PacketId, When),
 }
@@ -2,4 +2,5 @@ pub mod coding;
 pub mod error;
 pub mod nibble;
 pub mod packet;
+pub mod position;
 pub mod variadic_int;
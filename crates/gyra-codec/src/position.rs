@@ -0,0 +1,68 @@
+use crate::coding::{Decoder, Encoder};
+use crate::error::Result;
+use std::io::{Read, Write};
+
+/// A block position packed into a long: 26 bits of x, 12 of y and 26 of z.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl Decoder for Position {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        let packed = i64::decode(reader)?;
+
+        // shifting back down keeps the sign
+        Ok(Self {
+            x: (packed >> 38) as i32,
+            y: (packed << 26 >> 52) as i32,
+            z: (packed << 38 >> 38) as i32,
+        })
+    }
+}
+
+impl Encoder for Position {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<usize> {
+        let packed = ((self.x as i64 & 0x3FFFFFF) << 38)
+            | ((self.y as i64 & 0xFFF) << 26)
+            | (self.z as i64 & 0x3FFFFFF);
+
+        packed.encode(writer)
+    }
+}
+
+#[test]
+fn position_round_trip() {
+    for position in [
+        Position::new(0, 0, 0),
+        Position::new(18357644, 831, -20882616),
+        Position::new(-1, 255, -1),
+        Position::new(-33554432, 0, 33554431),
+    ] {
+        let mut buffer = Vec::new();
+        position.encode(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 8);
+
+        let decoded = Position::decode(&mut buffer.as_slice()).unwrap();
+        assert_eq!(decoded, position);
+    }
+}
+
+#[test]
+fn position_matches_vanilla_layout() {
+    let mut buffer = Vec::new();
+    Position::new(1, 2, 3).encode(&mut buffer).unwrap();
+
+    assert_eq!(
+        i64::from_be_bytes(buffer.try_into().unwrap()),
+        1 << 38 | 2 << 26 | 3
+    );
+}
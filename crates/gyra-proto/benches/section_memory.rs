@@ -0,0 +1,113 @@
+// Memory used by paletted sections compared to the flat layout they replaced,
+// run with `cargo bench -p gyra-proto --bench section_memory`.
+
+use gyra_codec::coding::Decoder;
+use gyra_proto::smp::{ChunkColumn, ChunkSection, NetworkBlock};
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::Instant;
+
+const BLOCKS: usize = 4096;
+
+// a Vec<NetworkBlock> of 4096 entries plus two nibble arrays
+const FLAT_SECTION: usize = BLOCKS * size_of::<NetworkBlock>() + 2 * BLOCKS / 2;
+
+// section in the network layout: blocks, block light and sky light
+fn encode(block: impl Fn(usize) -> u16, blocklight: u8, skylight: impl Fn(usize) -> u8) -> Vec<u8> {
+    let mut data = Vec::with_capacity(BLOCKS * 3);
+
+    for idx in 0..BLOCKS {
+        data.extend(block(idx).to_le_bytes());
+    }
+
+    data.extend(std::iter::repeat_n(
+        blocklight | blocklight << 4,
+        BLOCKS / 2,
+    ));
+    data.extend((0..BLOCKS / 2).map(|idx| skylight(idx * 2) | skylight(idx * 2 + 1) << 4));
+
+    data
+}
+
+fn decode(data: Vec<u8>) -> ChunkSection {
+    ChunkSection::decode(&mut Cursor::new(data)).unwrap()
+}
+
+// xorshift, so every run measures the same sections
+fn noise(seed: usize) -> usize {
+    let mut x = seed as u32 ^ 0x9e37_79b9;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x as usize
+}
+
+fn report(name: &str, section: &ChunkSection) {
+    let paletted = size_of::<ChunkSection>() + section.heap_size();
+
+    println!(
+        "{name:<12} flat: {FLAT_SECTION:>6} B  paletted: {paletted:>6} B  ({:.1}%)",
+        paletted as f64 * 100.0 / FLAT_SECTION as f64
+    );
+}
+
+fn main() {
+    let stone = decode(encode(|_| 1 << 4, 0, |_| 0));
+
+    // grass on top of dirt and stone with a few ores, lit from above
+    let surface = decode(encode(
+        |idx| match idx >> 8 {
+            15 => 0,
+            14 => 2 << 4,
+            11..=13 => 3 << 4,
+            _ if noise(idx).is_multiple_of(50) => [14, 15, 16, 56][noise(idx) % 4] << 4,
+            _ => 1 << 4,
+        },
+        0,
+        |idx| if idx >> 8 == 15 { 15 } else { 0 },
+    ));
+
+    // a section every block id could be in, the worst case for a palette
+    let noisy = decode(encode(
+        |idx| (((noise(idx) % 256) << 4) | (noise(idx * 7) % 16)) as u16,
+        3,
+        |idx| (noise(idx * 3) % 16) as u8,
+    ));
+
+    report("stone", &stone);
+    report("surface", &surface);
+    report("noisy", &noisy);
+
+    let sections = std::iter::once(stone)
+        .chain(std::iter::repeat_n(surface, 4))
+        .collect::<Vec<_>>();
+    let column = ChunkColumn::from_sections(sections, 0b11111, 0, 0);
+
+    println!(
+        "column       flat: {:>6} B  paletted: {:>6} B",
+        5 * FLAT_SECTION,
+        column.heap_size()
+    );
+
+    // what `update_active_chunks` and every mesh task used to pay per column
+    let runs = 10_000;
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(column.clone());
+    }
+    println!(
+        "column clone {:>8.1} ns, sections stay shared",
+        start.elapsed().as_nanos() as f64 / runs as f64
+    );
+
+    let mut written = column.clone();
+    let start = Instant::now();
+    written
+        .section_mut(4)
+        .unwrap()
+        .set_block(0, 0, 0, NetworkBlock { id: 4, metadata: 0 });
+    println!(
+        "first write  {:>8.1} ns, copies one section",
+        start.elapsed().as_nanos() as f64
+    );
+}
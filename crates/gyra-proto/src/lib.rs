@@ -2,6 +2,8 @@ mod handshake;
 mod login;
 pub mod network;
 mod play;
+pub mod palette;
+pub mod slot;
 pub mod smp;
 mod status;
 pub mod distance;
@@ -179,4 +179,9 @@ macro_rules! mk_proto {
 mk_proto!(Proto => PingPong, StatusRequest, StatusResponse, Handshake,
     LoginStart, LoginSuccess, SetCompression, KeepAlive, JoinGame, ChatMessage,
     EntityRelativeMove, Entity, Disconnect, LoginDisconnect, SendChatMessage, PlayerLook,
-    ChunkData, MapChunkBulk, PlayerPositionAndLook, PlayerPosition);
+    ChunkData, MapChunkBulk, PlayerPositionAndLook, PlayerPosition, Player,
+    SendPlayerPositionAndLook, PlayerDigging, PlayerBlockPlacement, Animation, BlockChange,
+    MultiBlockChange, OpenWindow, CloseWindow, SendCloseWindow, SetSlot, WindowItems,
+    ConfirmTransaction, SendConfirmTransaction, ClickWindow, UpdateHealth, SetExperience,
+    HeldItemChange, SendHeldItemChange, Respawn, ClientStatus, TimeUpdate,
+    ChangeGameState, SpawnGlobalEntity, TabComplete, TabCompleteResponse);
@@ -0,0 +1,227 @@
+// Paletted storage, every distinct value is kept once and entries only store an index into it.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/**
+ * A fixed length array of `T` backed by a palette.
+ * The indices are packed `bits` wide into u64 words, an entry never spans two words.
+ * A palette with a single value needs no words at all, which is the common case for
+ * sections full of stone or skylight that is 15 everywhere.
+ */
+#[derive(Clone, Debug)]
+pub struct PalettedArray<T> {
+    palette: Vec<T>,
+    bits: u32,
+    words: Vec<u64>,
+    len: usize,
+}
+
+fn bits_for(palette_len: usize) -> u32 {
+    if palette_len <= 1 {
+        0
+    } else {
+        usize::BITS - (palette_len - 1).leading_zeros()
+    }
+}
+
+fn words_for(len: usize, bits: u32) -> usize {
+    64u32
+        .checked_div(bits)
+        .map_or(0, |per_word| len.div_ceil(per_word as usize))
+}
+
+impl<T: Copy + Eq + Hash> PalettedArray<T> {
+    pub fn filled(value: T, len: usize) -> Self {
+        Self {
+            palette: vec![value],
+            bits: 0,
+            words: vec![],
+            len,
+        }
+    }
+
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut palette = Vec::new();
+        let mut lookup = HashMap::new();
+
+        let indices = values
+            .iter()
+            .map(|value| {
+                *lookup.entry(*value).or_insert_with(|| {
+                    palette.push(*value);
+                    palette.len() as u64 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if palette.is_empty() {
+            return Self {
+                palette,
+                bits: 0,
+                words: vec![],
+                len: 0,
+            };
+        }
+
+        palette.shrink_to_fit();
+
+        let bits = bits_for(palette.len());
+        let mut array = Self {
+            palette,
+            bits,
+            words: vec![0; words_for(values.len(), bits)],
+            len: values.len(),
+        };
+
+        for (index, palette_index) in indices.into_iter().enumerate() {
+            array.store(index, palette_index);
+        }
+
+        array
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The distinct values, entries that were overwritten since are not removed.
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        assert!(index < self.len, "index {index} is out of bounds");
+        self.palette[self.load(index) as usize]
+    }
+
+    /// Stores `value` at `index`, growing the index width when the palette runs out of room.
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        assert!(index < self.len, "index {index} is out of bounds");
+
+        let palette_index = match self.palette.iter().position(|entry| *entry == value) {
+            Some(found) => found,
+            None => {
+                self.palette.push(value);
+                self.resize(bits_for(self.palette.len()));
+                self.palette.len() - 1
+            }
+        };
+
+        let old = self.palette[self.load(index) as usize];
+        self.store(index, palette_index as u64);
+        old
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len).map(|index| self.get(index))
+    }
+
+    /// Bytes this array owns on the heap.
+    pub fn heap_size(&self) -> usize {
+        self.palette.capacity() * size_of::<T>() + self.words.capacity() * size_of::<u64>()
+    }
+
+    fn resize(&mut self, bits: u32) {
+        if bits <= self.bits {
+            return;
+        }
+
+        let indices = (0..self.len)
+            .map(|index| self.load(index))
+            .collect::<Vec<_>>();
+
+        self.bits = bits;
+        self.words = vec![0; words_for(self.len, bits)];
+
+        for (index, palette_index) in indices.into_iter().enumerate() {
+            self.store(index, palette_index);
+        }
+    }
+
+    fn load(&self, index: usize) -> u64 {
+        if self.bits == 0 {
+            return 0;
+        }
+
+        let per_word = (64 / self.bits) as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+
+        (self.words[index / per_word] >> shift) & mask
+    }
+
+    fn store(&mut self, index: usize, palette_index: u64) {
+        if self.bits == 0 {
+            return;
+        }
+
+        let per_word = (64 / self.bits) as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+        let word = &mut self.words[index / per_word];
+
+        *word = (*word & !(mask << shift)) | ((palette_index & mask) << shift);
+    }
+}
+
+// two arrays are equal when they hold the same values, whatever order their palettes are in
+impl<T: Copy + Eq + Hash> PartialEq for PalettedArray<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+#[test]
+fn uniform_array_has_no_words() {
+    let array = PalettedArray::from_slice(&[7u8; 4096]);
+
+    assert_eq!(array.bits(), 0);
+    assert_eq!(array.get(4095), 7);
+    assert_eq!(array.heap_size(), 1);
+}
+
+#[test]
+fn set_grows_the_palette() {
+    let mut array = PalettedArray::filled(0u16, 4096);
+
+    for index in 0..300 {
+        array.set(index, index as u16);
+    }
+
+    assert_eq!(array.bits(), 9);
+    assert_eq!(array.palette().len(), 300);
+
+    for index in 0..300 {
+        assert_eq!(array.get(index), index as u16);
+    }
+    assert_eq!(array.get(300), 0);
+}
+
+#[test]
+fn set_returns_the_old_value() {
+    let mut array = PalettedArray::from_slice(&[1u8, 2, 3, 4]);
+
+    assert_eq!(array.set(2, 9), 3);
+    assert_eq!(array.iter().collect::<Vec<_>>(), vec![1, 2, 9, 4]);
+}
+
+#[test]
+fn equality_ignores_palette_order() {
+    let mut a = PalettedArray::filled(0u8, 8);
+    a.set(0, 5);
+    a.set(0, 6);
+
+    let mut b = PalettedArray::filled(0u8, 8);
+    b.set(0, 6);
+
+    assert_eq!(a, b);
+}
@@ -0,0 +1,86 @@
+use crate::slot::Slot;
+use gyra_codec::coding::{Decoder, Encoder};
+use gyra_codec::position::Position;
+use gyra_codec::variadic_int::VarInt;
+use gyra_macros::{packet, CodecDecode, CodecEncode};
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x07, when: Play, server)]
+pub struct PlayerDigging {
+    /// 0 started, 1 cancelled, 2 finished
+    pub status: u8,
+    pub location: Position,
+    pub face: u8,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x08, when: Play, server)]
+pub struct PlayerBlockPlacement {
+    pub location: Position,
+    /// 255 when using the held item without a block
+    pub face: u8,
+    pub held_item: Slot,
+    /// where on the face, in sixteenths of a block
+    pub cursor_x: u8,
+    pub cursor_y: u8,
+    pub cursor_z: u8,
+}
+
+/// Swings the arm.
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x0A, when: Play, server)]
+pub struct Animation {}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x23, when: Play)]
+pub struct BlockChange {
+    pub location: Position,
+    /// id << 4 | metadata
+    pub block_id: VarInt,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockRecord {
+    /// x in the high nibble, z in the low one
+    pub horizontal: u8,
+    pub y: u8,
+    pub block_id: VarInt,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[packet(id: 0x22, when: Play)]
+pub struct MultiBlockChange {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub records: Vec<BlockRecord>,
+}
+
+impl Decoder for MultiBlockChange {
+    fn decode<R: std::io::Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        let chunk_x = i32::decode(reader)?;
+        let chunk_z = i32::decode(reader)?;
+        let count = VarInt::decode(reader)?.0;
+
+        let records = (0..count)
+            .map(|_| {
+                Ok(BlockRecord {
+                    horizontal: u8::decode(reader)?,
+                    y: u8::decode(reader)?,
+                    block_id: VarInt::decode(reader)?,
+                })
+            })
+            .collect::<gyra_codec::error::Result<_>>()?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            records,
+        })
+    }
+}
+
+impl Encoder for MultiBlockChange {
+    fn encode<W: std::io::Write>(&self, _writer: &mut W) -> gyra_codec::error::Result<usize> {
+        unreachable!("MultiBlockChange is not a packet that should be sent by the client")
+    }
+}
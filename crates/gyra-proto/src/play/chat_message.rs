@@ -1,3 +1,6 @@
+use gyra_codec::coding::{Decoder, Encoder};
+use gyra_codec::position::Position;
+use gyra_codec::variadic_int::VarInt;
 use gyra_macros::{packet, CodecDecode, CodecEncode};
 
 #[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
@@ -12,3 +15,96 @@ pub struct ChatMessage {
 pub struct SendChatMessage {
     pub content: String,
 }
+
+/// Asks for ways to finish the last word of `text`.
+#[derive(Clone, Debug, PartialEq)]
+#[packet(id: 0x14, when: Play, server)]
+pub struct TabComplete {
+    pub text: String,
+    /// the block the player is looking at, some commands take its coordinates
+    pub looked_at: Option<Position>,
+}
+
+impl Decoder for TabComplete {
+    fn decode<R: std::io::Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        let text = String::decode(reader)?;
+        let looked_at = if bool::decode(reader)? {
+            Some(Position::decode(reader)?)
+        } else {
+            None
+        };
+
+        Ok(Self { text, looked_at })
+    }
+}
+
+impl Encoder for TabComplete {
+    fn encode<W: std::io::Write>(&self, writer: &mut W) -> gyra_codec::error::Result<usize> {
+        let mut written = self.text.encode(writer)?;
+        written += self.looked_at.is_some().encode(writer)?;
+        if let Some(position) = &self.looked_at {
+            written += position.encode(writer)?;
+        }
+
+        Ok(written)
+    }
+}
+
+/// What the last word of a `TabComplete` could be.
+#[derive(Clone, Debug, PartialEq)]
+#[packet(id: 0x3A, when: Play)]
+pub struct TabCompleteResponse {
+    pub matches: Vec<String>,
+}
+
+impl Decoder for TabCompleteResponse {
+    fn decode<R: std::io::Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        let count = VarInt::decode(reader)?.0.max(0);
+        let matches = (0..count)
+            .map(|_| String::decode(reader))
+            .collect::<gyra_codec::error::Result<_>>()?;
+
+        Ok(Self { matches })
+    }
+}
+
+impl Encoder for TabCompleteResponse {
+    fn encode<W: std::io::Write>(&self, writer: &mut W) -> gyra_codec::error::Result<usize> {
+        let mut written = VarInt(self.matches.len() as i32).encode(writer)?;
+        for name in &self.matches {
+            written += name.encode(writer)?;
+        }
+
+        Ok(written)
+    }
+}
+
+#[test]
+fn tab_complete_without_a_block() {
+    let packet = TabComplete {
+        text: "/tp Ste".to_string(),
+        looked_at: None,
+    };
+
+    let mut buffer = Vec::new();
+    packet.encode(&mut buffer).unwrap();
+
+    assert_eq!(buffer.last(), Some(&0));
+    assert_eq!(TabComplete::decode(&mut buffer.as_slice()).unwrap(), packet);
+}
+
+#[test]
+fn tab_complete_response_round_trip() {
+    let packet = TabCompleteResponse {
+        matches: vec!["Steve".to_string(), "Stevie".to_string()],
+    };
+
+    let mut buffer = Vec::new();
+    packet.encode(&mut buffer).unwrap();
+
+    assert_eq!(buffer[0], 2);
+    assert_eq!(
+        TabCompleteResponse::decode(&mut buffer.as_slice()).unwrap(),
+        packet
+    );
+}
@@ -12,6 +12,8 @@ pub struct ChunkData {
     pub primary_bit_mask: u16,
     pub chunk_size: VarInt,
     pub sections: Vec<smp::ChunkSection>,
+    // only sent with full chunks
+    pub biomes: Option<Box<[u8; 256]>>,
 }
 
 impl Decoder for ChunkData {
@@ -23,13 +25,20 @@ impl Decoder for ChunkData {
         let primary_bit_mask = u16::decode(reader)?;
         let chunk_size = VarInt::decode(reader)?;
 
-        let mut sections = Vec::new();
+        // sky light has no flag, it is whatever is left after blocks, block light and biomes
+        let count = primary_bit_mask.count_ones() as i32;
+        let without_sky = count * (8192 + 2048) + if full_chunk { 256 } else { 0 };
+        let sky_light = count > 0 && chunk_size.0 >= without_sky + count * 2048;
 
-        for i in 0..16 {
-            if primary_bit_mask & (1 << i) != 0 {
-                sections.push(smp::ChunkSection::decode(reader)?);
-            }
-        }
+        let sections = smp::decode_sections(reader, primary_bit_mask, sky_light)?;
+
+        let biomes = if full_chunk {
+            let mut biomes = Box::new([0; 256]);
+            reader.read_exact(biomes.as_mut())?;
+            Some(biomes)
+        } else {
+            None
+        };
 
         Ok(Self {
             x,
@@ -38,6 +47,7 @@ impl Decoder for ChunkData {
             primary_bit_mask,
             chunk_size,
             sections,
+            biomes,
         })
     }
 }
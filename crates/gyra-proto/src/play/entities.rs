@@ -16,3 +16,16 @@ pub struct EntityRelativeMove {
     pub delta_z: i8,
     pub on_ground: bool,
 }
+
+/// Entities seen from anywhere in the world, only thunderbolts.
+#[derive(CodecDecode, CodecEncode, Debug, PartialEq)]
+#[packet(id: 0x2C, when: Play)]
+pub struct SpawnGlobalEntity {
+    pub entity_id: VarInt,
+    /// 1 is a thunderbolt
+    pub kind: i8,
+    /// fixed point, 1/32 of a block
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
@@ -11,3 +11,14 @@ pub struct JoinGame {
     pub level_type: String,
     pub reduced_debug_info: bool,
 }
+
+/// Sent after dying and when changing dimension, the world starts over.
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x07, when: Play)]
+pub struct Respawn {
+    /// -1 nether, 0 overworld, 1 end
+    pub dimension: i32,
+    pub difficulty: u8,
+    pub game_mode: u8,
+    pub level_type: String,
+}
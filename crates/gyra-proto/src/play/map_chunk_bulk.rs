@@ -3,6 +3,7 @@ use gyra_codec::coding::{Decoder, Encoder};
 use gyra_codec::variadic_int::VarInt;
 use gyra_macros::{packet, CodecDecode, CodecEncode};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 #[derive(Clone, Debug, PartialEq, CodecDecode, CodecEncode)]
 pub struct ChunkMetadata {
@@ -54,6 +55,8 @@ impl Decoder for MapChunkBulk {
                 z: metadata.z,
             };
 
+            let mut sections = smp::decode_sections(reader, bitmask, is_overworld)?;
+
             for i in 0..=15 {
                 if 0 != (bitmask & (1 << i)) {
                     log::info!(
@@ -63,15 +66,11 @@ impl Decoder for MapChunkBulk {
                         i << 4,
                     );
 
-                    let resp = smp::ChunkSection::decode(reader)?;
-
-                    column.sections[i as usize] = Some(resp);
+                    column.sections[i as usize] = Some(Arc::new(sections.remove(0)));
                 }
             }
 
-            // TODO: Use this
-            let mut biome = [0; 256];
-            reader.read_exact(&mut biome)?;
+            reader.read_exact(&mut column.biomes)?;
 
             columns.push(column);
         }
@@ -1,3 +1,4 @@
+mod blocks;
 mod chat_message;
 mod chunk_data;
 mod disconnect;
@@ -6,8 +7,12 @@ mod join_game;
 mod keep_alive;
 mod map_chunk_bulk;
 mod movement;
+mod player_status;
 mod sync_packets;
+mod window;
+mod world_state;
 
+pub use blocks::*;
 pub use chat_message::*;
 pub use chunk_data::ChunkData;
 pub use disconnect::*;
@@ -16,4 +21,7 @@ pub use join_game::*;
 pub use keep_alive::*;
 pub use map_chunk_bulk::{ChunkMetadata, MapChunkBulk};
 pub use movement::*;
+pub use player_status::*;
 pub use sync_packets::{PlayerPosition, PlayerPositionAndLook};
+pub use window::*;
+pub use world_state::*;
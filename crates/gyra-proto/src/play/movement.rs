@@ -1,5 +1,11 @@
 use gyra_macros::{packet, CodecDecode, CodecEncode};
 
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x03, when: Play, server)]
+pub struct Player {
+    pub on_ground: bool,
+}
+
 #[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
 #[packet(id: 0x05, when: Play, server)]
 pub struct PlayerLook {
@@ -7,3 +13,14 @@ pub struct PlayerLook {
     pub pitch: f32,
     pub on_ground: bool,
 }
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x06, when: Play, server)]
+pub struct SendPlayerPositionAndLook {
+    pub x: f64,
+    pub feet_y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
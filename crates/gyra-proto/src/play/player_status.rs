@@ -0,0 +1,40 @@
+use gyra_codec::variadic_int::VarInt;
+use gyra_macros::{packet, CodecDecode, CodecEncode};
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x06, when: Play)]
+pub struct UpdateHealth {
+    /// 0 or less is dead, 20 is full
+    pub health: f32,
+    pub food: VarInt,
+    pub food_saturation: f32,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x1F, when: Play)]
+pub struct SetExperience {
+    /// how far into the level, from 0 to 1
+    pub experience_bar: f32,
+    pub level: VarInt,
+    pub total_experience: VarInt,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x09, when: Play)]
+pub struct HeldItemChange {
+    /// hotbar slot, 0 to 8
+    pub slot: i8,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x09, when: Play, server)]
+pub struct SendHeldItemChange {
+    pub slot: i16,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x16, when: Play, server)]
+pub struct ClientStatus {
+    /// 0 respawn, 1 request stats, 2 open inventory achievement
+    pub action_id: VarInt,
+}
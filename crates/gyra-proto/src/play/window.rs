@@ -0,0 +1,167 @@
+use crate::slot::Slot;
+use gyra_codec::coding::{Decoder, Encoder};
+use gyra_macros::{packet, CodecDecode, CodecEncode};
+
+#[derive(Clone, Debug, PartialEq)]
+#[packet(id: 0x2D, when: Play)]
+pub struct OpenWindow {
+    pub window_id: u8,
+    /// like `minecraft:chest`, or `EntityHorse`
+    pub window_type: String,
+    /// chat component JSON
+    pub title: String,
+    /// slots of the container, not counting the player's inventory
+    pub slot_count: u8,
+    /// the horse whose inventory this is
+    pub entity_id: Option<i32>,
+}
+
+impl Decoder for OpenWindow {
+    fn decode<R: std::io::Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        let window_id = u8::decode(reader)?;
+        let window_type = String::decode(reader)?;
+        let title = String::decode(reader)?;
+        let slot_count = u8::decode(reader)?;
+        let entity_id = if window_type == "EntityHorse" {
+            Some(i32::decode(reader)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            window_id,
+            window_type,
+            title,
+            slot_count,
+            entity_id,
+        })
+    }
+}
+
+impl Encoder for OpenWindow {
+    fn encode<W: std::io::Write>(&self, _writer: &mut W) -> gyra_codec::error::Result<usize> {
+        unreachable!("OpenWindow is not a packet that should be sent by the client")
+    }
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x2E, when: Play)]
+pub struct CloseWindow {
+    pub window_id: u8,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x0D, when: Play, server)]
+pub struct SendCloseWindow {
+    pub window_id: u8,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x2F, when: Play)]
+pub struct SetSlot {
+    /// -1 with slot -1 sets the item held by the cursor
+    pub window_id: i8,
+    pub slot: i16,
+    pub data: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[packet(id: 0x30, when: Play)]
+pub struct WindowItems {
+    pub window_id: u8,
+    pub slots: Vec<Slot>,
+}
+
+impl Decoder for WindowItems {
+    fn decode<R: std::io::Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        let window_id = u8::decode(reader)?;
+        let count = i16::decode(reader)?.max(0);
+        let slots = (0..count)
+            .map(|_| Slot::decode(reader))
+            .collect::<gyra_codec::error::Result<_>>()?;
+
+        Ok(Self { window_id, slots })
+    }
+}
+
+impl Encoder for WindowItems {
+    fn encode<W: std::io::Write>(&self, writer: &mut W) -> gyra_codec::error::Result<usize> {
+        let mut written = self.window_id.encode(writer)?;
+        written += (self.slots.len() as i16).encode(writer)?;
+        for slot in &self.slots {
+            written += slot.encode(writer)?;
+        }
+
+        Ok(written)
+    }
+}
+
+/// Whether the server accepted a click, see `ClickWindow`.
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x32, when: Play)]
+pub struct ConfirmTransaction {
+    pub window_id: i8,
+    pub action_number: i16,
+    pub accepted: bool,
+}
+
+/// Sent back when the server refused a click, before it resends the window.
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x0F, when: Play, server)]
+pub struct SendConfirmTransaction {
+    pub window_id: i8,
+    pub action_number: i16,
+    pub accepted: bool,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x0E, when: Play, server)]
+pub struct ClickWindow {
+    pub window_id: u8,
+    /// -999 is outside the window
+    pub slot: i16,
+    pub button: i8,
+    pub action_number: i16,
+    /// 0 click, 1 shift click, 2 number key, 3 middle click, 4 drop, 5 drag, 6 double click
+    pub mode: i8,
+    /// what was in the slot before the click
+    pub clicked_item: Slot,
+}
+
+#[test]
+fn window_items_round_trip() {
+    use crate::slot::ItemStack;
+
+    let packet = WindowItems {
+        window_id: 0,
+        slots: vec![
+            Slot(None),
+            Slot(Some(ItemStack {
+                id: 1,
+                count: 64,
+                damage: 0,
+                nbt: None,
+            })),
+            Slot(None),
+        ],
+    };
+
+    let mut buffer = Vec::new();
+    packet.encode(&mut buffer).unwrap();
+
+    assert_eq!(&buffer[..3], [0, 0, 3]);
+    assert_eq!(WindowItems::decode(&mut buffer.as_slice()).unwrap(), packet);
+}
+
+#[test]
+fn horse_windows_carry_an_entity() {
+    let mut buffer = Vec::new();
+    2u8.encode(&mut buffer).unwrap();
+    "EntityHorse".to_string().encode(&mut buffer).unwrap();
+    "\"Horse\"".to_string().encode(&mut buffer).unwrap();
+    2u8.encode(&mut buffer).unwrap();
+    77i32.encode(&mut buffer).unwrap();
+
+    let packet = OpenWindow::decode(&mut buffer.as_slice()).unwrap();
+    assert_eq!(packet.entity_id, Some(77));
+}
@@ -0,0 +1,19 @@
+use gyra_macros::{packet, CodecDecode, CodecEncode};
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x03, when: Play)]
+pub struct TimeUpdate {
+    /// ticks since the world was created, not changed by commands
+    pub world_age: i64,
+    /// 0 is sunrise, 6000 noon, negative when the daylight cycle is stopped
+    pub time_of_day: i64,
+}
+
+#[derive(Clone, Debug, CodecEncode, CodecDecode, PartialEq)]
+#[packet(id: 0x2B, when: Play)]
+pub struct ChangeGameState {
+    /// 1 begins raining, 2 ends raining, 3 changes the game mode,
+    /// 7 is the rain level and 8 the thunder level
+    pub reason: u8,
+    pub value: f32,
+}
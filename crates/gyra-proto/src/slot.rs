@@ -0,0 +1,203 @@
+use gyra_codec::coding::{Decoder, Encoder};
+use gyra_codec::error::{CodecError, Result};
+use std::io::{Read, Write};
+
+/// An item with how many there are of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemStack {
+    pub id: u16,
+    pub count: u8,
+    pub damage: i16,
+    /// the NBT compound exactly as it was sent, if there is one
+    pub nbt: Option<Vec<u8>>,
+}
+
+/// An inventory slot on the wire, an id of -1 means it's empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Slot(pub Option<ItemStack>);
+
+/// How deep compounds and lists may nest, like vanilla.
+const MAX_DEPTH: usize = 512;
+
+/// Lengths come from the server, so the buffer only grows as far as the data really goes.
+fn copy<R: Read>(reader: &mut R, len: usize, out: &mut Vec<u8>) -> Result<()> {
+    let read = reader.by_ref().take(len as u64).read_to_end(out)?;
+
+    if read < len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+fn copy_length<R: Read>(reader: &mut R, out: &mut Vec<u8>) -> Result<usize> {
+    let start = out.len();
+    copy(reader, 4, out)?;
+    let len = i32::from_be_bytes(out[start..].try_into().unwrap());
+    Ok(len.max(0) as usize)
+}
+
+/// Copies the payload of one NBT tag, there's no way to know its size without walking it.
+fn copy_payload<R: Read>(reader: &mut R, tag: u8, out: &mut Vec<u8>, depth: usize) -> Result<()> {
+    if depth > MAX_DEPTH {
+        return Err(CodecError::NbtTooDeep(MAX_DEPTH));
+    }
+
+    match tag {
+        1 => copy(reader, 1, out),
+        2 => copy(reader, 2, out),
+        3 | 5 => copy(reader, 4, out),
+        4 | 6 => copy(reader, 8, out),
+        7 => {
+            let len = copy_length(reader, out)?;
+            copy(reader, len, out)
+        }
+        8 => {
+            let start = out.len();
+            copy(reader, 2, out)?;
+            let len = u16::from_be_bytes(out[start..].try_into().unwrap());
+            copy(reader, len as usize, out)
+        }
+        9 => {
+            copy(reader, 1, out)?;
+            let element = out[out.len() - 1];
+            let len = copy_length(reader, out)?;
+
+            for _ in 0..len {
+                copy_payload(reader, element, out, depth + 1)?;
+            }
+
+            Ok(())
+        }
+        10 => loop {
+            copy(reader, 1, out)?;
+            let tag = out[out.len() - 1];
+
+            if tag == 0 {
+                return Ok(());
+            }
+
+            copy_payload(reader, 8, out, depth)?;
+            copy_payload(reader, tag, out, depth + 1)?;
+        },
+        11 => {
+            let len = copy_length(reader, out)?;
+            copy(reader, len.saturating_mul(4), out)
+        }
+        tag => Err(CodecError::UnknownNbtTag(tag)),
+    }
+}
+
+impl Decoder for Slot {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        let id = i16::decode(reader)?;
+
+        if id < 0 {
+            return Ok(Slot(None));
+        }
+
+        let count = u8::decode(reader)?;
+        let damage = i16::decode(reader)?;
+
+        // a lone TAG_End when there's no compound
+        let mut nbt = vec![u8::decode(reader)?];
+        let nbt = if nbt[0] == 0 {
+            None
+        } else {
+            // the root compound has a name too
+            copy_payload(reader, 8, &mut nbt, 0)?;
+            copy_payload(reader, nbt[0], &mut nbt, 0)?;
+            Some(nbt)
+        };
+
+        Ok(Slot(Some(ItemStack {
+            id: id as u16,
+            count,
+            damage,
+            nbt,
+        })))
+    }
+}
+
+impl Encoder for Slot {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<usize> {
+        let Some(item) = &self.0 else {
+            return (-1i16).encode(writer);
+        };
+
+        let mut written = (item.id as i16).encode(writer)?;
+        written += item.count.encode(writer)?;
+        written += item.damage.encode(writer)?;
+
+        match &item.nbt {
+            Some(nbt) => {
+                writer.write_all(nbt)?;
+                written += nbt.len();
+            }
+            None => written += 0u8.encode(writer)?,
+        }
+
+        Ok(written)
+    }
+}
+
+#[test]
+fn empty_slot_is_a_negative_id() {
+    let mut buffer = Vec::new();
+    Slot::default().encode(&mut buffer).unwrap();
+    assert_eq!(buffer, [0xFF, 0xFF]);
+
+    assert_eq!(Slot::decode(&mut buffer.as_slice()).unwrap(), Slot(None));
+}
+
+#[test]
+fn slot_keeps_nbt_and_stops_after_it() {
+    #[rustfmt::skip]
+    let nbt = vec![
+        10, 0, 0, // root compound without a name
+        10, 0, 7, b'd', b'i', b's', b'p', b'l', b'a', b'y',
+        8, 0, 4, b'N', b'a', b'm', b'e', 0, 2, b'h', b'i',
+        9, 0, 4, b'L', b'o', b'r', b'e', 8, 0, 0, 0, 1, 0, 1, b'x',
+        0,
+        3, 0, 1, b'n', 0, 0, 0, 42,
+        0,
+    ];
+    let slot = Slot(Some(ItemStack {
+        id: 276,
+        count: 1,
+        damage: 3,
+        nbt: Some(nbt),
+    }));
+
+    let mut buffer = Vec::new();
+    slot.encode(&mut buffer).unwrap();
+    buffer.push(0x7F);
+
+    let mut reader = buffer.as_slice();
+    assert_eq!(Slot::decode(&mut reader).unwrap(), slot);
+    assert_eq!(reader, [0x7F]);
+}
+
+#[test]
+fn nbt_lengths_past_the_packet_are_rejected() {
+    // an int array claiming 2^31 entries with nothing after it
+    let buffer = [
+        0, 1, 1, 0, 0, 10, 0, 0, 11, 0, 1, b'a', 0x7F, 0xFF, 0xFF, 0xFF,
+    ];
+
+    let error = Slot::decode(&mut buffer.as_slice()).unwrap_err();
+    assert!(matches!(error, CodecError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn nbt_nesting_is_limited() {
+    let mut buffer = vec![0, 1, 1, 0, 0, 10, 0, 0];
+    for _ in 0..1000 {
+        // a compound holding a compound named ""
+        buffer.extend([10, 0, 0]);
+    }
+    buffer.extend([0; 1001]);
+
+    let error = Slot::decode(&mut buffer.as_slice()).unwrap_err();
+    assert!(matches!(error, CodecError::NbtTooDeep(512)));
+}
@@ -1,12 +1,14 @@
 // The Minecraft SMP Format is a format used by Minecraft to store the world data.
 
+use crate::palette::PalettedArray;
 use crate::play::ChunkMetadata;
 use gyra_codec::coding::{Decoder, Encoder};
 use gyra_codec::nibble::NibbleArray;
 use gyra_codec::variadic_int::VarInt;
 use std::io::Read;
+use std::sync::Arc;
 
-#[derive(Clone, Eq, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Eq, Copy, Debug, PartialEq, Hash, Default)]
 pub struct NetworkBlock {
     pub id: u16,      // Block ID (higher 12 bits)
     pub metadata: u8, // Metadata (lower 4 bits)
@@ -15,7 +17,7 @@ pub struct NetworkBlock {
 impl NetworkBlock {
     pub const AIR: NetworkBlock = NetworkBlock { id: 0, metadata: 0 };
 
-    fn from_u16(num: u16) -> Self {
+    pub fn from_u16(num: u16) -> Self {
         let id = num >> 4; // Extract the higher 12 bits as the block ID
         let metadata = (num & 0xF) as u8; // Extract the lower 4 bits as the metadata
         NetworkBlock { id, metadata }
@@ -35,92 +37,155 @@ pub(crate) const ARRAY_SIZE: usize = WIDTH * HEIGHT * Z_SIZE;
 
 /**
  * A chunk section is a 16x16x16 block of the world.
- * It contains the blocks, the skylight and the blocklight.
+ * It contains the blocks, the skylight and the blocklight, each one paletted so
+ * uniform sections cost next to nothing.
  */
 #[derive(Clone, Debug, PartialEq)]
 pub struct ChunkSection {
-    // blockID (4) : (4) nibble(metadata)
-    pub blocks: Vec<NetworkBlock>,
-    pub skylight: NibbleArray,
-    pub blocklight: NibbleArray,
+    blocks: PalettedArray<NetworkBlock>,
+    skylight: PalettedArray<u8>,
+    blocklight: PalettedArray<u8>,
     pub count: u32,
 }
 
 impl Default for ChunkSection {
     fn default() -> Self {
         Self {
-            blocks: vec![NetworkBlock::default(); ARRAY_SIZE],
-            skylight: NibbleArray::from_bytes(vec![0; ARRAY_SIZE / 2]),
-            blocklight: NibbleArray::from_bytes(vec![0; ARRAY_SIZE / 2]),
+            blocks: PalettedArray::filled(NetworkBlock::AIR, ARRAY_SIZE),
+            skylight: PalettedArray::filled(0, ARRAY_SIZE),
+            blocklight: PalettedArray::filled(0, ARRAY_SIZE),
             count: 0,
         }
     }
 }
 
 impl ChunkSection {
-    fn new(blocks: Vec<NetworkBlock>, skylight: NibbleArray, blocklight: NibbleArray) -> Self {
+    fn new(blocks: &[NetworkBlock], skylight: &NibbleArray, blocklight: &NibbleArray) -> Self {
         let count = blocks.iter().filter(|&b| b.id != 0).count() as u32;
+        let unpack = |nibbles: &NibbleArray| {
+            (0..ARRAY_SIZE)
+                .map(|idx| nibbles.get(idx))
+                .collect::<Vec<_>>()
+        };
+
         Self {
-            blocks,
-            skylight,
-            blocklight,
+            blocks: PalettedArray::from_slice(blocks),
+            skylight: PalettedArray::from_slice(&unpack(skylight)),
+            blocklight: PalettedArray::from_slice(&unpack(blocklight)),
             count,
         }
     }
 
-    fn recount(&mut self) {
-        self.count = self.blocks.iter().filter(|&b| b.id != 0).count() as u32;
-    }
-
     fn index(x: u16, y: u16, z: u16) -> u16 {
         ((y & 0xf) << 8) | (z << 4) | x
     }
 
+    pub fn block(&self, x: u16, y: u16, z: u16) -> NetworkBlock {
+        let index = ChunkSection::index(x, y, z) as usize;
+        if index >= ARRAY_SIZE {
+            return NetworkBlock::AIR;
+        }
+
+        self.blocks.get(index)
+    }
+
     pub fn metadata(&self, x: u16, y: u16, z: u16) -> u8 {
-        self.blocks[ChunkSection::index(x, y, z) as usize].metadata
+        self.block(x, y, z).metadata
     }
 
     pub fn block_id(&self, x: u16, y: u16, z: u16) -> u16 {
-        let index = ChunkSection::index(x, y, z) as usize;
-        if index >= self.blocks.len() {
-            return 0; // AIR
+        self.block(x, y, z).id
+    }
+
+    /// Replaces a block and returns the previous one.
+    pub fn set_block(&mut self, x: u16, y: u16, z: u16, block: NetworkBlock) -> NetworkBlock {
+        let old = self
+            .blocks
+            .set(ChunkSection::index(x, y, z) as usize, block);
+
+        match (old.id, block.id) {
+            (0, id) if id != 0 => self.count += 1,
+            (id, 0) if id != 0 => self.count -= 1,
+            _ => {}
         }
 
-        self.blocks[index].id
+        old
     }
-}
 
-impl Decoder for ChunkSection {
-    // TODO: Implement skylight
-    fn decode<R: Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
-        let mut blocks = vec![NetworkBlock::AIR; ARRAY_SIZE];
+    /// An empty section, `skylight` is the level every block of it starts with.
+    pub fn with_skylight(skylight: u8) -> Self {
+        Self {
+            skylight: PalettedArray::filled(skylight & 0xf, ARRAY_SIZE),
+            ..Default::default()
+        }
+    }
 
-        for y in 0..=15 {
-            for z in 0..=15 {
-                for x in 0..=15 {
-                    let idx = ChunkSection::index(x, y, z) as usize;
-                    let mut buff = [0; 2];
-                    reader.read_exact(&mut buff)?;
+    pub fn skylight(&self, x: u16, y: u16, z: u16) -> u8 {
+        self.skylight.get(ChunkSection::index(x, y, z) as usize)
+    }
 
-                    let num = u16::from_le_bytes(buff);
-                    let block = NetworkBlock::from_u16(num);
+    pub fn blocklight(&self, x: u16, y: u16, z: u16) -> u8 {
+        self.blocklight.get(ChunkSection::index(x, y, z) as usize)
+    }
 
-                    blocks[idx] = block;
-                }
-            }
-        }
+    /// Bytes this section owns on the heap.
+    pub fn heap_size(&self) -> usize {
+        self.blocks.heap_size() + self.skylight.heap_size() + self.blocklight.heap_size()
+    }
+}
 
-        let mut blocklight = vec![0; ARRAY_SIZE / 2];
-        reader.read_exact(&mut blocklight)?;
+fn read_blocks<R: Read>(reader: &mut R) -> gyra_codec::error::Result<Vec<NetworkBlock>> {
+    let mut buff = vec![0; ARRAY_SIZE * 2];
+    reader.read_exact(&mut buff)?;
 
-        let mut skylight = vec![0; ARRAY_SIZE / 2];
-        reader.read_exact(&mut skylight)?;
+    // already in y, z, x order
+    Ok(buff
+        .chunks_exact(2)
+        .map(|raw| NetworkBlock::from_u16(u16::from_le_bytes([raw[0], raw[1]])))
+        .collect())
+}
 
-        Ok(Self::new(
-            blocks,
-            NibbleArray::from_bytes(blocklight),
-            NibbleArray::from_bytes(skylight),
-        ))
+fn read_nibbles<R: Read>(reader: &mut R) -> gyra_codec::error::Result<NibbleArray> {
+    let mut buff = vec![0; ARRAY_SIZE / 2];
+    reader.read_exact(&mut buff)?;
+
+    Ok(NibbleArray::from_bytes(buff))
+}
+
+/// Reads the sections of one column in the 1.8 layout: every block array first, then every
+/// block light array, then every sky light array if the dimension has a sky.
+pub fn decode_sections<R: Read>(
+    reader: &mut R,
+    bitmask: u16,
+    sky_light: bool,
+) -> gyra_codec::error::Result<Vec<ChunkSection>> {
+    let count = bitmask.count_ones() as usize;
+
+    let blocks = (0..count)
+        .map(|_| read_blocks(reader))
+        .collect::<gyra_codec::error::Result<Vec<_>>>()?;
+    let blocklight = (0..count)
+        .map(|_| read_nibbles(reader))
+        .collect::<gyra_codec::error::Result<Vec<_>>>()?;
+    let skylight = if sky_light {
+        (0..count)
+            .map(|_| read_nibbles(reader))
+            .collect::<gyra_codec::error::Result<Vec<_>>>()?
+    } else {
+        vec![NibbleArray::from_bytes(vec![0; ARRAY_SIZE / 2]); count]
+    };
+
+    Ok(blocks
+        .iter()
+        .zip(blocklight.iter().zip(skylight.iter()))
+        .map(|(blocks, (blocklight, skylight))| ChunkSection::new(blocks, skylight, blocklight))
+        .collect())
+}
+
+impl Decoder for ChunkSection {
+    // a lone section with sky light, which is the same in both layouts
+    fn decode<R: Read>(reader: &mut R) -> gyra_codec::error::Result<Self> {
+        Ok(decode_sections(reader, 1, true)?.remove(0))
     }
 }
 
@@ -135,10 +200,13 @@ impl Encoder for ChunkSection {
 * It contains the sections and the biomes.
 * Did you know that the world height is limit is caused because of the chunk section counter
 * is a nibble? So it only can store 16 values.
+*
+* Sections are shared, cloning a column only bumps reference counts and a section is
+* copied the first time a shared column writes to it.
 */
 #[derive(Debug, Clone, PartialEq)]
 pub struct ChunkColumn {
-    pub sections: [Option<ChunkSection>; 16],
+    pub sections: [Option<Arc<ChunkSection>>; 16],
     pub biomes: [u8; 256],
     pub x: i32,
     pub z: i32,
@@ -149,7 +217,7 @@ impl ChunkColumn {
         for i in 0..=15 {
             if 0 != (bitmask & (1 << i)) {
                 let section = sections.remove(0);
-                column.sections[i] = Some(section);
+                column.sections[i] = Some(Arc::new(section));
             }
         }
     }
@@ -167,6 +235,20 @@ impl ChunkColumn {
         column
     }
 
+    /// Mutable access to a section, copying it first if another column shares it.
+    pub fn section_mut(&mut self, index: usize) -> Option<&mut ChunkSection> {
+        self.sections.get_mut(index)?.as_mut().map(Arc::make_mut)
+    }
+
+    /// Bytes the sections of this column own on the heap, shared ones included.
+    pub fn heap_size(&self) -> usize {
+        self.sections
+            .iter()
+            .flatten()
+            .map(|section| size_of::<ChunkSection>() + section.heap_size())
+            .sum()
+    }
+
     // Returns the world coordinates of a block in the chunk column
     pub fn block_coordinates(&self, local_x: u32, local_y: u32, local_z: u32) -> (i32, u32, i32) {
         debug_assert!(
@@ -198,14 +280,28 @@ impl ChunkColumn {
         ((start_x, start_y, start_z), (end_x, end_y, end_z))
     }
 
+    /// The section holding column-local `y`, none when it is empty or above the world.
+    pub fn section_of(&self, y: u16) -> Option<&ChunkSection> {
+        self.sections.get((y / 16) as usize)?.as_deref()
+    }
+
+    /// Block at column-local coordinates, empty sections and y past 255 are air.
+    pub fn block(&self, x: u16, y: u16, z: u16) -> NetworkBlock {
+        self.section_of(y)
+            .map_or(NetworkBlock::AIR, |section| section.block(x, y, z))
+    }
+
     pub fn block_id_of(&self, x: u16, y: u16, z: u16) -> Option<u16> {
-        let section = &self.sections[((y / 16) % 16) as usize];
-        Some(section.as_ref()?.block_id(x, y, z))
+        Some(self.section_of(y)?.block_id(x, y, z))
     }
 
     pub fn metadata_of(&self, x: u16, y: u16, z: u16) -> u8 {
-        let section = &self.sections[(y / 16) as usize];
-        section.as_ref().map_or(0, |s| s.metadata(x, y, z))
+        self.block(x, y, z).metadata
+    }
+
+    /// Biome id at column-local x and z.
+    pub fn biome(&self, x: u16, z: u16) -> u8 {
+        self.biomes[((z & 0xf) << 4 | (x & 0xf)) as usize]
     }
 }
 
@@ -250,3 +346,99 @@ fn parse_example_chunk_section() {
     let id = section.block_id(1, 1, 1);
     assert_eq!(id, 121);
 }
+
+#[test]
+fn uniform_section_is_compact() {
+    let blocks = vec![NetworkBlock::from_u16(1936); ARRAY_SIZE];
+    let light = NibbleArray::from_bytes(vec![0xff; ARRAY_SIZE / 2]);
+    let section = ChunkSection::new(
+        &blocks,
+        &light,
+        &NibbleArray::from_bytes(vec![0; ARRAY_SIZE / 2]),
+    );
+
+    assert_eq!(section.count, ARRAY_SIZE as u32);
+    assert_eq!(section.skylight(3, 4, 5), 15);
+    assert_eq!(section.blocklight(3, 4, 5), 0);
+    assert!(section.heap_size() < 64);
+}
+
+#[test]
+fn set_block_keeps_count() {
+    let mut section = ChunkSection::default();
+
+    section.set_block(1, 2, 3, NetworkBlock { id: 1, metadata: 0 });
+    section.set_block(1, 2, 3, NetworkBlock { id: 4, metadata: 0 });
+    assert_eq!(section.count, 1);
+    assert_eq!(section.block_id(1, 2, 3), 4);
+
+    section.set_block(1, 2, 3, NetworkBlock::AIR);
+    assert_eq!(section.count, 0);
+}
+
+#[test]
+fn cloned_columns_copy_on_write() {
+    let mut column = ChunkColumn::from_sections(vec![ChunkSection::default()], 1, 0, 0);
+    let shared = column.clone();
+
+    assert!(Arc::ptr_eq(
+        column.sections[0].as_ref().unwrap(),
+        shared.sections[0].as_ref().unwrap()
+    ));
+
+    column
+        .section_mut(0)
+        .unwrap()
+        .set_block(0, 0, 0, NetworkBlock { id: 1, metadata: 0 });
+
+    assert_eq!(column.block_id_of(0, 0, 0), Some(1));
+    assert_eq!(shared.block_id_of(0, 0, 0), Some(0));
+}
+
+#[test]
+fn decode_sections_reads_light_after_all_blocks() {
+    let mut data = vec![];
+
+    for raw in [1u16 << 4, 3 << 4] {
+        for _ in 0..4096 {
+            data.extend(raw.to_le_bytes());
+        }
+    }
+
+    // block light 2 and 4, then sky light 15 and 0
+    for light in [0x22u8, 0x44, 0xff, 0x00] {
+        data.extend([light; 2048]);
+    }
+
+    let mut reader = std::io::Cursor::new(data);
+    let sections = decode_sections(&mut reader, 0b101, true).unwrap();
+
+    assert_eq!(sections[0].block_id(0, 0, 0), 1);
+    assert_eq!(sections[1].block_id(15, 15, 15), 3);
+    assert_eq!(sections[0].blocklight(4, 4, 4), 2);
+    assert_eq!(sections[1].blocklight(4, 4, 4), 4);
+    assert_eq!(sections[0].skylight(4, 4, 4), 15);
+    assert_eq!(sections[1].skylight(4, 4, 4), 0);
+}
+
+#[test]
+fn column_indexing_is_consistent() {
+    let mut section = ChunkSection::default();
+    section.set_block(
+        2,
+        5,
+        7,
+        NetworkBlock {
+            id: 17,
+            metadata: 2,
+        },
+    );
+
+    let column = ChunkColumn::from_sections(vec![section], 1 << 3, 0, 0);
+
+    assert_eq!(column.block_id_of(2, 53, 7), Some(17));
+    assert_eq!(column.metadata_of(2, 53, 7), 2);
+    assert_eq!(column.block_id_of(2, 5, 7), None);
+    assert_eq!(column.block(2, 300, 7), NetworkBlock::AIR);
+    assert_eq!(column.metadata_of(2, 300, 7), 0);
+}
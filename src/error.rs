@@ -27,6 +27,15 @@ pub enum Error {
 
     #[error("Unable to serialize TOML: {0}")]
     TomlSerializeError(#[from] toml::ser::Error),
+
+    #[error("Unable to read archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+
+    #[error("Unable to decode texture: {0}")]
+    Texture(#[from] bevy::render::texture::TextureError),
 }
 
 // for any SendError in Result<T>
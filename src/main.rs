@@ -3,6 +3,7 @@ mod error;
 mod message;
 mod net;
 mod plugin;
+mod resource_pack;
 mod resources;
 mod state;
 
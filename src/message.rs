@@ -1,5 +1,6 @@
-use bevy::math::Vec3;
+use bevy::math::{DVec3, IVec3, Vec3};
 use bevy::prelude::Event;
+use gyra_proto::slot::Slot;
 use gyra_proto::{network as proto, smp};
 
 #[derive(Event, Debug)]
@@ -25,10 +26,94 @@ pub enum ServerMessage {
     },
 
     PlayerPositionAndLook {
-        position: Vec3,
+        position: DVec3,
         yaw: f32,
         pitch: f32,
+        /// which of x, y, z, yaw and pitch (bits 0 to 4) are relative to the current ones
+        relative: u8,
+    },
+
+    BlockChange {
+        position: IVec3,
+        block: smp::NetworkBlock,
+    },
+
+    OpenWindow {
+        id: u8,
+        kind: String,
+        /// chat component JSON
+        title: String,
+        slot_count: u8,
+    },
+
+    CloseWindow {
+        id: u8,
+    },
+
+    WindowItems {
+        id: u8,
+        slots: Vec<Slot>,
+    },
+
+    SetSlot {
+        /// -1 for the cursor
+        id: i8,
+        slot: i16,
+        item: Slot,
+    },
+
+    ConfirmTransaction {
+        id: i8,
+        action: i16,
+        accepted: bool,
+    },
+
+    UpdateHealth {
+        health: f32,
+        food: i32,
+        saturation: f32,
+    },
+
+    SetExperience {
+        /// progress towards the next level, from 0 to 1
+        bar: f32,
+        level: i32,
+        total: i32,
+    },
+
+    HeldItemChange {
+        slot: u8,
+    },
+
+    Respawn {
+        dimension: i32,
+        game_mode: u8,
+    },
+
+    TimeUpdate {
+        age: i64,
+        time_of_day: i64,
+    },
+
+    ChangeGameState {
+        reason: u8,
+        value: f32,
     },
+
+    Lightning {
+        position: Vec3,
+    },
+
+    TabComplete {
+        matches: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiggingStatus {
+    Started,
+    Cancelled,
+    Finished,
 }
 
 #[derive(Event)]
@@ -37,16 +122,62 @@ pub enum ClientMessage {
         message: String,
     },
 
-    Moved {
-        x: f64,
-        feet_y: f64,
-        z: f64,
+    /// Sent every tick, only what changed since the last report is set.
+    Movement {
+        /// feet
+        position: Option<DVec3>,
+        /// yaw and pitch in degrees, as Minecraft counts them
+        look: Option<(f32, f32)>,
         on_ground: bool,
     },
 
-    Look {
-        yaw: f32,
-        pitch: f32,
-        on_ground: bool,
+    Digging {
+        status: DiggingStatus,
+        position: IVec3,
+        face: u8,
+    },
+
+    PlaceBlock {
+        /// the block clicked, not where the new one goes
+        position: IVec3,
+        face: u8,
+        held_item: Slot,
+        /// where the face was clicked, from 0 to 1 inside the block
+        cursor: Vec3,
+    },
+
+    SwingArm,
+
+    ClickWindow {
+        id: u8,
+        /// -999 for outside the window
+        slot: i16,
+        button: i8,
+        action: i16,
+        mode: i8,
+        /// what was in the slot before the click
+        item: Slot,
+    },
+
+    CloseWindow {
+        id: u8,
+    },
+
+    ConfirmTransaction {
+        id: i8,
+        action: i16,
+        accepted: bool,
+    },
+
+    HeldItemChange {
+        slot: u8,
+    },
+
+    /// Asks to come back to life after dying.
+    Respawn,
+
+    TabComplete {
+        text: String,
+        looked_at: Option<IVec3>,
     },
 }
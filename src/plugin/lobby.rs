@@ -11,6 +11,9 @@ use bevy_cosmic_edit::{
 };
 use serde_json::Value;
 
+use super::chat_proto::{ChatComponent, Span};
+use super::lang::Translations;
+use super::rich_text::RichText;
 use super::{transport::NetworkTransport, ErrorFound};
 use crate::resources::DisconnectedReason;
 use crate::{
@@ -44,7 +47,7 @@ struct ServerInfoFetcher {
 enum ServerInfo {
     Disconnected,
     Found {
-        description: String,
+        description: ChatComponent,
         players: u32,
         max_players: u32,
         latency: u64,
@@ -96,6 +99,7 @@ pub fn lobby_startup(
     account: Res<PlayerAccount>,
     current_server: Res<CurrentServerAddress>,
     disconnect_reason: Option<Res<DisconnectedReason>>,
+    lang: Res<Translations>,
 ) {
     let attrs = Attrs::new().color(bevy::color::palettes::basic::GRAY.to_cosmic());
 
@@ -151,41 +155,45 @@ pub fn lobby_startup(
         })
         .with_children(|parent| {
             if let Some(reason) = disconnect_reason {
-                parent.spawn(TextBundle {
-                    style: Style {
-                        align_self: AlignSelf::Center,
-                        justify_content: JustifyContent::Center,
-                        ..default()
+                // servers send a chat component, our own errors are plain text
+                let mut spans = vec![Span {
+                    text: "Got disconnected: ".to_string(),
+                    style: default(),
+                }];
+                spans.extend(ChatComponent::parse(&reason.why).spans(&lang));
+
+                let (mut text, rich) = RichText::bundle(
+                    &spans,
+                    TextStyle {
+                        color: Color::Srgba(bevy::color::palettes::tailwind::RED_500),
+                        font_size: 24.0,
+                        ..Default::default()
                     },
-                    text: Text::from_section(
-                        format!("Got disconnected: {}", reason.why),
-                        TextStyle {
-                            color: Color::Srgba(bevy::color::palettes::tailwind::RED_500),
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                    ),
+                );
+                text.style = Style {
+                    align_self: AlignSelf::Center,
+                    justify_content: JustifyContent::Center,
                     ..default()
-                });
+                };
+
+                parent.spawn((text, rich));
             }
 
-            parent
-                .spawn(TextBundle {
-                    style: Style {
-                        align_self: AlignSelf::Center,
-                        justify_content: JustifyContent::Center,
-                        ..default()
-                    },
-                    text: Text::from_section(
-                        "Please insert a server and a nickname",
-                        TextStyle {
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                    ),
-                    ..default()
-                })
-                .insert(LobbyText);
+            let (mut text, rich) = RichText::bundle(
+                &ChatComponent::Text("Please insert a server and a nickname".to_string())
+                    .spans(&lang),
+                TextStyle {
+                    font_size: 24.0,
+                    ..Default::default()
+                },
+            );
+            text.style = Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            };
+
+            parent.spawn((text, rich)).insert(LobbyText);
 
             let input_style = Style {
                 width: Val::Percent(50.0),
@@ -246,10 +254,9 @@ fn query_status_of_server(address: &str) -> Result<ServerInfo, crate::error::Err
     let status = client.query_status()?;
     let info = serde_json::from_str::<Value>(&status.server_info)?;
     Ok(ServerInfo::Found {
-        description: info["description"]
-            .as_str()
-            .unwrap_or("No description")
-            .to_string(),
+        // either a chat component or a string with legacy codes
+        description: serde_json::from_value(info["description"].clone())
+            .unwrap_or_else(|_| ChatComponent::Text("No description".to_string())),
         players: info["players"]["online"].as_u64().unwrap_or(0) as _,
         max_players: info["players"]["max"].as_u64().unwrap_or(0) as _,
         latency: status.latency,
@@ -337,13 +344,19 @@ fn update_ids(
 }
 
 fn update_server_info(
-    mut lobby_text_query: Query<&mut Text, With<LobbyText>>,
+    mut lobby_text_query: Query<(&mut Text, &mut RichText), With<LobbyText>>,
     server_info: Res<ServerInfo>,
+    lang: Res<Translations>,
 ) {
-    for mut text in lobby_text_query.iter_mut() {
-        match *server_info {
+    if !server_info.is_changed() {
+        return;
+    }
+
+    for (mut text, mut rich) in lobby_text_query.iter_mut() {
+        let spans = match *server_info {
             ServerInfo::Disconnected => {
-                text.sections[0].value = "Please insert a valid server and a nickname".to_string();
+                ChatComponent::Text("Please insert a valid server and a nickname".to_string())
+                    .spans(&lang)
             }
             ServerInfo::Found {
                 ref description,
@@ -351,10 +364,16 @@ fn update_server_info(
                 max_players,
                 latency,
             } => {
-                text.sections[0].value =
-                    format!("{description} - {players}/{max_players} - {latency}ms");
+                let mut spans = description.spans(&lang);
+                spans.extend(
+                    ChatComponent::Text(format!(" - {players}/{max_players} - {latency}ms"))
+                        .spans(&lang),
+                );
+                spans
             }
-        }
+        };
+
+        rich.set(&mut text, &spans);
     }
 }
 
@@ -1,12 +1,19 @@
 use crate::error::Error;
-use crate::message::{ClientMessage, ServerMessage};
+use crate::message::{ClientMessage, DiggingStatus, ServerMessage};
 use crate::plugin::transport::NetworkTransport;
 use crate::resources::PlayerAccount;
 use bevy::log;
+use bevy::math::DVec3;
 use bevy::prelude::*;
 use gyra_codec::error::CodecError;
 use gyra_codec::packet::When;
-use gyra_proto::network::{PlayerLook, PlayerPosition, Proto, SendChatMessage};
+use gyra_codec::position::Position;
+use gyra_codec::variadic_int::VarInt;
+use gyra_proto::network::{
+    Animation, ClickWindow, ClientStatus, Player, PlayerBlockPlacement, PlayerDigging, PlayerLook,
+    PlayerPosition, Proto, SendChatMessage, SendCloseWindow, SendConfirmTransaction,
+    SendHeldItemChange, SendPlayerPositionAndLook, TabComplete,
+};
 use gyra_proto::smp;
 use gyra_proto::smp::ChunkColumn;
 
@@ -208,9 +215,136 @@ fn packet_handler(
 
                 Proto::PlayerPositionAndLook(look) => {
                     server_message_writer.send(ServerMessage::PlayerPositionAndLook {
-                        position: Vec3::new(look.x as _, look.y as _, look.z as _),
+                        position: DVec3::new(look.x, look.y, look.z),
                         yaw: look.yaw,
                         pitch: look.pitch,
+                        relative: look.flags,
+                    });
+                }
+
+                Proto::BlockChange(change) => {
+                    let location = change.location;
+                    server_message_writer.send(ServerMessage::BlockChange {
+                        position: IVec3::new(location.x, location.y, location.z),
+                        block: smp::NetworkBlock::from_u16(change.block_id.0 as u16),
+                    });
+                }
+
+                Proto::MultiBlockChange(change) => {
+                    let origin = IVec3::new(change.chunk_x * 16, 0, change.chunk_z * 16);
+                    let changes = change.records.iter().map(|record| {
+                        let offset = IVec3::new(
+                            (record.horizontal >> 4) as i32,
+                            record.y as i32,
+                            (record.horizontal & 15) as i32,
+                        );
+
+                        ServerMessage::BlockChange {
+                            position: origin + offset,
+                            block: smp::NetworkBlock::from_u16(record.block_id.0 as u16),
+                        }
+                    });
+
+                    server_message_writer.send_batch(changes);
+                }
+
+                Proto::OpenWindow(window) => {
+                    info!(
+                        "Opening window {}: {}",
+                        window.window_id, window.window_type
+                    );
+                    server_message_writer.send(ServerMessage::OpenWindow {
+                        id: window.window_id,
+                        kind: window.window_type.clone(),
+                        title: window.title.clone(),
+                        slot_count: window.slot_count,
+                    });
+                }
+
+                Proto::CloseWindow(window) => {
+                    server_message_writer.send(ServerMessage::CloseWindow {
+                        id: window.window_id,
+                    });
+                }
+
+                Proto::WindowItems(items) => {
+                    server_message_writer.send(ServerMessage::WindowItems {
+                        id: items.window_id,
+                        slots: items.slots.clone(),
+                    });
+                }
+
+                Proto::SetSlot(set) => {
+                    server_message_writer.send(ServerMessage::SetSlot {
+                        id: set.window_id,
+                        slot: set.slot,
+                        item: set.data.clone(),
+                    });
+                }
+
+                Proto::ConfirmTransaction(confirm) => {
+                    server_message_writer.send(ServerMessage::ConfirmTransaction {
+                        id: confirm.window_id,
+                        action: confirm.action_number,
+                        accepted: confirm.accepted,
+                    });
+                }
+
+                Proto::UpdateHealth(health) => {
+                    server_message_writer.send(ServerMessage::UpdateHealth {
+                        health: health.health,
+                        food: health.food.0,
+                        saturation: health.food_saturation,
+                    });
+                }
+
+                Proto::SetExperience(experience) => {
+                    server_message_writer.send(ServerMessage::SetExperience {
+                        bar: experience.experience_bar,
+                        level: experience.level.0,
+                        total: experience.total_experience.0,
+                    });
+                }
+
+                Proto::HeldItemChange(change) => {
+                    server_message_writer.send(ServerMessage::HeldItemChange {
+                        slot: change.slot.clamp(0, 8) as u8,
+                    });
+                }
+
+                Proto::Respawn(respawn) => {
+                    server_message_writer.send(ServerMessage::Respawn {
+                        dimension: respawn.dimension,
+                        game_mode: respawn.game_mode,
+                    });
+                }
+
+                Proto::TimeUpdate(time) => {
+                    server_message_writer.send(ServerMessage::TimeUpdate {
+                        age: time.world_age,
+                        time_of_day: time.time_of_day,
+                    });
+                }
+
+                Proto::ChangeGameState(change) => {
+                    server_message_writer.send(ServerMessage::ChangeGameState {
+                        reason: change.reason,
+                        value: change.value,
+                    });
+                }
+
+                Proto::SpawnGlobalEntity(entity) => {
+                    // thunderbolts are the only global entities
+                    if entity.kind == 1 {
+                        server_message_writer.send(ServerMessage::Lightning {
+                            position: IVec3::new(entity.x, entity.y, entity.z).as_vec3() / 32.0,
+                        });
+                    }
+                }
+
+                Proto::TabCompleteResponse(response) => {
+                    server_message_writer.send(ServerMessage::TabComplete {
+                        matches: response.matches.clone(),
                     });
                 }
 
@@ -233,13 +367,17 @@ fn packet_handler(
                         chunk_data.z * 16
                     );
 
-                    let column = ChunkColumn::from_sections(
+                    let mut column = ChunkColumn::from_sections(
                         chunk_data.sections.clone(),
                         chunk_data.primary_bit_mask,
                         chunk_data.x,
                         chunk_data.z,
                     );
 
+                    if let Some(biomes) = &chunk_data.biomes {
+                        column.biomes = **biomes;
+                    }
+
                     server_message_writer.send(ServerMessage::NewChunk { chunk: column });
                 }
                 _ => {
@@ -277,46 +415,140 @@ fn handle_client_messages(
 ) {
     for message in client_reader.read() {
         match message {
-            ClientMessage::Look {
-                yaw,
-                pitch,
+            ClientMessage::Movement {
+                position,
+                look,
                 on_ground,
             } => {
-                let look = Proto::PlayerLook(PlayerLook {
-                    yaw: *yaw,
-                    pitch: *pitch,
-                    on_ground: *on_ground,
+                let on_ground = *on_ground;
+                let packet = match (position, look) {
+                    (Some(position), Some((yaw, pitch))) => {
+                        Proto::SendPlayerPositionAndLook(SendPlayerPositionAndLook {
+                            x: position.x,
+                            feet_y: position.y,
+                            z: position.z,
+                            yaw: *yaw,
+                            pitch: *pitch,
+                            on_ground,
+                        })
+                    }
+                    (Some(position), None) => Proto::PlayerPosition(PlayerPosition {
+                        x: position.x,
+                        feet_y: position.y,
+                        z: position.z,
+                        on_ground,
+                    }),
+                    (None, Some((yaw, pitch))) => Proto::PlayerLook(PlayerLook {
+                        yaw: *yaw,
+                        pitch: *pitch,
+                        on_ground,
+                    }),
+                    (None, None) => Proto::Player(Player { on_ground }),
+                };
+
+                packet_writer.send(UploadPacket { packet });
+            }
+
+            ClientMessage::Digging {
+                status,
+                position,
+                face,
+            } => {
+                let digging = Proto::PlayerDigging(PlayerDigging {
+                    status: match status {
+                        DiggingStatus::Started => 0,
+                        DiggingStatus::Cancelled => 1,
+                        DiggingStatus::Finished => 2,
+                    },
+                    location: Position::new(position.x, position.y, position.z),
+                    face: *face,
                 });
 
-                packet_writer.send(UploadPacket { packet: look });
+                packet_writer.send(UploadPacket { packet: digging });
             }
 
-            ClientMessage::Moved {
-                x,
-                feet_y,
-                z,
-                on_ground,
+            ClientMessage::PlaceBlock {
+                position,
+                face,
+                held_item,
+                cursor,
             } => {
-                let move_packet = Proto::PlayerPosition(PlayerPosition {
-                    x: *x,
-                    feet_y: *feet_y,
-                    z: *z,
-                    on_ground: *on_ground,
+                // sixteenths of a block
+                let cursor = (*cursor * 16.0).as_uvec3();
+                let placement = Proto::PlayerBlockPlacement(PlayerBlockPlacement {
+                    location: Position::new(position.x, position.y, position.z),
+                    face: *face,
+                    held_item: held_item.clone(),
+                    cursor_x: cursor.x as u8,
+                    cursor_y: cursor.y as u8,
+                    cursor_z: cursor.z as u8,
                 });
 
+                packet_writer.send(UploadPacket { packet: placement });
+            }
+
+            ClientMessage::SwingArm => {
                 packet_writer.send(UploadPacket {
-                    packet: move_packet,
+                    packet: Proto::Animation(Animation {}),
                 });
             }
 
-            ClientMessage::ChatMessage { message } => {
-                let mut message = message.trim().to_string();
+            ClientMessage::ClickWindow {
+                id,
+                slot,
+                button,
+                action,
+                mode,
+                item,
+            } => {
+                let click = Proto::ClickWindow(ClickWindow {
+                    window_id: *id,
+                    slot: *slot,
+                    button: *button,
+                    action_number: *action,
+                    mode: *mode,
+                    clicked_item: item.clone(),
+                });
 
-                if message.len() > 100 {
-                    log::warn!("Chat message too long! Truncating to 100 characters.");
-                    message = message.chars().take(100).collect();
-                }
+                packet_writer.send(UploadPacket { packet: click });
+            }
 
+            ClientMessage::CloseWindow { id } => {
+                packet_writer.send(UploadPacket {
+                    packet: Proto::SendCloseWindow(SendCloseWindow { window_id: *id }),
+                });
+            }
+
+            ClientMessage::ConfirmTransaction {
+                id,
+                action,
+                accepted,
+            } => {
+                let confirm = Proto::SendConfirmTransaction(SendConfirmTransaction {
+                    window_id: *id,
+                    action_number: *action,
+                    accepted: *accepted,
+                });
+
+                packet_writer.send(UploadPacket { packet: confirm });
+            }
+
+            ClientMessage::HeldItemChange { slot } => {
+                packet_writer.send(UploadPacket {
+                    packet: Proto::SendHeldItemChange(SendHeldItemChange { slot: *slot as i16 }),
+                });
+            }
+
+            ClientMessage::Respawn => {
+                packet_writer.send(UploadPacket {
+                    packet: Proto::ClientStatus(ClientStatus {
+                        action_id: VarInt(0),
+                    }),
+                });
+            }
+
+            // already split to fit by the chat queue
+            ClientMessage::ChatMessage { message } => {
                 let chat_message = Proto::SendChatMessage(SendChatMessage {
                     content: message.to_owned(),
                 });
@@ -325,6 +557,16 @@ fn handle_client_messages(
                     packet: chat_message,
                 });
             }
+
+            ClientMessage::TabComplete { text, looked_at } => {
+                let request = Proto::TabComplete(TabComplete {
+                    text: text.clone(),
+                    looked_at: looked_at
+                        .map(|position| Position::new(position.x, position.y, position.z)),
+                });
+
+                packet_writer.send(UploadPacket { packet: request });
+            }
         }
     }
 }
@@ -0,0 +1,53 @@
+/// How warm and wet a biome is, from the vanilla biome list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Climate {
+    pub temperature: f32,
+    /// deserts, savannas and mesas never see rain or snow
+    pub precipitation: bool,
+}
+
+impl Climate {
+    const fn new(temperature: f32, precipitation: bool) -> Self {
+        Self {
+            temperature,
+            precipitation,
+        }
+    }
+
+    pub fn of(biome: u8) -> Self {
+        // mutated biomes are the base biome + 128 and share its climate
+        match biome & 0x7F {
+            0 | 7 | 24 => Climate::new(0.5, true),
+            1 | 6 | 16 => Climate::new(0.8, true),
+            2 | 8 | 17 | 37..=39 => Climate::new(2.0, false),
+            3 | 20 | 25 | 34 => Climate::new(0.2, true),
+            4 | 18 | 29 => Climate::new(0.7, true),
+            5 | 19 => Climate::new(0.25, true),
+            9 => Climate::new(0.5, false),
+            10..=13 => Climate::new(0.0, true),
+            14 | 15 => Climate::new(0.9, true),
+            21..=23 => Climate::new(0.95, true),
+            26 => Climate::new(0.05, true),
+            27 | 28 => Climate::new(0.6, true),
+            30 | 31 => Climate::new(-0.5, true),
+            32 | 33 => Climate::new(0.3, true),
+            35 => Climate::new(1.2, false),
+            36 => Climate::new(1.0, false),
+            _ => Climate::new(0.8, true),
+        }
+    }
+
+    /// It gets colder higher up.
+    pub fn temperature_at(&self, y: i32) -> f32 {
+        if y > 64 {
+            self.temperature - (y - 64) as f32 * 0.05 / 30.0
+        } else {
+            self.temperature
+        }
+    }
+
+    /// Whether it snows instead of rains at `y`.
+    pub fn snows_at(&self, y: i32) -> bool {
+        self.temperature_at(y) < 0.15
+    }
+}
@@ -0,0 +1,426 @@
+use crate::plugin::play::block_builder::FALLBACK_COLORS;
+use crate::resource_pack::ResourcePack;
+use crate::resources::ResourcePackLocation;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+const BLOCK_TEXTURES: &str = "assets/minecraft/textures/blocks";
+const MISSING_TEXTURE: &str = "missingno";
+
+/// Vanilla animations advance once per game tick.
+const ANIMATION_TICK: Duration = Duration::from_millis(50);
+
+/// Where every block texture lives inside the atlas, shared with the mesh tasks.
+#[derive(Debug, Default)]
+pub struct AtlasLayout {
+    tiles: HashMap<String, Rect>,
+    missing: Rect,
+}
+
+impl AtlasLayout {
+    /// UV rectangle of the texture called `name`, or of the missing texture.
+    pub fn uv(&self, name: &str) -> Rect {
+        self.tiles.get(name).copied().unwrap_or(self.missing)
+    }
+}
+
+#[derive(Resource)]
+pub struct BlockAtlas {
+    pub image: Handle<Image>,
+    pub layout: Arc<AtlasLayout>,
+    animations: Vec<AnimatedTexture>,
+}
+
+struct AnimatedTexture {
+    // pixel position of the tile inside the atlas
+    origin: UVec2,
+    tile: u32,
+    frames: Vec<Vec<u8>>,
+    // (frame index, duration in ticks)
+    schedule: Vec<(usize, u32)>,
+    interpolate: bool,
+    step: usize,
+    ticks: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct TextureMeta {
+    animation: Option<AnimationMeta>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AnimationMeta {
+    #[serde(default = "default_frame_time")]
+    frametime: u32,
+    #[serde(default)]
+    frames: Option<Vec<AnimationFrame>>,
+    #[serde(default)]
+    interpolate: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum AnimationFrame {
+    Index(usize),
+    Timed { index: usize, time: u32 },
+}
+
+fn default_frame_time() -> u32 {
+    1
+}
+
+/// A decoded texture waiting to be stitched.
+struct SourceTexture {
+    name: String,
+    size: u32,
+    // every frame is `size * size` RGBA pixels
+    frames: Vec<Vec<u8>>,
+    animation: Option<AnimationMeta>,
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, load_block_atlas)
+        .add_systems(Update, animate_block_atlas);
+}
+
+pub(crate) fn load_block_atlas(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    location: Res<ResourcePackLocation>,
+) {
+    let textures = match &location.path {
+        Some(path) => match read_pack_textures(path) {
+            Ok(textures) => textures,
+            Err(e) => {
+                error!("Could not load block textures from {path:?}: {e}");
+                warn!("Falling back to flat colours.");
+                fallback_textures()
+            }
+        },
+
+        None => {
+            info!("No resource pack configured, using flat colours.");
+            fallback_textures()
+        }
+    };
+
+    let (image, layout, animations) = stitch(textures);
+    info!(
+        "Stitched {} block textures into a {}x{} atlas",
+        layout.tiles.len(),
+        image.width(),
+        image.height()
+    );
+
+    commands.insert_resource(BlockAtlas {
+        image: images.add(image),
+        layout: Arc::new(layout),
+        animations,
+    });
+}
+
+fn read_pack_textures(path: &std::path::Path) -> crate::error::Result<Vec<SourceTexture>> {
+    let mut pack = ResourcePack::open(path)?;
+    let mut textures = vec![missing_texture()];
+
+    for file in pack.list(BLOCK_TEXTURES)? {
+        let Some(name) = file.strip_suffix(".png") else {
+            continue;
+        };
+
+        let data = pack.read(&format!("{BLOCK_TEXTURES}/{file}"))?;
+        let image = match Image::from_buffer(
+            &data,
+            ImageType::Extension("png"),
+            CompressedImageFormats::NONE,
+            true,
+            ImageSampler::nearest(),
+            RenderAssetUsages::MAIN_WORLD,
+        ) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("Skipping texture {name}: {e}");
+                continue;
+            }
+        };
+
+        if image.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb {
+            warn!("Skipping texture {name}: unsupported pixel format");
+            continue;
+        }
+
+        if image.height() < image.width() {
+            warn!("Skipping texture {name}: it is wider than tall");
+            continue;
+        }
+
+        let animation = match pack.read(&format!("{BLOCK_TEXTURES}/{file}.mcmeta")) {
+            Ok(meta) => match serde_json::from_slice::<TextureMeta>(&meta) {
+                Ok(meta) => meta.animation,
+                Err(e) => {
+                    warn!("Ignoring broken metadata of {name}: {e}");
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        // Animated textures are a vertical strip of square frames.
+        let size = image.width();
+        let frame_count = (image.height() / size).max(1) as usize;
+        let frame_bytes = (size * size * 4) as usize;
+
+        let frames = if animation.is_some() {
+            image
+                .data
+                .chunks_exact(frame_bytes)
+                .take(frame_count)
+                .map(<[u8]>::to_vec)
+                .collect()
+        } else {
+            vec![image.data[..frame_bytes.min(image.data.len())].to_vec()]
+        };
+
+        textures.push(SourceTexture {
+            name: name.to_string(),
+            size,
+            frames,
+            animation,
+        });
+    }
+
+    Ok(textures)
+}
+
+fn solid_texture(name: &str, color: [u8; 4]) -> SourceTexture {
+    SourceTexture {
+        name: name.to_string(),
+        size: 1,
+        frames: vec![color.to_vec()],
+        animation: None,
+    }
+}
+
+fn fallback_textures() -> Vec<SourceTexture> {
+    let mut textures = vec![solid_texture(MISSING_TEXTURE, [255, 255, 255, 255])];
+
+    for (name, color) in FALLBACK_COLORS {
+        textures.push(solid_texture(name, color));
+    }
+
+    textures
+}
+
+/// The magenta and black checkerboard everyone knows.
+fn missing_texture() -> SourceTexture {
+    let mut pixels = Vec::with_capacity(2 * 2 * 4);
+    for idx in 0..4 {
+        if idx == 0 || idx == 3 {
+            pixels.extend_from_slice(&[248, 0, 248, 255]);
+        } else {
+            pixels.extend_from_slice(&[0, 0, 0, 255]);
+        }
+    }
+
+    SourceTexture {
+        name: MISSING_TEXTURE.to_string(),
+        size: 2,
+        frames: vec![pixels],
+        animation: None,
+    }
+}
+
+/// Nearest-neighbour resize, for packs mixing texture resolutions.
+fn resize(pixels: &[u8], from: u32, to: u32) -> Vec<u8> {
+    if from == to {
+        return pixels.to_vec();
+    }
+
+    let mut out = Vec::with_capacity((to * to * 4) as usize);
+    for y in 0..to {
+        for x in 0..to {
+            let src = ((y * from / to) * from + x * from / to) as usize * 4;
+            out.extend_from_slice(&pixels[src..src + 4]);
+        }
+    }
+
+    out
+}
+
+fn stitch(textures: Vec<SourceTexture>) -> (Image, AtlasLayout, Vec<AnimatedTexture>) {
+    // Every tile gets the most common resolution in the pack, usually 16x16.
+    let mut sizes = HashMap::<u32, usize>::new();
+    for texture in textures.iter() {
+        *sizes.entry(texture.size).or_default() += 1;
+    }
+
+    let tile = sizes
+        .into_iter()
+        .max_by_key(|(size, count)| (*count, *size))
+        .map(|(size, _)| size)
+        .unwrap_or(16);
+
+    let columns = (textures.len() as f32).sqrt().ceil().max(1.0) as u32;
+    let side = (columns * tile).next_power_of_two();
+    let columns = side / tile;
+
+    let mut data = vec![0u8; (side * side * 4) as usize];
+    let mut layout = AtlasLayout::default();
+    let mut animations = vec![];
+
+    // A tiny inset keeps the sampler from bleeding into the neighbour tile.
+    let inset = 0.01 / side as f32;
+
+    for (idx, texture) in textures.into_iter().enumerate() {
+        let origin = UVec2::new(idx as u32 % columns, idx as u32 / columns) * tile;
+        let frames = texture
+            .frames
+            .iter()
+            .map(|frame| resize(frame, texture.size, tile))
+            .collect::<Vec<_>>();
+
+        blit(&mut data, side, origin, tile, &frames[0]);
+
+        let min = origin.as_vec2() / side as f32 + inset;
+        let max = (origin + tile).as_vec2() / side as f32 - inset;
+        let rect = Rect::from_corners(min, max);
+
+        if texture.name == MISSING_TEXTURE {
+            layout.missing = rect;
+        }
+
+        if let Some(animation) = texture.animation {
+            let frame_count = frames.len();
+            let schedule = match animation.frames {
+                Some(frames) => frames
+                    .into_iter()
+                    .map(|frame| match frame {
+                        AnimationFrame::Index(index) => (index, animation.frametime),
+                        AnimationFrame::Timed { index, time } => (index, time),
+                    })
+                    .filter(|(index, _)| *index < frame_count)
+                    .collect(),
+                None => (0..frame_count)
+                    .map(|index| (index, animation.frametime))
+                    .collect::<Vec<_>>(),
+            };
+
+            if schedule.len() > 1 {
+                animations.push(AnimatedTexture {
+                    origin,
+                    tile,
+                    frames,
+                    schedule,
+                    interpolate: animation.interpolate,
+                    step: 0,
+                    ticks: 0,
+                });
+            }
+        }
+
+        layout.tiles.insert(texture.name, rect);
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: side,
+            height: side,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+
+    (image, layout, animations)
+}
+
+fn blit(atlas: &mut [u8], side: u32, origin: UVec2, tile: u32, pixels: &[u8]) {
+    let row = (tile * 4) as usize;
+
+    for y in 0..tile {
+        let dst = (((origin.y + y) * side + origin.x) * 4) as usize;
+        let src = y as usize * row;
+        atlas[dst..dst + row].copy_from_slice(&pixels[src..src + row]);
+    }
+}
+
+fn animate_block_atlas(
+    time: Res<Time>,
+    atlas: Option<ResMut<BlockAtlas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut elapsed: Local<Duration>,
+    mut blended: Local<Vec<u8>>,
+) {
+    let Some(mut atlas) = atlas else {
+        return;
+    };
+
+    if atlas.animations.is_empty() {
+        return;
+    }
+
+    *elapsed += time.delta();
+
+    let mut ticks = 0;
+    while *elapsed >= ANIMATION_TICK {
+        *elapsed -= ANIMATION_TICK;
+        ticks += 1;
+    }
+
+    if ticks == 0 {
+        return;
+    }
+
+    let image_handle = atlas.image.clone();
+    let Some(image) = images.get_mut(&image_handle) else {
+        return;
+    };
+
+    let side = image.width();
+
+    for animation in atlas.animations.iter_mut() {
+        for _ in 0..ticks {
+            animation.ticks += 1;
+            if animation.ticks >= animation.schedule[animation.step].1.max(1) {
+                animation.ticks = 0;
+                animation.step = (animation.step + 1) % animation.schedule.len();
+            }
+        }
+
+        let tile = animation.tile;
+        let (frame, duration) = animation.schedule[animation.step];
+
+        if animation.interpolate {
+            let (next, _) = animation.schedule[(animation.step + 1) % animation.schedule.len()];
+            let t = animation.ticks as f32 / duration.max(1) as f32;
+
+            // reused between frames and animations, only the contents change
+            blended.clear();
+            blended.extend(
+                animation.frames[frame]
+                    .iter()
+                    .zip(animation.frames[next].iter())
+                    .map(|(a, b)| (*a as f32 + (*b as f32 - *a as f32) * t) as u8),
+            );
+
+            blit(&mut image.data, side, animation.origin, tile, &blended);
+        } else {
+            blit(
+                &mut image.data,
+                side,
+                animation.origin,
+                tile,
+                &animation.frames[frame],
+            );
+        }
+    }
+}
@@ -1,5 +1,9 @@
+use bevy::math::{IVec3, Vec3};
+
 pub enum Shape {
     Cube,
+    // anything drawn from a block model that doesn't fill the whole block
+    Model,
     Air,
 }
 
@@ -14,33 +18,450 @@ impl Shape {
     pub fn is_solid(&self) -> bool {
         match self {
             Shape::Cube => true,
-            Shape::Air => false,
+            Shape::Model | Shape::Air => false,
         }
     }
 }
 
-pub enum Block {
-    Air,
-    Dirt,
-    Grass,
+/// Which pass a block is drawn in, like vanilla's block layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockLayer {
+    Solid,
+    // fully opaque or fully transparent pixels, like leaves and plants
+    Cutout,
+    // blended with whatever is behind, like water and stained glass
+    Translucent,
+}
 
-    Unknown,
+/// The six faces of a block, in the same order Minecraft numbers them on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockFace {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
 }
 
-impl Block {
-    pub fn from_id(id: u16) -> Self {
-        match id {
-            0 => Block::Air,
-            1 => Block::Dirt,
-            2 => Block::Grass,
-            _ => Block::Unknown,
+impl BlockFace {
+    pub const ALL: [BlockFace; 6] = [
+        BlockFace::Down,
+        BlockFace::Up,
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::East,
+    ];
+
+    pub fn normal(&self) -> IVec3 {
+        match self {
+            BlockFace::Down => IVec3::NEG_Y,
+            BlockFace::Up => IVec3::Y,
+            BlockFace::North => IVec3::NEG_Z,
+            BlockFace::South => IVec3::Z,
+            BlockFace::West => IVec3::NEG_X,
+            BlockFace::East => IVec3::X,
         }
     }
 
-    pub fn shape(&self) -> Shape {
+    pub fn opposite(&self) -> Self {
         match self {
-            Block::Air => Shape::Air,
+            BlockFace::Down => BlockFace::Up,
+            BlockFace::Up => BlockFace::Down,
+            BlockFace::North => BlockFace::South,
+            BlockFace::South => BlockFace::North,
+            BlockFace::West => BlockFace::East,
+            BlockFace::East => BlockFace::West,
+        }
+    }
+
+    /// Parses the face names used by block models, like `north` or `up`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "down" | "bottom" => Some(BlockFace::Down),
+            "up" | "top" => Some(BlockFace::Up),
+            "north" => Some(BlockFace::North),
+            "south" => Some(BlockFace::South),
+            "west" => Some(BlockFace::West),
+            "east" => Some(BlockFace::East),
+            _ => None,
+        }
+    }
+
+    /// The face whose normal is closest to `normal`.
+    pub fn from_normal(normal: Vec3) -> Self {
+        let abs = normal.abs();
+
+        if abs.x >= abs.y && abs.x >= abs.z {
+            if normal.x < 0.0 {
+                BlockFace::West
+            } else {
+                BlockFace::East
+            }
+        } else if abs.y >= abs.z {
+            if normal.y < 0.0 {
+                BlockFace::Down
+            } else {
+                BlockFace::Up
+            }
+        } else if normal.z < 0.0 {
+            BlockFace::North
+        } else {
+            BlockFace::South
+        }
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, BlockFace::Up | BlockFace::Down)
+    }
+}
+
+const WOOL: [&str; 16] = [
+    "wool_colored_white",
+    "wool_colored_orange",
+    "wool_colored_magenta",
+    "wool_colored_light_blue",
+    "wool_colored_yellow",
+    "wool_colored_lime",
+    "wool_colored_pink",
+    "wool_colored_gray",
+    "wool_colored_silver",
+    "wool_colored_cyan",
+    "wool_colored_purple",
+    "wool_colored_blue",
+    "wool_colored_brown",
+    "wool_colored_green",
+    "wool_colored_red",
+    "wool_colored_black",
+];
+
+const STAINED_GLASS: [&str; 16] = [
+    "glass_white",
+    "glass_orange",
+    "glass_magenta",
+    "glass_light_blue",
+    "glass_yellow",
+    "glass_lime",
+    "glass_pink",
+    "glass_gray",
+    "glass_silver",
+    "glass_cyan",
+    "glass_purple",
+    "glass_blue",
+    "glass_brown",
+    "glass_green",
+    "glass_red",
+    "glass_black",
+];
+
+const STAINED_CLAY: [&str; 16] = [
+    "hardened_clay_stained_white",
+    "hardened_clay_stained_orange",
+    "hardened_clay_stained_magenta",
+    "hardened_clay_stained_light_blue",
+    "hardened_clay_stained_yellow",
+    "hardened_clay_stained_lime",
+    "hardened_clay_stained_pink",
+    "hardened_clay_stained_gray",
+    "hardened_clay_stained_silver",
+    "hardened_clay_stained_cyan",
+    "hardened_clay_stained_purple",
+    "hardened_clay_stained_blue",
+    "hardened_clay_stained_brown",
+    "hardened_clay_stained_green",
+    "hardened_clay_stained_red",
+    "hardened_clay_stained_black",
+];
+
+const STONE: [&str; 7] = [
+    "stone",
+    "stone_granite",
+    "stone_granite_smooth",
+    "stone_diorite",
+    "stone_diorite_smooth",
+    "stone_andesite",
+    "stone_andesite_smooth",
+];
+
+const PLANKS: [&str; 6] = [
+    "planks_oak",
+    "planks_spruce",
+    "planks_birch",
+    "planks_jungle",
+    "planks_acacia",
+    "planks_big_oak",
+];
+
+const LOGS: [(&str, &str); 6] = [
+    ("log_oak", "log_oak_top"),
+    ("log_spruce", "log_spruce_top"),
+    ("log_birch", "log_birch_top"),
+    ("log_jungle", "log_jungle_top"),
+    ("log_acacia", "log_acacia_top"),
+    ("log_big_oak", "log_big_oak_top"),
+];
+
+const LEAVES: [&str; 6] = [
+    "leaves_oak",
+    "leaves_spruce",
+    "leaves_birch",
+    "leaves_jungle",
+    "leaves_acacia",
+    "leaves_big_oak",
+];
+
+const STONE_BRICKS: [&str; 4] = [
+    "stonebrick",
+    "stonebrick_mossy",
+    "stonebrick_cracked",
+    "stonebrick_carved",
+];
+
+// Plains defaults, until biome colours are read from the colormaps.
+const GRASS_TINT: [f32; 4] = [0.568, 0.741, 0.349, 1.0];
+const FOLIAGE_TINT: [f32; 4] = [0.467, 0.671, 0.184, 1.0];
+const SPRUCE_TINT: [f32; 4] = [0.380, 0.600, 0.380, 1.0];
+const BIRCH_TINT: [f32; 4] = [0.502, 0.655, 0.333, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Block {
+    pub id: u16,
+    pub metadata: u8,
+}
+
+impl Block {
+    pub fn new(id: u16, metadata: u8) -> Self {
+        Self { id, metadata }
+    }
+
+    pub fn shape(&self) -> Shape {
+        match self.id {
+            0 => Shape::Air,
+            // saplings, rails, cobweb, plants and flowers
+            6 | 27 | 28 | 30 | 31 | 32 | 37 | 38 | 39 | 40 => Shape::Model,
+            // slabs
+            44 | 126 | 182 => Shape::Model,
+            // stairs
+            53 | 67 | 108 | 109 | 114 | 128 | 134 | 135 | 136 | 156 | 163 | 164 | 180 => {
+                Shape::Model
+            }
+            // torches, wheat, farmland, ladder, rail, pressure plates, snow layer
+            50 | 59 | 60 | 65 | 66 | 70 | 72 | 75 | 76 | 78 => Shape::Model,
+            // cactus, reeds, fences, trapdoor, bars, panes, vine, fence gates, lily pad
+            81 | 83 | 85 | 96 | 101 | 102 | 106 | 107 | 111 | 113 | 157 | 160 => Shape::Model,
+            // carpet, tall flowers, wooden fence gates and fences
+            171 | 175 | 183..=192 => Shape::Model,
             _ => Shape::Cube,
         }
     }
+
+    pub fn layer(&self) -> BlockLayer {
+        match self.id {
+            8 | 9 | 79 | 95 | 160 | 165 => BlockLayer::Translucent,
+            18 | 20 | 52 | 96 | 101 | 102 | 161 => BlockLayer::Cutout,
+            // non-cube shapes are mostly plants, rails and torches
+            _ if matches!(self.shape(), Shape::Model) => BlockLayer::Cutout,
+            _ => BlockLayer::Solid,
+        }
+    }
+
+    pub fn is_water(&self) -> bool {
+        matches!(self.id, 8 | 9)
+    }
+
+    /// Flowing and still water or lava.
+    pub fn is_fluid(&self) -> bool {
+        matches!(self.id, 8..=11)
+    }
+
+    pub fn is_lava(&self) -> bool {
+        matches!(self.id, 10 | 11)
+    }
+
+    /// A full block nothing can be seen through.
+    pub fn is_opaque(&self) -> bool {
+        self.shape().is_solid() && self.layer() == BlockLayer::Solid
+    }
+
+    /// Whether this block hides the face of `other` that touches it.
+    pub fn culls_face_of(&self, other: &Block) -> bool {
+        if !self.shape().is_solid() {
+            return false;
+        }
+
+        match self.layer() {
+            BlockLayer::Solid => true,
+            // leaves are drawn fancy, every face stays
+            BlockLayer::Cutout if matches!(self.id, 18 | 161) => false,
+            // water next to water, glass next to the same glass
+            _ => (self.is_water() && other.is_water()) || self == other,
+        }
+    }
+
+    /// Name of the texture in `assets/minecraft/textures/blocks` drawn on `face`.
+    pub fn texture(&self, face: BlockFace) -> &'static str {
+        let meta = self.metadata as usize;
+
+        match (self.id, face) {
+            (1, _) => STONE.get(meta).copied().unwrap_or("stone"),
+            (2, BlockFace::Up) => "grass_top",
+            (2, BlockFace::Down) => "dirt",
+            (2, _) => "grass_side",
+            (3, _) if meta == 1 => "coarse_dirt",
+            (3, BlockFace::Up) if meta == 2 => "dirt_podzol_top",
+            (3, BlockFace::Down) => "dirt",
+            (3, _) if meta == 2 => "dirt_podzol_side",
+            (3, _) => "dirt",
+            (4, _) => "cobblestone",
+            (5, _) => PLANKS.get(meta).copied().unwrap_or("planks_oak"),
+            (7, _) => "bedrock",
+            (8 | 9, _) => "water_still",
+            (10 | 11, _) => "lava_still",
+            (12, _) if meta == 1 => "red_sand",
+            (12, _) => "sand",
+            (13, _) => "gravel",
+            (14, _) => "gold_ore",
+            (15, _) => "iron_ore",
+            (16, _) => "coal_ore",
+            (17, face) => self.log_texture(&LOGS[meta & 3], face),
+            (18, _) => LEAVES[meta & 3],
+            (19, _) => "sponge",
+            (20, _) => "glass",
+            (21, _) => "lapis_ore",
+            (22, _) => "lapis_block",
+            (24, BlockFace::Up) => "sandstone_top",
+            (24, BlockFace::Down) => "sandstone_bottom",
+            (24, _) if meta == 1 => "sandstone_carved",
+            (24, _) if meta == 2 => "sandstone_smooth",
+            (24, _) => "sandstone_normal",
+            (31, _) if meta == 2 => "fern",
+            (31, _) => "tallgrass",
+            (32, _) => "deadbush",
+            (35, _) => WOOL[meta & 15],
+            (37, _) => "flower_dandelion",
+            (38, _) => "flower_rose",
+            (39, _) => "mushroom_brown",
+            (40, _) => "mushroom_red",
+            (41, _) => "gold_block",
+            (42, _) => "iron_block",
+            (43 | 44, face) if face.is_vertical() => "stone_slab_top",
+            (43 | 44, _) => "stone_slab_side",
+            (45, _) => "brick",
+            (46, BlockFace::Up) => "tnt_top",
+            (46, BlockFace::Down) => "tnt_bottom",
+            (46, _) => "tnt_side",
+            (47, face) if face.is_vertical() => "planks_oak",
+            (47, _) => "bookshelf",
+            (48, _) => "cobblestone_mossy",
+            (49, _) => "obsidian",
+            (50, _) => "torch_on",
+            (52, _) => "mob_spawner",
+            (53, _) => "planks_oak",
+            (56, _) => "diamond_ore",
+            (57, _) => "diamond_block",
+            (58, BlockFace::Up) => "crafting_table_top",
+            (58, BlockFace::Down) => "planks_oak",
+            (58, BlockFace::North | BlockFace::South) => "crafting_table_front",
+            (58, _) => "crafting_table_side",
+            (60, BlockFace::Up) => "farmland_dry",
+            (60, _) => "dirt",
+            (66, _) => "rail_normal",
+            (67, _) => "cobblestone",
+            (73 | 74, _) => "redstone_ore",
+            (78 | 80, _) => "snow",
+            (79, _) => "ice",
+            (81, BlockFace::Up) => "cactus_top",
+            (81, BlockFace::Down) => "cactus_bottom",
+            (81, _) => "cactus_side",
+            (82, _) => "clay",
+            (83, _) => "reeds",
+            (85, _) => "planks_oak",
+            (86 | 91, face) if face.is_vertical() => "pumpkin_top",
+            (86, _) => "pumpkin_side",
+            (91, _) => "pumpkin_face_on",
+            (87, _) => "netherrack",
+            (88, _) => "soul_sand",
+            (89, _) => "glowstone",
+            (95, _) => STAINED_GLASS[meta & 15],
+            (98, _) => STONE_BRICKS.get(meta).copied().unwrap_or("stonebrick"),
+            (103, face) if face.is_vertical() => "melon_top",
+            (103, _) => "melon_side",
+            (106, _) => "vine",
+            (108, _) => "brick",
+            (109, _) => "stonebrick",
+            (110, BlockFace::Up) => "mycelium_top",
+            (110, BlockFace::Down) => "dirt",
+            (110, _) => "mycelium_side",
+            (111, _) => "waterlily",
+            (112..=114, _) => "nether_brick",
+            (121, _) => "end_stone",
+            (129, _) => "emerald_ore",
+            (133, _) => "emerald_block",
+            (152, _) => "redstone_block",
+            (155, BlockFace::Up) => "quartz_block_top",
+            (155, BlockFace::Down) => "quartz_block_bottom",
+            (155, _) => "quartz_block_side",
+            (159, _) => STAINED_CLAY[meta & 15],
+            (161, _) => LEAVES[4 + (meta & 1)],
+            (162, face) => self.log_texture(&LOGS[4 + (meta & 1)], face),
+            (168, _) => "prismarine_rough",
+            (171, _) => WOOL[meta & 15],
+            (172, _) => "hardened_clay",
+            (173, _) => "coal_block",
+            (174, _) => "ice_packed",
+            _ => "missingno",
+        }
+    }
+
+    fn log_texture(
+        &self,
+        (side, top): &(&'static str, &'static str),
+        face: BlockFace,
+    ) -> &'static str {
+        // The upper two bits of the metadata hold the axis the log is lying on.
+        let on_axis = match self.metadata >> 2 {
+            0 => face.is_vertical(),
+            1 => matches!(face, BlockFace::West | BlockFace::East),
+            2 => matches!(face, BlockFace::North | BlockFace::South),
+            _ => false,
+        };
+
+        if on_axis {
+            top
+        } else {
+            side
+        }
+    }
+
+    /// The colour the texture on `face` is multiplied with, for grayscale textures such as grass.
+    pub fn tint(&self, face: BlockFace) -> Option<[f32; 4]> {
+        match (self.id, face) {
+            (2, BlockFace::Up) => Some(GRASS_TINT),
+            (31 | 106 | 111, _) => Some(GRASS_TINT),
+            (18, _) => match self.metadata & 3 {
+                1 => Some(SPRUCE_TINT),
+                2 => Some(BIRCH_TINT),
+                _ => Some(FOLIAGE_TINT),
+            },
+            (161, _) => Some(FOLIAGE_TINT),
+            _ => None,
+        }
+    }
 }
+
+/// Colours used when no resource pack is configured, so the world is still readable.
+pub const FALLBACK_COLORS: [(&str, [u8; 4]); 12] = [
+    ("grass_top", [255, 255, 255, 255]),
+    ("grass_side", [110, 150, 60, 255]),
+    ("dirt", [138, 69, 58, 255]),
+    ("stone", [150, 150, 150, 255]),
+    ("cobblestone", [150, 150, 150, 255]),
+    ("bedrock", [0, 0, 0, 255]),
+    ("water_still", [0, 0, 255, 150]),
+    ("log_oak", [139, 69, 19, 255]),
+    ("log_jungle", [139, 69, 19, 255]),
+    ("gravel", [104, 104, 104, 255]),
+    ("end_stone", [216, 214, 164, 255]),
+    ("nether_brick", [63, 42, 35, 255]),
+];
@@ -0,0 +1,272 @@
+use super::block_builder::{Block, BlockFace};
+use super::block_state::{fence_connects, pane_connects};
+use super::physics::BoundingBox;
+use bevy::math::DVec3;
+
+fn cuboid(min: [f64; 3], max: [f64; 3]) -> BoundingBox {
+    BoundingBox::new(DVec3::from_array(min), DVec3::from_array(max))
+}
+
+// a box spanning the whole block horizontally
+fn slab(min_y: f64, max_y: f64) -> BoundingBox {
+    cuboid([0.0, min_y, 0.0], [1.0, max_y, 1.0])
+}
+
+// a box inset by `inset` on every horizontal side
+fn inset(inset: f64, height: f64) -> BoundingBox {
+    cuboid([inset, 0.0, inset], [1.0 - inset, height, 1.0 - inset])
+}
+
+/// A thin panel against one side of the block, like ladders, trapdoors and doors.
+fn panel(side: BlockFace, thickness: f64, height: f64) -> BoundingBox {
+    let (min, max) = match side {
+        BlockFace::North => ([0.0, 0.0, 0.0], [1.0, height, thickness]),
+        BlockFace::South => ([0.0, 0.0, 1.0 - thickness], [1.0, height, 1.0]),
+        BlockFace::West => ([0.0, 0.0, 0.0], [thickness, height, 1.0]),
+        _ => ([1.0 - thickness, 0.0, 0.0], [1.0, height, 1.0]),
+    };
+
+    cuboid(min, max)
+}
+
+/// Boxes of a post in the middle and the arms that reach out to the connected sides.
+fn post(half: f64, height: f64, connects: impl Fn(BlockFace) -> bool) -> Vec<BoundingBox> {
+    let (min, max) = (0.5 - half, 0.5 + half);
+    let west = if connects(BlockFace::West) { 0.0 } else { min };
+    let east = if connects(BlockFace::East) { 1.0 } else { max };
+    let north = if connects(BlockFace::North) { 0.0 } else { min };
+    let south = if connects(BlockFace::South) { 1.0 } else { max };
+
+    vec![
+        cuboid([west, 0.0, min], [east, height, max]),
+        cuboid([min, 0.0, north], [max, height, south]),
+    ]
+}
+
+// horizontal facing of doors and fence gates: south, west, north, east
+fn horizontal(meta: u8) -> BlockFace {
+    [
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::North,
+        BlockFace::East,
+    ][meta as usize & 3]
+}
+
+fn rotate_clockwise(face: BlockFace) -> BlockFace {
+    match face {
+        BlockFace::North => BlockFace::East,
+        BlockFace::East => BlockFace::South,
+        BlockFace::South => BlockFace::West,
+        BlockFace::West => BlockFace::North,
+        vertical => vertical,
+    }
+}
+
+impl Block {
+    /// What entities collide with, in block-local coordinates. Fences and walls reach 1.5 up.
+    /// `neighbor` looks up the adjacent blocks, which decide how fences, panes and doors are shaped.
+    pub fn collision_boxes(&self, neighbor: impl Fn(BlockFace) -> Block) -> Vec<BoundingBox> {
+        let meta = self.metadata;
+
+        match self.id {
+            // air, plants, fluids, rails, torches, redstone, signs, buttons, plates, portals,
+            // cobweb and vines are walked through
+            0
+            | 6
+            | 8..=11
+            | 27
+            | 28
+            | 30..=32
+            | 37..=40
+            | 50
+            | 51
+            | 55
+            | 59
+            | 63
+            | 66
+            | 68
+            | 69
+            | 70
+            | 72
+            | 75
+            | 76
+            | 77
+            | 83
+            | 90
+            | 104
+            | 105
+            | 106
+            | 115
+            | 119
+            | 131
+            | 132
+            | 141
+            | 142
+            | 143
+            | 147
+            | 148
+            | 157
+            | 175..=177 => vec![],
+
+            // slabs
+            44 | 126 | 182 if meta & 8 != 0 => vec![slab(0.5, 1.0)],
+            44 | 126 | 182 => vec![slab(0.0, 0.5)],
+
+            // stairs, a slab and the raised half towards where they ascend
+            53 | 67 | 108 | 109 | 114 | 128 | 134..=136 | 156 | 163 | 164 | 180 => {
+                let (base, step) = if meta & 4 != 0 {
+                    ((0.5, 1.0), (0.0, 0.5))
+                } else {
+                    ((0.0, 0.5), (0.5, 1.0))
+                };
+                let (min, max) = match meta & 3 {
+                    0 => ([0.5, step.0, 0.0], [1.0, step.1, 1.0]),
+                    1 => ([0.0, step.0, 0.0], [0.5, step.1, 1.0]),
+                    2 => ([0.0, step.0, 0.5], [1.0, step.1, 1.0]),
+                    _ => ([0.0, step.0, 0.0], [1.0, step.1, 0.5]),
+                };
+
+                vec![slab(base.0, base.1), cuboid(min, max)]
+            }
+
+            // ladder, against the block it hangs on
+            65 => {
+                let side = match meta {
+                    2 => BlockFace::South,
+                    3 => BlockFace::North,
+                    4 => BlockFace::East,
+                    _ => BlockFace::West,
+                };
+
+                vec![panel(side, 0.125, 1.0)]
+            }
+
+            // trapdoors, flat when closed and against their hinge when open
+            96 | 167 if meta & 4 != 0 => {
+                let side = [
+                    BlockFace::South,
+                    BlockFace::North,
+                    BlockFace::East,
+                    BlockFace::West,
+                ][meta as usize & 3];
+
+                vec![panel(side, 0.1875, 1.0)]
+            }
+            96 | 167 if meta & 8 != 0 => vec![slab(0.8125, 1.0)],
+            96 | 167 => vec![slab(0.0, 0.1875)],
+
+            // doors, the facing and whether it is open are kept in the lower half,
+            // the hinge side in the upper one
+            64 | 71 | 193..=197 => {
+                let (lower, upper) = if meta & 8 != 0 {
+                    (neighbor(BlockFace::Down).metadata, meta)
+                } else {
+                    (meta, neighbor(BlockFace::Up).metadata)
+                };
+
+                let facing = rotate_clockwise(horizontal(lower).opposite());
+                let side = match (lower & 4 != 0, upper & 1 != 0) {
+                    (false, _) => facing.opposite(),
+                    // hinge on the left
+                    (true, true) => rotate_clockwise(facing),
+                    (true, false) => rotate_clockwise(facing).opposite(),
+                };
+
+                vec![panel(side, 0.1875, 1.0)]
+            }
+
+            // fence gates, closed ones are as tall as fences
+            107 | 183..=187 if meta & 4 != 0 => vec![],
+            107 | 183..=187 => match horizontal(meta) {
+                BlockFace::North | BlockFace::South => {
+                    vec![cuboid([0.0, 0.0, 0.375], [1.0, 1.5, 0.625])]
+                }
+                _ => vec![cuboid([0.375, 0.0, 0.0], [0.625, 1.5, 1.0])],
+            },
+
+            85 | 113 | 188..=192 => post(0.125, 1.5, |face| fence_connects(neighbor(face))),
+            139 => post(0.25, 1.5, |face| {
+                let other = neighbor(face);
+                other.id == 139 || fence_connects(other)
+            }),
+
+            // panes and bars on their own are a cross
+            101 | 102 | 160 => {
+                let connects = |face| pane_connects(neighbor(face));
+                let alone = [
+                    BlockFace::North,
+                    BlockFace::South,
+                    BlockFace::West,
+                    BlockFace::East,
+                ]
+                .into_iter()
+                .all(|face| !connects(face));
+
+                post(0.0625, 1.0, |face| alone || connects(face))
+            }
+
+            // snow layers, the first one is too thin to stand on
+            78 => match meta & 7 {
+                0 => vec![],
+                layers => vec![slab(0.0, layers as f64 * 0.125)],
+            },
+
+            26 => vec![slab(0.0, 0.5625)],
+            54 | 130 | 146 => vec![inset(0.0625, 0.875)],
+            60 => vec![slab(0.0, 0.9375)],
+            81 => vec![inset(0.0625, 0.9375)],
+            88 => vec![slab(0.0, 0.875)],
+            92 => vec![inset(0.0625, 0.5)],
+            93 | 94 | 149 | 150 => vec![slab(0.0, 0.125)],
+            111 => vec![slab(0.0, 0.015625)],
+            116 => vec![slab(0.0, 0.75)],
+            117 => vec![slab(0.0, 0.125), inset(0.4375, 0.875)],
+            120 => vec![slab(0.0, 0.8125)],
+            140 => vec![inset(0.3125, 0.375)],
+            144 => vec![inset(0.25, 0.5)],
+            151 | 178 => vec![slab(0.0, 0.375)],
+            171 => vec![slab(0.0, 0.0625)],
+
+            // cauldrons and hoppers are hollow
+            118 | 154 => {
+                let floor = if self.id == 118 { 0.3125 } else { 0.625 };
+                let mut boxes = vec![slab(0.0, floor)];
+                boxes.extend(
+                    [
+                        BlockFace::North,
+                        BlockFace::South,
+                        BlockFace::West,
+                        BlockFace::East,
+                    ]
+                    .map(|side| panel(side, 0.125, 1.0)),
+                );
+
+                boxes
+            }
+
+            _ => vec![slab(0.0, 1.0)],
+        }
+    }
+
+    /// Ladders and vines.
+    pub fn is_climbable(&self) -> bool {
+        matches!(self.id, 65 | 106)
+    }
+
+    /// How much of the block a fluid fills, its level is kept in the metadata.
+    pub fn fluid_height(&self) -> f64 {
+        // falling fluid is always full
+        let level = if self.metadata >= 8 { 0 } else { self.metadata };
+        1.0 - (level as f64 + 1.0) / 9.0
+    }
+
+    /// How much ground speed is kept each tick, ice is slippery.
+    pub fn slipperiness(&self) -> f64 {
+        match self.id {
+            79 | 174 => 0.98,
+            165 => 0.8,
+            _ => 0.6,
+        }
+    }
+}
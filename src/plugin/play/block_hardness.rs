@@ -0,0 +1,319 @@
+use super::block_builder::Block;
+
+/// What a block is best broken with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Sword,
+    Shears,
+}
+
+/// A held item that digs faster than a hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tool {
+    pub kind: ToolKind,
+    /// how much faster it digs the blocks it is made for
+    pub efficiency: f32,
+    /// 0 wood and gold, 1 stone, 2 iron, 3 diamond
+    pub level: u8,
+}
+
+impl Tool {
+    pub fn from_item(id: u16) -> Option<Self> {
+        // wood, stone, iron, diamond, gold
+        let (efficiency, level) = match id {
+            268..=271 => (2.0, 0),
+            272..=275 => (4.0, 1),
+            256..=258 | 267 => (6.0, 2),
+            276..=279 => (8.0, 3),
+            283..=286 => (12.0, 0),
+            359 => {
+                return Some(Tool {
+                    kind: ToolKind::Shears,
+                    efficiency: 1.0,
+                    level: 0,
+                })
+            }
+            _ => return None,
+        };
+
+        let kind = match id {
+            256 | 269 | 273 | 277 | 284 => ToolKind::Shovel,
+            257 | 270 | 274 | 278 | 285 => ToolKind::Pickaxe,
+            258 | 271 | 275 | 279 | 286 => ToolKind::Axe,
+            _ => ToolKind::Sword,
+        };
+
+        Some(Tool {
+            kind,
+            efficiency,
+            level,
+        })
+    }
+}
+
+impl Block {
+    /// Vanilla 1.8 hardness, negative for blocks that can't be broken.
+    pub fn hardness(&self) -> f32 {
+        match self.id {
+            7 | 36 | 90 | 119 | 120 | 137 | 166 => -1.0,
+            8..=11 => 100.0,
+            49 => 50.0,
+            130 => 22.5,
+            42 | 52 | 57 | 71 | 101 | 116 | 133 | 145 | 152 | 167 | 173 => 5.0,
+            30 => 4.0,
+            23 | 61 | 62 | 158 => 3.5,
+            14..=16
+            | 21
+            | 22
+            | 41
+            | 56
+            | 64
+            | 73
+            | 74
+            | 96
+            | 121
+            | 122
+            | 129
+            | 138
+            | 153
+            | 154
+            | 193..=197 => 3.0,
+            54 | 58 | 146 => 2.5,
+            4
+            | 5
+            | 17
+            | 43..=45
+            | 48
+            | 53
+            | 67
+            | 84
+            | 85
+            | 107
+            | 108
+            | 112..=114
+            | 118
+            | 125
+            | 126
+            | 134..=136
+            | 139
+            | 162..=164
+            | 181..=192 => 2.0,
+            1 | 47 | 98 | 109 | 168 => 1.5,
+            159 | 172 => 1.25,
+            63 | 68 | 86 | 91 | 103 | 144 | 176 | 177 => 1.0,
+            24 | 25 | 35 | 155 | 156 | 179 | 180 => 0.8,
+            97 => 0.75,
+            27 | 28 | 66 | 157 => 0.7,
+            2 | 13 | 19 | 60 | 82 | 110 => 0.6,
+            3 | 12 | 29 | 33 | 34 | 69 | 70 | 72 | 77 | 79 | 88 | 92 | 117 | 143 | 147 | 148
+            | 170 | 174 => 0.5,
+            65 | 81 | 87 => 0.4,
+            20 | 89 | 95 | 102 | 123 | 124 | 160 | 169 => 0.3,
+            18 | 26 | 80 | 99 | 100 | 106 | 127 | 151 | 161 | 178 => 0.2,
+            78 | 171 => 0.1,
+            _ => 0.0,
+        }
+    }
+
+    /// The tool that digs this block faster.
+    pub fn tool(&self) -> Option<ToolKind> {
+        match self.id {
+            1
+            | 4
+            | 14..=16
+            | 21..=24
+            | 27
+            | 28
+            | 41..=45
+            | 48
+            | 49
+            | 52
+            | 56
+            | 57
+            | 61
+            | 62
+            | 66
+            | 67
+            | 70
+            | 71
+            | 73
+            | 74
+            | 79
+            | 87
+            | 98
+            | 101
+            | 108
+            | 109
+            | 112..=114
+            | 116..=118
+            | 121
+            | 128..=130
+            | 133
+            | 139
+            | 145
+            | 147
+            | 148
+            | 152..=159
+            | 167
+            | 168
+            | 172..=174
+            | 179..=182 => Some(ToolKind::Pickaxe),
+            5
+            | 17
+            | 25
+            | 47
+            | 53
+            | 54
+            | 58
+            | 63
+            | 64
+            | 68
+            | 72
+            | 84..=86
+            | 91
+            | 96
+            | 99
+            | 100
+            | 103
+            | 107
+            | 125
+            | 126
+            | 127
+            | 134..=136
+            | 143
+            | 146
+            | 151
+            | 162..=164
+            | 176..=178
+            | 183..=197 => Some(ToolKind::Axe),
+            2 | 3 | 12 | 13 | 60 | 78 | 80 | 82 | 88 | 110 => Some(ToolKind::Shovel),
+            30 => Some(ToolKind::Sword),
+            18 | 35 | 161 => Some(ToolKind::Shears),
+            _ => None,
+        }
+    }
+
+    /// Blocks that only drop with the right tool of at least this level.
+    fn harvest_level(&self) -> Option<u8> {
+        match self.id {
+            49 => Some(3),
+            14 | 41 | 56 | 57 | 73 | 74 | 129 | 133 => Some(2),
+            15 | 21 | 22 | 42 => Some(1),
+            // stone, metal, snow and cobwebs
+            30 | 78 | 80 => Some(0),
+            // rails and ice come off by hand
+            27 | 28 | 66 | 79 | 157 | 174 => None,
+            _ if self.tool() == Some(ToolKind::Pickaxe) => Some(0),
+            _ => None,
+        }
+    }
+
+    fn can_harvest(&self, tool: Option<Tool>) -> bool {
+        let Some(level) = self.harvest_level() else {
+            return true;
+        };
+
+        match (self.id, tool) {
+            // cobwebs drop string with swords and shears
+            (30, Some(tool)) => matches!(tool.kind, ToolKind::Sword | ToolKind::Shears),
+            (_, Some(tool)) => Some(tool.kind) == self.tool() && tool.level >= level,
+            (_, None) => false,
+        }
+    }
+
+    fn dig_speed(&self, tool: Option<Tool>) -> f32 {
+        let Some(tool) = tool else {
+            return 1.0;
+        };
+
+        match (tool.kind, self.id) {
+            (ToolKind::Sword | ToolKind::Shears, 30) => 15.0,
+            (ToolKind::Shears, 18 | 161) => 15.0,
+            (ToolKind::Shears, 35) => 5.0,
+            // plants, leaves and gourds
+            (ToolKind::Sword, 18 | 86 | 91 | 103 | 106 | 161) => 1.5,
+            (ToolKind::Sword | ToolKind::Shears, _) => 1.0,
+            (kind, _) if Some(kind) == self.tool() => tool.efficiency,
+            _ => 1.0,
+        }
+    }
+
+    /// How much of the block is broken each tick, it breaks once this adds up to 1.
+    pub fn dig_progress(&self, held_item: Option<u16>, in_water: bool, on_ground: bool) -> f32 {
+        let hardness = self.hardness();
+        if hardness < 0.0 {
+            return 0.0;
+        }
+
+        let tool = held_item.and_then(Tool::from_item);
+        let mut speed = self.dig_speed(tool);
+
+        if in_water {
+            speed /= 5.0;
+        }
+
+        if !on_ground {
+            speed /= 5.0;
+        }
+
+        let penalty = if self.can_harvest(tool) { 30.0 } else { 100.0 };
+        speed / hardness / penalty
+    }
+}
+
+#[cfg(test)]
+fn ticks_to_break(id: u16, held_item: Option<u16>) -> f32 {
+    (1.0 / Block::new(id, 0).dig_progress(held_item, false, true)).ceil()
+}
+
+#[test]
+fn hand_digs_at_vanilla_speed() {
+    // dirt takes 0.75s, stone 7.5s as it can't be harvested by hand
+    assert_eq!(ticks_to_break(3, None), 15.0);
+    assert_eq!(ticks_to_break(1, None), 150.0);
+}
+
+#[test]
+fn tools_speed_up_their_blocks() {
+    // wooden pickaxe on stone, diamond pickaxe on obsidian
+    assert_eq!(ticks_to_break(1, Some(270)), 23.0);
+    assert_eq!(ticks_to_break(49, Some(278)), 188.0);
+
+    // a pickaxe doesn't help with dirt, a shovel does
+    assert_eq!(ticks_to_break(3, Some(270)), 15.0);
+    assert_eq!(ticks_to_break(3, Some(269)), 8.0);
+}
+
+#[test]
+fn low_tools_dig_like_a_hand() {
+    // iron ore needs a stone pickaxe to drop
+    let by_hand = ticks_to_break(15, None);
+    assert_eq!(ticks_to_break(15, Some(270)), (by_hand / 2.0).ceil());
+    assert_eq!(ticks_to_break(15, Some(274)), 23.0);
+}
+
+#[test]
+fn swords_and_shears_cut_cobwebs() {
+    assert_eq!(ticks_to_break(30, None), 400.0);
+    assert_eq!(ticks_to_break(30, Some(267)), 8.0);
+    assert_eq!(ticks_to_break(30, Some(359)), 8.0);
+}
+
+#[test]
+fn water_and_air_slow_digging() {
+    let dirt = Block::new(3, 0);
+    let normal = dirt.dig_progress(None, false, true);
+
+    assert_eq!(dirt.dig_progress(None, true, true), normal / 5.0);
+    assert_eq!(dirt.dig_progress(None, false, false), normal / 5.0);
+    assert!((dirt.dig_progress(None, true, false) - normal / 25.0).abs() < 1e-6);
+}
+
+#[test]
+fn unbreakable_and_instant_blocks() {
+    assert_eq!(Block::new(7, 0).dig_progress(Some(278), false, true), 0.0);
+    assert!(Block::new(31, 1).dig_progress(None, false, true) >= 1.0);
+}
@@ -0,0 +1,536 @@
+use super::block_atlas::{self, AtlasLayout, BlockAtlas};
+use super::block_builder::BlockFace;
+use super::block_state::BlockState;
+use crate::resource_pack::ResourcePack;
+use crate::resources::ResourcePackLocation;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use std::sync::Arc;
+
+const BLOCKSTATES: &str = "assets/minecraft/blockstates";
+const MODELS: &str = "assets/minecraft/models";
+
+/// Parent chains are short in vanilla, this only guards against loops.
+const MAX_PARENTS: usize = 16;
+
+#[derive(Deserialize, Debug)]
+struct BlockStateDefinition {
+    #[serde(default)]
+    variants: HashMap<String, VariantList>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum VariantList {
+    Single(Variant),
+    // one is picked at random for every position
+    Weighted(Vec<Variant>),
+}
+
+#[derive(Deserialize, Debug)]
+struct Variant {
+    model: String,
+    #[serde(default)]
+    x: i32,
+    #[serde(default)]
+    y: i32,
+    #[serde(default)]
+    uvlock: bool,
+    #[serde(default = "default_weight")]
+    weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct ModelDefinition {
+    parent: Option<String>,
+    #[serde(default)]
+    textures: HashMap<String, String>,
+    elements: Option<Vec<Element>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Element {
+    from: [f32; 3],
+    to: [f32; 3],
+    rotation: Option<ElementRotation>,
+    #[serde(default)]
+    faces: HashMap<String, ElementFace>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ElementRotation {
+    origin: [f32; 3],
+    axis: Axis,
+    angle: f32,
+    #[serde(default)]
+    rescale: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    fn unit(&self) -> Vec3 {
+        match self {
+            Axis::X => Vec3::X,
+            Axis::Y => Vec3::Y,
+            Axis::Z => Vec3::Z,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ElementFace {
+    uv: Option<[f32; 4]>,
+    texture: String,
+    cullface: Option<String>,
+    #[serde(default)]
+    rotation: i32,
+    tintindex: Option<i32>,
+}
+
+/// A textured quad of a model, ready to be copied into a section mesh.
+#[derive(Debug, Clone)]
+pub struct BakedQuad {
+    // bottom-left, bottom-right, top-right, top-left, in block units
+    pub vertices: [[f32; 3]; 4],
+    pub normal: [f32; 3],
+    // already inside the atlas tile
+    pub uv: [[f32; 2]; 4],
+    pub face: BlockFace,
+    // skipped when the neighbour on this side is a full block
+    pub cull: Option<BlockFace>,
+    pub tinted: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct BakedModel {
+    pub quads: Vec<BakedQuad>,
+}
+
+// variant -> (model, weight)
+type BakedVariants = HashMap<String, Vec<(Arc<BakedModel>, u32)>>;
+
+/// Every blockstate variant of the resource pack, baked against the block atlas.
+#[derive(Debug, Default)]
+pub struct BakedModels {
+    // keyed by blockstate file
+    states: HashMap<String, BakedVariants>,
+}
+
+impl BakedModels {
+    /// The model drawn for `state` at the world position `pos`.
+    pub fn get(&self, state: &BlockState, pos: IVec3) -> Option<&BakedModel> {
+        let variants = self.states.get(&state.name)?;
+        let choices = variants
+            .get(&state.variant)
+            .or_else(|| variants.get("normal"))?;
+
+        if choices.len() == 1 {
+            return Some(&choices[0].0);
+        }
+
+        let total = choices
+            .iter()
+            .map(|(_, weight)| *weight)
+            .sum::<u32>()
+            .max(1);
+        let mut pick = (position_random(pos) >> 16).unsigned_abs() as u32 % total;
+
+        for (model, weight) in choices.iter() {
+            if pick < *weight {
+                return Some(model);
+            }
+            pick -= weight;
+        }
+
+        choices.first().map(|(model, _)| model.as_ref())
+    }
+}
+
+/// The same per-position seed vanilla uses, so random variants line up with the real client.
+fn position_random(pos: IVec3) -> i64 {
+    let seed = (pos.x as i64).wrapping_mul(3129871)
+        ^ (pos.z as i64).wrapping_mul(116129781)
+        ^ pos.y as i64;
+
+    seed.wrapping_mul(seed)
+        .wrapping_mul(42317861)
+        .wrapping_add(seed.wrapping_mul(11))
+}
+
+#[derive(Resource)]
+pub struct BlockModels {
+    pub baked: Arc<BakedModels>,
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Startup,
+        load_block_models.after(block_atlas::load_block_atlas),
+    );
+}
+
+pub(crate) fn load_block_models(
+    mut commands: Commands,
+    location: Res<ResourcePackLocation>,
+    atlas: Res<BlockAtlas>,
+) {
+    let baked = match &location.path {
+        Some(path) => match read_pack_models(path, &atlas.layout) {
+            Ok(baked) => baked,
+            Err(e) => {
+                error!("Could not load block models from {path:?}: {e}");
+                warn!("Every block will be drawn as a cube.");
+                BakedModels::default()
+            }
+        },
+
+        None => BakedModels::default(),
+    };
+
+    info!("Baked models for {} block states", baked.states.len());
+
+    commands.insert_resource(BlockModels {
+        baked: Arc::new(baked),
+    });
+}
+
+fn read_pack_models(
+    path: &std::path::Path,
+    atlas: &AtlasLayout,
+) -> crate::error::Result<BakedModels> {
+    let mut loader = ModelLoader {
+        pack: ResourcePack::open(path)?,
+        definitions: HashMap::new(),
+    };
+
+    let mut baked_cache = HashMap::<(String, i32, i32, bool), Arc<BakedModel>>::new();
+    let mut baked = BakedModels::default();
+
+    for file in loader.pack.list(BLOCKSTATES)? {
+        let Some(name) = file.strip_suffix(".json") else {
+            continue;
+        };
+
+        let data = loader.pack.read(&format!("{BLOCKSTATES}/{file}"))?;
+        let definition = match serde_json::from_slice::<BlockStateDefinition>(&data) {
+            Ok(definition) => definition,
+            Err(e) => {
+                warn!("Skipping blockstate {name}: {e}");
+                continue;
+            }
+        };
+
+        let mut variants = BakedVariants::new();
+
+        for (key, list) in definition.variants {
+            let list = match list {
+                VariantList::Single(variant) => vec![variant],
+                VariantList::Weighted(list) => list,
+            };
+
+            let mut choices = vec![];
+
+            for variant in list {
+                let cache_key = (variant.model.clone(), variant.x, variant.y, variant.uvlock);
+
+                if let Some(model) = baked_cache.get(&cache_key) {
+                    choices.push((model.clone(), variant.weight));
+                    continue;
+                }
+
+                let Some(model) = loader.resolve(&block_model_name(&variant.model)) else {
+                    warn!("Blockstate {name} uses missing model {}", variant.model);
+                    continue;
+                };
+
+                let model = Arc::new(bake(&model, &variant, atlas));
+                baked_cache.insert(cache_key, model.clone());
+                choices.push((model, variant.weight));
+            }
+
+            if !choices.is_empty() {
+                variants.insert(key, choices);
+            }
+        }
+
+        baked.states.insert(name.to_string(), variants);
+    }
+
+    Ok(baked)
+}
+
+/// Blockstates name models relative to `models/block`.
+fn block_model_name(name: &str) -> String {
+    let name = name.trim_start_matches("minecraft:");
+
+    if name.contains('/') {
+        name.to_string()
+    } else {
+        format!("block/{name}")
+    }
+}
+
+struct ModelLoader {
+    pack: ResourcePack,
+    definitions: HashMap<String, Option<Arc<ModelDefinition>>>,
+}
+
+impl ModelLoader {
+    fn definition(&mut self, name: &str) -> Option<Arc<ModelDefinition>> {
+        if let Some(definition) = self.definitions.get(name) {
+            return definition.clone();
+        }
+
+        let path = format!("{MODELS}/{}.json", name.trim_start_matches("minecraft:"));
+        let definition = match self.pack.read(&path) {
+            Ok(data) => match serde_json::from_slice::<ModelDefinition>(&data) {
+                Ok(definition) => Some(Arc::new(definition)),
+                Err(e) => {
+                    warn!("Skipping model {name}: {e}");
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        self.definitions
+            .insert(name.to_string(), definition.clone());
+        definition
+    }
+
+    /// Flattens the parent chain: the closest elements win, and children override texture variables.
+    fn resolve(&mut self, name: &str) -> Option<ModelDefinition> {
+        let root = self.definition(name)?;
+
+        let mut resolved = ModelDefinition::default();
+        let mut current = Some(root);
+
+        for _ in 0..MAX_PARENTS {
+            let Some(definition) = current.take() else {
+                break;
+            };
+
+            for (key, value) in definition.textures.iter() {
+                resolved
+                    .textures
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+
+            if resolved.elements.is_none() {
+                resolved.elements = definition.elements.clone();
+            }
+
+            // builtin parents have no file, the chain simply ends there
+            current = definition
+                .parent
+                .as_deref()
+                .and_then(|parent| self.definition(parent));
+        }
+
+        Some(resolved)
+    }
+}
+
+/// Follows `#variable` references until a real texture path shows up.
+fn resolve_texture<'a>(textures: &'a HashMap<String, String>, name: &'a str) -> Option<&'a str> {
+    let mut name = name;
+
+    for _ in 0..MAX_PARENTS {
+        match name.strip_prefix('#') {
+            Some(variable) => name = textures.get(variable)?,
+            None => {
+                let name = name.trim_start_matches("minecraft:");
+                return Some(name.strip_prefix("blocks/").unwrap_or(name));
+            }
+        }
+    }
+
+    None
+}
+
+fn bake(model: &ModelDefinition, variant: &Variant, atlas: &AtlasLayout) -> BakedModel {
+    let mut quads = vec![];
+
+    // blockstate rotations go clockwise, x first
+    let variant_rotation = Quat::from_rotation_y(-(variant.y as f32).to_radians())
+        * Quat::from_rotation_x(-(variant.x as f32).to_radians());
+
+    for element in model.elements.iter().flatten() {
+        for (face_name, face) in element.faces.iter() {
+            let Some(direction) = BlockFace::from_name(face_name) else {
+                continue;
+            };
+
+            let texture = resolve_texture(&model.textures, &face.texture).unwrap_or("missingno");
+
+            quads.push(bake_face(
+                element,
+                direction,
+                face,
+                atlas.uv(texture),
+                variant_rotation,
+                variant.uvlock,
+            ));
+        }
+    }
+
+    BakedModel { quads }
+}
+
+/// Corners of one side of the element box, looking at it from outside:
+/// bottom-left, bottom-right, top-right, top-left.
+fn face_corners(face: BlockFace, from: Vec3, to: Vec3) -> [Vec3; 4] {
+    match face {
+        BlockFace::Down => [
+            Vec3::new(from.x, from.y, from.z),
+            Vec3::new(to.x, from.y, from.z),
+            Vec3::new(to.x, from.y, to.z),
+            Vec3::new(from.x, from.y, to.z),
+        ],
+        BlockFace::Up => [
+            Vec3::new(from.x, to.y, to.z),
+            Vec3::new(to.x, to.y, to.z),
+            Vec3::new(to.x, to.y, from.z),
+            Vec3::new(from.x, to.y, from.z),
+        ],
+        BlockFace::North => [
+            Vec3::new(to.x, from.y, from.z),
+            Vec3::new(from.x, from.y, from.z),
+            Vec3::new(from.x, to.y, from.z),
+            Vec3::new(to.x, to.y, from.z),
+        ],
+        BlockFace::South => [
+            Vec3::new(from.x, from.y, to.z),
+            Vec3::new(to.x, from.y, to.z),
+            Vec3::new(to.x, to.y, to.z),
+            Vec3::new(from.x, to.y, to.z),
+        ],
+        BlockFace::West => [
+            Vec3::new(from.x, from.y, from.z),
+            Vec3::new(from.x, from.y, to.z),
+            Vec3::new(from.x, to.y, to.z),
+            Vec3::new(from.x, to.y, from.z),
+        ],
+        BlockFace::East => [
+            Vec3::new(to.x, from.y, to.z),
+            Vec3::new(to.x, from.y, from.z),
+            Vec3::new(to.x, to.y, from.z),
+            Vec3::new(to.x, to.y, to.z),
+        ],
+    }
+}
+
+/// Where a point on a face lands on its texture, in the 0..16 space models use.
+/// Faces without an explicit `uv` and uvlocked faces are textured this way.
+fn project_uv(face: BlockFace, pos: Vec3) -> Vec2 {
+    match face {
+        BlockFace::Down => Vec2::new(pos.x, 16.0 - pos.z),
+        BlockFace::Up => Vec2::new(pos.x, pos.z),
+        BlockFace::North => Vec2::new(16.0 - pos.x, 16.0 - pos.y),
+        BlockFace::South => Vec2::new(pos.x, 16.0 - pos.y),
+        BlockFace::West => Vec2::new(pos.z, 16.0 - pos.y),
+        BlockFace::East => Vec2::new(16.0 - pos.z, 16.0 - pos.y),
+    }
+}
+
+fn bake_face(
+    element: &Element,
+    direction: BlockFace,
+    face: &ElementFace,
+    tile: Rect,
+    variant_rotation: Quat,
+    uvlock: bool,
+) -> BakedQuad {
+    let from = Vec3::from(element.from);
+    let to = Vec3::from(element.to);
+    let corners = face_corners(direction, from, to);
+
+    // texture corners, clockwise from the top-left
+    let [u1, v1, u2, v2] = face.uv.unwrap_or_else(|| {
+        let a = project_uv(direction, corners[3]);
+        let b = project_uv(direction, corners[1]);
+        [a.x, a.y, b.x, b.y]
+    });
+    let clockwise = [
+        Vec2::new(u1, v1),
+        Vec2::new(u2, v1),
+        Vec2::new(u2, v2),
+        Vec2::new(u1, v2),
+    ];
+    let steps = (face.rotation / 90).rem_euclid(4) as usize;
+    let texture_corner = |idx: usize| clockwise[(idx + 4 - steps) % 4];
+    let mut uvs = [
+        texture_corner(3),
+        texture_corner(2),
+        texture_corner(1),
+        texture_corner(0),
+    ];
+
+    // element rotation, around its own origin
+    let mut normal = direction.normal().as_vec3();
+    let mut vertices = corners;
+
+    if let Some(rotation) = &element.rotation {
+        let origin = Vec3::from(rotation.origin);
+        let axis = rotation.axis.unit();
+        let quat = Quat::from_axis_angle(axis, rotation.angle.to_radians());
+
+        // rescaling stretches the element back to the full block width
+        let scale = if rotation.rescale {
+            let factor = 1.0 / rotation.angle.to_radians().cos();
+            Vec3::ONE + (Vec3::ONE - axis) * (factor - 1.0)
+        } else {
+            Vec3::ONE
+        };
+
+        for vertex in vertices.iter_mut() {
+            *vertex = origin + (quat * (*vertex - origin)) * scale;
+        }
+
+        normal = quat * normal;
+    }
+
+    // blockstate rotation, around the block centre
+    let center = Vec3::splat(8.0);
+    for vertex in vertices.iter_mut() {
+        *vertex = center + variant_rotation * (*vertex - center);
+    }
+    normal = (variant_rotation * normal).normalize_or_zero();
+
+    let face_after = BlockFace::from_normal(normal);
+
+    // uvlock keeps the texture aligned to the world instead of spinning with the model
+    if uvlock && element.rotation.is_none() {
+        for (uv, vertex) in uvs.iter_mut().zip(vertices.iter()) {
+            *uv = project_uv(face_after, *vertex);
+        }
+    }
+
+    let cull = face
+        .cullface
+        .as_deref()
+        .and_then(BlockFace::from_name)
+        .map(|cull| BlockFace::from_normal(variant_rotation * cull.normal().as_vec3()));
+
+    BakedQuad {
+        vertices: vertices.map(|vertex| (vertex / 16.0).to_array()),
+        normal: normal.to_array(),
+        uv: uvs.map(|uv| (tile.min + uv / 16.0 * tile.size()).to_array()),
+        face: face_after,
+        cull,
+        tinted: face.tintindex.is_some(),
+    }
+}
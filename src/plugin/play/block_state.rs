@@ -0,0 +1,418 @@
+use super::block_builder::{Block, BlockFace};
+
+/// Which file in `assets/minecraft/blockstates` describes a block, and which of its variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockState {
+    pub name: String,
+    // properties sorted by name, like `facing=east,half=bottom,shape=straight`
+    pub variant: String,
+}
+
+impl BlockState {
+    fn new(name: impl Into<String>, variant: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            variant: variant.into(),
+        }
+    }
+
+    fn normal(name: impl Into<String>) -> Self {
+        Self::new(name, "normal")
+    }
+}
+
+const WOOD: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+
+const COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "silver",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+const STONE_SLABS: [&str; 8] = [
+    "stone",
+    "sandstone",
+    "wood_old",
+    "cobblestone",
+    "brick",
+    "stone_brick",
+    "nether_brick",
+    "quartz",
+];
+
+const FLOWERS: [&str; 9] = [
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "houstonia",
+    "red_tulip",
+    "orange_tulip",
+    "white_tulip",
+    "pink_tulip",
+    "oxeye_daisy",
+];
+
+const DOUBLE_PLANTS: [&str; 6] = [
+    "sunflower",
+    "syringa",
+    "double_grass",
+    "double_fern",
+    "double_rose",
+    "paeonia",
+];
+
+const RAIL_SHAPES: [&str; 10] = [
+    "north_south",
+    "east_west",
+    "ascending_east",
+    "ascending_west",
+    "ascending_north",
+    "ascending_south",
+    "south_east",
+    "south_west",
+    "north_west",
+    "north_east",
+];
+
+impl Block {
+    /// The blockstate this block is drawn with, if it is drawn from a model.
+    /// `neighbor` looks up the adjacent blocks, which decide how fences, panes and vines connect.
+    pub fn state(&self, neighbor: impl Fn(BlockFace) -> Block) -> Option<BlockState> {
+        let meta = self.metadata as usize;
+
+        let state = match self.id {
+            6 => BlockState::new(
+                format!("{}_sapling", WOOD.get(meta & 7)?),
+                format!("stage={}", meta >> 3),
+            ),
+            27 | 28 | 157 => {
+                let name = match self.id {
+                    27 => "golden_rail",
+                    28 => "detector_rail",
+                    _ => "activator_rail",
+                };
+
+                BlockState::new(
+                    name,
+                    format!(
+                        "powered={},shape={}",
+                        meta & 8 != 0,
+                        RAIL_SHAPES.get(meta & 7)?
+                    ),
+                )
+            }
+            30 => BlockState::normal("web"),
+            31 => BlockState::normal(match meta {
+                0 => "dead_bush",
+                2 => "fern",
+                _ => "tall_grass",
+            }),
+            32 => BlockState::normal("dead_bush"),
+            37 => BlockState::normal("dandelion"),
+            38 => BlockState::normal(*FLOWERS.get(meta)?),
+            39 => BlockState::normal("brown_mushroom"),
+            40 => BlockState::normal("red_mushroom"),
+            44 => BlockState::new(format!("{}_slab", STONE_SLABS[meta & 7]), half(meta)),
+            126 => BlockState::new(format!("{}_slab", WOOD.get(meta & 7)?), half(meta)),
+            182 => BlockState::new("red_sandstone_slab", half(meta)),
+            50 | 75 | 76 => {
+                let name = match self.id {
+                    50 => "torch",
+                    75 => "unlit_redstone_torch",
+                    _ => "redstone_torch",
+                };
+
+                let facing = match meta {
+                    1 => "east",
+                    2 => "west",
+                    3 => "south",
+                    4 => "north",
+                    _ => "up",
+                };
+
+                BlockState::new(name, format!("facing={facing}"))
+            }
+            53 | 67 | 108 | 109 | 114 | 128 | 134 | 135 | 136 | 156 | 163 | 164 | 180 => {
+                let name = match self.id {
+                    53 => "oak_stairs",
+                    67 => "stone_stairs",
+                    108 => "brick_stairs",
+                    109 => "stone_brick_stairs",
+                    114 => "nether_brick_stairs",
+                    128 => "sandstone_stairs",
+                    134 => "spruce_stairs",
+                    135 => "birch_stairs",
+                    136 => "jungle_stairs",
+                    156 => "quartz_stairs",
+                    163 => "acacia_stairs",
+                    164 => "dark_oak_stairs",
+                    _ => "red_sandstone_stairs",
+                };
+
+                let (facing, top) = stairs(*self)?;
+                let half = if top { "top" } else { "bottom" };
+
+                BlockState::new(
+                    name,
+                    format!(
+                        "facing={},half={half},shape={}",
+                        horizontal_name(facing),
+                        stairs_shape(*self, &neighbor)?,
+                    ),
+                )
+            }
+            59 => BlockState::new("wheat", format!("age={}", meta & 7)),
+            60 => BlockState::new("farmland", format!("moisture={}", meta & 7)),
+            65 => BlockState::new("ladder", format!("facing={}", facing_of(meta)?)),
+            66 => BlockState::new("rail", format!("shape={}", RAIL_SHAPES.get(meta)?)),
+            70 => BlockState::new("stone_pressure_plate", powered(meta)),
+            72 => BlockState::new("wooden_pressure_plate", powered(meta)),
+            78 => BlockState::new("snow_layer", format!("layers={}", (meta & 7) + 1)),
+            81 => BlockState::normal("cactus"),
+            83 => BlockState::normal("reeds"),
+            85 | 113 | 188..=192 => {
+                let name = match self.id {
+                    85 => "fence",
+                    113 => "nether_brick_fence",
+                    188 => "spruce_fence",
+                    189 => "birch_fence",
+                    190 => "jungle_fence",
+                    191 => "dark_oak_fence",
+                    _ => "acacia_fence",
+                };
+
+                BlockState::new(name, connections(|face| fence_connects(neighbor(face))))
+            }
+            96 => {
+                let facing = ["north", "south", "west", "east"][meta & 3];
+                let half = if meta & 8 != 0 { "top" } else { "bottom" };
+
+                BlockState::new(
+                    "trapdoor",
+                    format!("facing={facing},half={half},open={}", meta & 4 != 0),
+                )
+            }
+            101 | 102 | 160 => {
+                let name = match self.id {
+                    101 => "iron_bars".to_string(),
+                    102 => "glass_pane".to_string(),
+                    _ => format!("{}_stained_glass_pane", COLORS[meta & 15]),
+                };
+
+                BlockState::new(name, connections(|face| pane_connects(neighbor(face))))
+            }
+            106 => {
+                let up = neighbor(BlockFace::Up).shape().is_solid();
+
+                BlockState::new(
+                    "vine",
+                    format!(
+                        "east={},north={},south={},up={up},west={}",
+                        meta & 8 != 0,
+                        meta & 4 != 0,
+                        meta & 1 != 0,
+                        meta & 2 != 0,
+                    ),
+                )
+            }
+            107 | 183..=187 => {
+                let name = match self.id {
+                    107 => "fence_gate",
+                    183 => "spruce_fence_gate",
+                    184 => "birch_fence_gate",
+                    185 => "jungle_fence_gate",
+                    186 => "dark_oak_fence_gate",
+                    _ => "acacia_fence_gate",
+                };
+
+                let facing = ["south", "west", "north", "east"][meta & 3];
+
+                BlockState::new(
+                    name,
+                    format!("facing={facing},in_wall=false,open={}", meta & 4 != 0),
+                )
+            }
+            111 => BlockState::normal("waterlily"),
+            171 => BlockState::normal(format!("{}_carpet", COLORS[meta & 15])),
+            175 => {
+                // only the lower half knows which plant this is
+                if meta & 8 != 0 {
+                    let lower = neighbor(BlockFace::Down);
+                    let variant = match lower.id {
+                        175 => DOUBLE_PLANTS.get(lower.metadata as usize & 7)?,
+                        _ => &DOUBLE_PLANTS[0],
+                    };
+
+                    BlockState::new(*variant, "half=upper")
+                } else {
+                    BlockState::new(*DOUBLE_PLANTS.get(meta & 7)?, "half=lower")
+                }
+            }
+            _ => return None,
+        };
+
+        Some(state)
+    }
+}
+
+fn half(meta: usize) -> &'static str {
+    if meta & 8 != 0 {
+        "half=top"
+    } else {
+        "half=bottom"
+    }
+}
+
+fn powered(meta: usize) -> &'static str {
+    if meta & 1 != 0 {
+        "powered=true"
+    } else {
+        "powered=false"
+    }
+}
+
+fn facing_of(meta: usize) -> Option<&'static str> {
+    match meta {
+        2 => Some("north"),
+        3 => Some("south"),
+        4 => Some("west"),
+        5 => Some("east"),
+        _ => None,
+    }
+}
+
+/// Which way a stair's full side faces and whether it is upside down, if the block is a stair.
+fn stairs(block: Block) -> Option<(BlockFace, bool)> {
+    if !matches!(
+        block.id,
+        53 | 67 | 108 | 109 | 114 | 128 | 134 | 135 | 136 | 156 | 163 | 164 | 180
+    ) {
+        return None;
+    }
+
+    let facing = [
+        BlockFace::East,
+        BlockFace::West,
+        BlockFace::South,
+        BlockFace::North,
+    ][block.metadata as usize & 3];
+
+    Some((facing, block.metadata & 4 != 0))
+}
+
+/// Turned a quarter counterclockwise, seen from above.
+fn rotate_left(face: BlockFace) -> BlockFace {
+    match face {
+        BlockFace::North => BlockFace::West,
+        BlockFace::West => BlockFace::South,
+        BlockFace::South => BlockFace::East,
+        BlockFace::East => BlockFace::North,
+        vertical => vertical,
+    }
+}
+
+fn horizontal_name(face: BlockFace) -> &'static str {
+    match face {
+        BlockFace::North => "north",
+        BlockFace::South => "south",
+        BlockFace::West => "west",
+        _ => "east",
+    }
+}
+
+/// Corners form where stairs meet at a right angle, like vanilla's `BlockStairs.getActualState`.
+fn stairs_shape(block: Block, neighbor: impl Fn(BlockFace) -> Block) -> Option<&'static str> {
+    let (facing, top) = stairs(block)?;
+    let sideways = |other: BlockFace| other != facing && other != facing.opposite();
+
+    // a stair on the side doesn't continue this one in a straight line
+    let breaks_line = |side: BlockFace| stairs(neighbor(side)) != Some((facing, top));
+
+    // a stair behind, turned sideways, cuts the top step down to a corner
+    if let Some((back, back_top)) = stairs(neighbor(facing)) {
+        if back_top == top && sideways(back) && breaks_line(back.opposite()) {
+            return Some(if back == rotate_left(facing) {
+                "outer_left"
+            } else {
+                "outer_right"
+            });
+        }
+    }
+
+    // a stair in front, turned sideways, fills the corner next to the bottom step
+    if let Some((front, front_top)) = stairs(neighbor(facing.opposite())) {
+        if front_top == top && sideways(front) && breaks_line(front) {
+            return Some(if front == rotate_left(facing) {
+                "inner_left"
+            } else {
+                "inner_right"
+            });
+        }
+    }
+
+    Some("straight")
+}
+
+fn connections(connects: impl Fn(BlockFace) -> bool) -> String {
+    format!(
+        "east={},north={},south={},west={}",
+        connects(BlockFace::East),
+        connects(BlockFace::North),
+        connects(BlockFace::South),
+        connects(BlockFace::West),
+    )
+}
+
+pub(super) fn fence_connects(block: Block) -> bool {
+    matches!(block.id, 85 | 107 | 113 | 183..=192) || block.shape().is_solid()
+}
+
+pub(super) fn pane_connects(block: Block) -> bool {
+    matches!(block.id, 20 | 95 | 101 | 102 | 160) || block.shape().is_solid()
+}
+
+#[test]
+fn stairs_turn_corners_next_to_sideways_stairs() {
+    let east = Block::new(53, 0);
+    let north = Block::new(53, 3);
+    let shape = |neighbors: &[(BlockFace, Block)]| {
+        let neighbor = |face| {
+            neighbors
+                .iter()
+                .find(|(side, _)| *side == face)
+                .map_or(Block::new(0, 0), |(_, block)| *block)
+        };
+
+        stairs_shape(east, neighbor).unwrap()
+    };
+
+    assert_eq!(shape(&[]), "straight");
+    assert_eq!(shape(&[(BlockFace::East, north)]), "outer_left");
+    assert_eq!(shape(&[(BlockFace::West, north)]), "inner_left");
+    assert_eq!(
+        shape(&[(BlockFace::East, Block::new(53, 2))]),
+        "outer_right"
+    );
+
+    // stairs in a row stay straight even with a corner next to them
+    assert_eq!(
+        shape(&[(BlockFace::East, north), (BlockFace::South, east)]),
+        "straight"
+    );
+
+    // an upside down stair doesn't turn a bottom one
+    assert_eq!(shape(&[(BlockFace::East, Block::new(53, 7))]), "straight");
+}
@@ -0,0 +1,33 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+/// A grey labelled button, the death screen and the link dialog use these.
+pub fn spawn<'a>(parent: &'a mut ChildBuilder, label: &str, width: f32) -> EntityCommands<'a> {
+    let mut button = parent.spawn(ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(40.0),
+            border: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        border_color: BorderColor(Color::BLACK),
+        border_radius: BorderRadius::all(Val::Px(5.0)),
+        background_color: Color::srgb(0.3, 0.3, 0.3).into(),
+        ..default()
+    });
+
+    button.with_children(|p| {
+        p.spawn(TextBundle::from_section(
+            label,
+            TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+    });
+
+    button
+}
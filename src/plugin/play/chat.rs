@@ -1,5 +1,11 @@
-use crate::plugin::play::chat_proto;
+use crate::plugin::play::chat_history::{ChatScroll, InputHistory};
+use crate::plugin::play::chat_proto::{self, ChatColor, Span, SpanStyle};
+use crate::plugin::play::chat_queue::OutgoingChat;
+use crate::plugin::play::lang;
+use crate::plugin::play::rich_text::RichText;
+use crate::plugin::CursorState;
 use crate::state::AppState;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_cosmic_edit::*;
 use std::time::Duration;
@@ -17,15 +23,35 @@ pub struct ChatMessage {
 #[derive(Component, Debug)]
 pub struct ChatComponent;
 
+/// Messages that scrolled off are kept up to this many.
+const SCROLLBACK: usize = 100;
+
 #[derive(Resource, Debug)]
 pub struct ChatMaxLines {
     pub max_lines: usize,
+    /// lines shown while the chat is open
+    pub open_lines: usize,
 }
 
 #[derive(Resource, Debug)]
 pub struct ChatBuffer {
-    pub buffer: Vec<String>,
-    pub shown_buffer: Vec<String>,
+    pub buffer: Vec<Vec<Span>>,
+    pub shown_buffer: Vec<Vec<Span>>,
+}
+
+impl ChatBuffer {
+    /// Moves the oldest shown line to the scrollback.
+    fn archive_oldest(&mut self) {
+        if self.shown_buffer.is_empty() {
+            return;
+        }
+
+        let line = self.shown_buffer.remove(0);
+        self.buffer.push(line);
+
+        let extra = self.buffer.len().saturating_sub(SCROLLBACK);
+        self.buffer.drain(..extra);
+    }
 }
 
 #[derive(Component)]
@@ -39,6 +65,30 @@ pub struct ChatInputText;
 #[derive(Component)]
 pub struct ChatInputUI;
 
+/// Whether the chat input is showing, everything that acts differently while typing asks this.
+#[derive(SystemParam)]
+pub struct ChatOpen<'w, 's> {
+    input: Query<'w, 's, Ref<'static, Visibility>, With<ChatInputUI>>,
+}
+
+impl ChatOpen<'_, '_> {
+    pub fn is_open(&self) -> bool {
+        self.input
+            .iter()
+            .any(|visibility| *visibility == Visibility::Visible)
+    }
+
+    /// Opened or closed since the system last ran.
+    pub fn toggled(&self) -> bool {
+        self.input.iter().any(|visibility| visibility.is_changed())
+    }
+}
+
+/// Run condition for systems that only do something while the chat is open.
+pub fn chat_open(chat_open: ChatOpen) -> bool {
+    chat_open.is_open()
+}
+
 pub fn spawn_renderer(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -55,7 +105,8 @@ pub fn spawn_renderer(
         .spawn(CosmicEditBundle {
             default_attrs: DefaultAttrs(AttrsOwned::new(attrs)),
             max_lines: MaxLines(1),
-            max_chars: MaxChars(100),
+            // longer messages go out in several packets
+            max_chars: MaxChars(256),
             fill_color: CosmicBackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
             cursor_color: CursorColor(Color::WHITE),
             text_position: CosmicTextAlign::Left { padding: 12 },
@@ -74,10 +125,14 @@ pub fn spawn_renderer(
     commands
         .spawn(NodeBundle {
             background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            // like vanilla, the input is at the bottom left with the messages above it
             style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                left: Val::Px(0.0),
                 width: Val::Percent(30.0),
                 max_height: Val::Percent(50.0),
-                flex_direction: FlexDirection::Column,
+                flex_direction: FlexDirection::ColumnReverse,
                 justify_content: JustifyContent::FlexStart,
                 ..default()
             },
@@ -114,13 +169,21 @@ pub fn spawn_renderer(
             })
             .insert(ChatEditor {
                 timer: Timer::new(Duration::from_secs(5), TimerMode::Repeating),
-            });
+            })
+            .insert(RichText::new(TextStyle {
+                font: chat_font,
+                font_size: 20.0,
+                color: Color::WHITE,
+            }));
         })
         .insert(ChatComponent);
 }
 
 fn startup(mut commands: Commands) {
-    commands.insert_resource(ChatMaxLines { max_lines: 10 });
+    commands.insert_resource(ChatMaxLines {
+        max_lines: 10,
+        open_lines: 20,
+    });
 
     commands.insert_resource(ChatBuffer {
         buffer: Vec::new(),
@@ -128,14 +191,28 @@ fn startup(mut commands: Commands) {
     });
 }
 
+/// The chat input box and the editor inside it.
+#[derive(SystemParam)]
+struct ChatInput<'w, 's> {
+    container: Query<'w, 's, (&'static mut Visibility, &'static mut Style), With<ChatInputUI>>,
+    editor: Query<'w, 's, &'static mut CosmicEditor, With<ChatInputText>>,
+    source: Query<'w, 's, &'static CosmicSource, With<ChatInputUI>>,
+}
+
 fn chat_key_handler(
     keys: Res<ButtonInput<KeyCode>>,
-    mut editor_container: Query<(&mut Visibility, &mut Style), With<ChatInputUI>>,
-    mut editor: Query<&mut CosmicEditor, With<ChatInputText>>,
-    mut source: Query<&CosmicSource, With<ChatInputUI>>,
+    input: ChatInput,
     mut focused_widget: ResMut<FocusedWidget>,
     mut chat_writer: EventWriter<ChatMessage>,
+    mut cursor_state: ResMut<CursorState>,
+    mut history: ResMut<InputHistory>,
 ) {
+    let ChatInput {
+        container: mut editor_container,
+        mut editor,
+        mut source,
+    } = input;
+
     let mut is_focused = false;
     if let Some(focused) = focused_widget.0 {
         for source in source.iter() {
@@ -151,6 +228,7 @@ fn chat_key_handler(
             *visibility = Visibility::Hidden;
             style.height = Val::Px(0.0);
             *focused_widget = FocusedWidget(None);
+            cursor_state.is_locked = true;
 
             for mut editor in &mut editor.iter_mut() {
                 let text = editor.with_buffer(|b| b.get_text());
@@ -160,15 +238,17 @@ fn chat_key_handler(
                     break;
                 }
 
+                history.push(text.clone());
                 chat_writer.send(ChatMessage { message: text });
 
                 clear_buffer(&mut editor);
             }
         }
 
-        if !is_focused {
+        if !is_focused && *visibility != Visibility::Hidden {
             *visibility = Visibility::Hidden;
             style.height = Val::Px(0.0);
+            cursor_state.is_locked = true;
         }
     }
 
@@ -179,10 +259,13 @@ fn chat_key_handler(
                 *visibility = Visibility::Hidden;
                 style.height = Val::Px(0.0);
                 *focused_widget = FocusedWidget(None);
+                cursor_state.is_locked = true;
             } else {
                 info!("Showing chat editor");
                 *visibility = Visibility::Visible;
                 style.height = Val::Px(40.0);
+                // free to click links
+                cursor_state.is_locked = false;
 
                 for source in &mut source.iter_mut() {
                     *focused_widget = FocusedWidget(Some(source.0));
@@ -197,47 +280,89 @@ fn chat_key_handler(
 }
 
 fn clear_buffer(editor: &mut Mut<CosmicEditor>) {
+    set_input(editor, "");
+}
+
+/// Replaces what is typed in the chat input, the cursor goes to the end.
+pub fn set_input(editor: &mut Mut<CosmicEditor>, text: &str) {
     editor.with_buffer_mut(|b| {
-        for line in b.lines.iter_mut() {
+        for (i, line) in b.lines.iter_mut().enumerate() {
             let ending = line.ending();
             let attr_list = line.attrs_list().to_owned();
-            line.set_text("", ending, attr_list);
+            line.set_text(if i == 0 { text } else { "" }, ending, attr_list);
         }
     });
 
-    editor.set_cursor(Cursor::new(0, 0));
+    editor.set_cursor(Cursor::new(0, text.len()));
 }
 
+/// Recent messages, or the whole scrollback while the chat is open.
 pub fn handle_buffered_text(
-    mut query: Query<(&mut Text, &mut ChatEditor)>,
+    mut query: Query<(&mut Text, &mut RichText, &mut ChatEditor)>,
+    chat_open: ChatOpen,
     time: Res<Time>,
     mut chat_buffer: ResMut<ChatBuffer>,
+    scroll: Res<ChatScroll>,
+    max_lines: Res<ChatMaxLines>,
+    outgoing: Res<OutgoingChat>,
 ) {
-    let (mut text, mut editor) = query.single_mut();
+    let (mut text, mut rich, mut editor) = query.single_mut();
     editor.timer.tick(time.delta());
 
     if editor.timer.finished() {
         editor.timer.reset();
+        chat_buffer.archive_oldest();
+    } else if !chat_buffer.is_changed()
+        && !scroll.is_changed()
+        && !outgoing.is_changed()
+        && !chat_open.toggled()
+    {
+        return;
+    }
 
-        if !chat_buffer.shown_buffer.is_empty() {
-            let dropped = chat_buffer.shown_buffer.remove(0);
-            chat_buffer.buffer.push(dropped);
-        }
-
-        let mut buffer = String::new();
-        for line in chat_buffer.shown_buffer.iter() {
-            buffer.push_str(line);
-            buffer.push('\n');
+    let lines: Vec<_> = match chat_open.is_open() {
+        true => {
+            let all: Vec<_> = chat_buffer
+                .buffer
+                .iter()
+                .chain(chat_buffer.shown_buffer.iter())
+                .collect();
+            let end = all.len().saturating_sub(scroll.0);
+            let start = end.saturating_sub(max_lines.open_lines);
+            all[start..end].to_vec()
         }
+        false => chat_buffer.shown_buffer.iter().collect(),
+    };
+
+    let mut spans = vec![];
+    for line in lines {
+        spans.extend(line.iter().cloned());
+        spans.push(Span {
+            text: "\n".to_string(),
+            style: default(),
+        });
+    }
 
-        text.sections[0].value = buffer;
+    // still waiting for the rate limit
+    for message in &outgoing.queue {
+        spans.push(Span {
+            text: format!("[queued] {message}\n"),
+            style: SpanStyle {
+                color: Some(ChatColor::Gray),
+                italic: true,
+                ..default()
+            },
+        });
     }
+
+    rich.set(&mut text, &spans);
 }
 
 pub fn handle_new_chat_messages(
     mut new_messages: EventReader<NewRawChatMessage>,
     mut chat_buffer: ResMut<ChatBuffer>,
     chat_max_lines: Res<ChatMaxLines>,
+    lang: Res<lang::Translations>,
 ) {
     for message in new_messages.read() {
         info!("Trying to parse chat message: {}", message.raw_object);
@@ -245,12 +370,10 @@ pub fn handle_new_chat_messages(
         match serde_json::from_str::<chat_proto::ChatComponent>(&message.raw_object) {
             Ok(msg) => {
                 info!("Parsed chat message: {msg:?}");
-                chat_buffer.shown_buffer.push(msg.to_plain_text());
+                chat_buffer.shown_buffer.push(msg.spans(&lang));
 
                 if chat_buffer.shown_buffer.len() > chat_max_lines.max_lines {
-                    let item = chat_buffer.shown_buffer.remove(0);
-                    info!("Removing item from chat buffer: {item}");
-                    chat_buffer.buffer.push(item);
+                    chat_buffer.archive_oldest();
                 }
             }
             Err(e) => {
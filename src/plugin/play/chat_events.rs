@@ -0,0 +1,344 @@
+use super::button;
+use super::chat::{self, ChatEditor, ChatInputText, ChatMessage, ChatOpen};
+use super::chat_proto::{ClickAction, ClickEvent, SpanStyle};
+use super::rich_text::RichText;
+use crate::components::MainCamera;
+use crate::plugin::CursorState;
+use crate::state::AppState;
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::window::PrimaryWindow;
+use bevy_cosmic_edit::CosmicEditor;
+use std::process::Command;
+
+/// Chat span under the mouse while the chat is open.
+#[derive(Resource, Debug, Default)]
+struct Hovered(Option<SpanStyle>);
+
+/// Link waiting for the player to confirm it should be opened.
+#[derive(Resource, Debug, Default)]
+struct PendingUrl(Option<String>);
+
+#[derive(Component)]
+struct Tooltip;
+
+#[derive(Component)]
+struct UrlDialog;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum DialogButton {
+    Open,
+    Cancel,
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Hovered>()
+        .init_resource::<PendingUrl>()
+        .add_systems(OnEnter(AppState::Playing), spawn_tooltip)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                find_hovered,
+                show_tooltip,
+                click_span,
+                show_url_dialog,
+                answer_url_dialog,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn spawn_tooltip(mut commands: Commands, camera: Query<Entity, With<MainCamera>>) {
+    let mut tooltip = commands.spawn((
+        Tooltip,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            background_color: Color::srgba(0.06, 0.0, 0.06, 0.94).into(),
+            border_color: Color::srgb(0.16, 0.0, 0.5).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(30),
+            ..default()
+        },
+    ));
+
+    if let Ok(camera) = camera.get_single() {
+        tooltip.insert(TargetCamera(camera));
+    }
+
+    tooltip.with_children(|p| {
+        p.spawn(RichText::bundle(
+            &[],
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+    });
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut hovered: ResMut<Hovered>,
+    mut pending: ResMut<PendingUrl>,
+    tooltips: Query<Entity, With<Tooltip>>,
+    dialogs: Query<Entity, With<UrlDialog>>,
+) {
+    hovered.0 = None;
+    pending.0 = None;
+
+    for entity in tooltips.iter().chain(dialogs.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn find_hovered(
+    mut hovered: ResMut<Hovered>,
+    chat_open: ChatOpen,
+    texts: Query<(&RichText, &TextLayoutInfo, &Node, &GlobalTransform), With<ChatEditor>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window_q.get_single().ok();
+    let cursor = window.and_then(|window| window.cursor_position());
+
+    let style = match (cursor, chat_open.is_open()) {
+        (Some(cursor), true) => texts.iter().find_map(|(rich, layout, node, transform)| {
+            let top_left = transform.translation().truncate() - node.size() / 2.0;
+            let scale = window.map_or(1.0, |window| window.scale_factor());
+            rich.style_at(layout, (cursor - top_left) * scale)
+        }),
+        _ => None,
+    };
+
+    if hovered.0.as_ref() != style {
+        hovered.0 = style.cloned();
+    }
+}
+
+fn show_tooltip(
+    hovered: Res<Hovered>,
+    mut tooltip_q: Query<(&mut Style, &mut Visibility, &Children), With<Tooltip>>,
+    mut texts: Query<(&mut Text, &mut RichText)>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok((mut style, mut visibility, children)) = tooltip_q.get_single_mut() else {
+        return;
+    };
+
+    let hover = hovered.0.as_ref().and_then(|style| style.hover.as_ref());
+    let Some(hover) = hover else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    if let Some(cursor) = window_q
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    {
+        style.left = Val::Px(cursor.x + 12.0);
+        style.top = Val::Px(cursor.y - 12.0);
+    }
+    *visibility = Visibility::Visible;
+
+    if hovered.is_changed() {
+        for child in children.iter() {
+            if let Ok((mut text, mut rich)) = texts.get_mut(*child) {
+                rich.set(&mut text, hover);
+            }
+        }
+    }
+}
+
+fn click_span(
+    mouse: Res<ButtonInput<MouseButton>>,
+    hovered: Res<Hovered>,
+    mut pending: ResMut<PendingUrl>,
+    mut chat_writer: EventWriter<ChatMessage>,
+    mut editor: Query<&mut CosmicEditor, With<ChatInputText>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) || pending.0.is_some() {
+        return;
+    }
+
+    let click = hovered.0.as_ref().and_then(|style| style.click.as_ref());
+    let Some(ClickEvent { action, value }) = click else {
+        return;
+    };
+
+    info!("Clicked chat: {action:?} {value}");
+
+    match action {
+        ClickAction::RunCommand => {
+            chat_writer.send(ChatMessage {
+                message: value.clone(),
+            });
+        }
+        ClickAction::SuggestCommand => {
+            for mut editor in editor.iter_mut() {
+                chat::set_input(&mut editor, value);
+            }
+        }
+        ClickAction::OpenUrl if is_web_link(value) => {
+            pending.0 = Some(value.clone());
+        }
+        ClickAction::OpenUrl => warn!("Not opening {value}, only web links are allowed"),
+        ClickAction::ChangePage | ClickAction::Unknown => {}
+    }
+}
+
+fn show_url_dialog(
+    mut commands: Commands,
+    pending: Res<PendingUrl>,
+    camera: Query<Entity, With<MainCamera>>,
+    dialog: Query<Entity, With<UrlDialog>>,
+    mut cursor_state: ResMut<CursorState>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+
+    for entity in dialog.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(url) = &pending.0 else {
+        return;
+    };
+
+    cursor_state.is_locked = false;
+
+    let mut screen = commands.spawn((
+        UrlDialog,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.75).into(),
+            z_index: ZIndex::Global(25),
+            ..default()
+        },
+    ));
+
+    if let Ok(camera) = camera.get_single() {
+        screen.insert(TargetCamera(camera));
+    }
+
+    screen.with_children(|p| {
+        p.spawn(TextBundle::from_section(
+            "Do you want to open this link? Never open links from people you don't trust!",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        ));
+
+        p.spawn(TextBundle::from_section(
+            url.clone(),
+            TextStyle {
+                font_size: 18.0,
+                color: Color::srgb(0.33, 1.0, 1.0),
+                ..default()
+            },
+        ));
+
+        p.spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(24.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|p| {
+            button::spawn(p, "Open", 160.0).insert(DialogButton::Open);
+            button::spawn(p, "Cancel", 160.0).insert(DialogButton::Cancel);
+        });
+    });
+}
+
+fn answer_url_dialog(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut pending: ResMut<PendingUrl>,
+    mut buttons: Query<(Ref<Interaction>, &DialogButton, &mut BorderColor)>,
+    chat_open: ChatOpen,
+    mut cursor_state: ResMut<CursorState>,
+) {
+    let Some(url) = pending.0.clone() else {
+        return;
+    };
+
+    let mut answer = keys
+        .just_pressed(KeyCode::Escape)
+        .then_some(DialogButton::Cancel);
+
+    for (interaction, button, mut border_color) in buttons.iter_mut() {
+        if !interaction.is_changed() {
+            continue;
+        }
+
+        match *interaction {
+            Interaction::Pressed => answer = Some(*button),
+            Interaction::Hovered => {
+                border_color.0 = Color::Srgba(bevy::color::palettes::tailwind::CYAN_300);
+            }
+            Interaction::None => border_color.0 = Color::BLACK,
+        }
+    }
+
+    let Some(answer) = answer else {
+        return;
+    };
+
+    if answer == DialogButton::Open {
+        open_url(&url);
+    }
+
+    pending.0 = None;
+    // back to the game, unless the chat is still open
+    cursor_state.is_locked = !chat_open.is_open();
+}
+
+// not through cmd, it would run whatever follows a & in the link
+#[cfg(target_os = "windows")]
+const OPENER: &[&str] = &["rundll32", "url.dll,FileProtocolHandler"];
+#[cfg(target_os = "macos")]
+const OPENER: &[&str] = &["open"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const OPENER: &[&str] = &["xdg-open"];
+
+/// Only http and https, anything else could start a program.
+fn is_web_link(url: &str) -> bool {
+    let scheme = url
+        .split_once("://")
+        .map(|(scheme, _)| scheme.to_ascii_lowercase());
+
+    matches!(scheme.as_deref(), Some("http" | "https"))
+}
+
+fn open_url(url: &str) {
+    if !is_web_link(url) {
+        warn!("Not opening {url}, only web links are allowed");
+        return;
+    }
+
+    info!("Opening {url}");
+
+    if let Err(e) = Command::new(OPENER[0]).args(&OPENER[1..]).arg(url).spawn() {
+        error!("Could not open {url}: {e}");
+    }
+}
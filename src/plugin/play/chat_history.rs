@@ -0,0 +1,214 @@
+use super::chat::{self, ChatBuffer, ChatInputText, ChatMaxLines, ChatOpen};
+use crate::resources::{ChatSettings, CurrentServerAddress, GamePaths};
+use crate::state::AppState;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use bevy_cosmic_edit::CosmicEditor;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+/// Pixels of a touchpad scroll that count as one line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Messages and commands typed into the chat, oldest first, kept for each server.
+#[derive(Resource, Debug, Default)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    /// entry in the input while going through the history with Up and Down
+    browsing: Option<usize>,
+    path: PathBuf,
+    size: usize,
+    /// changed since it was last written to `path`
+    dirty: bool,
+}
+
+/// Lines the open chat is scrolled up from the newest message.
+#[derive(Resource, Debug, Default)]
+pub struct ChatScroll(pub usize);
+
+impl InputHistory {
+    pub fn push(&mut self, message: String) {
+        self.browsing = None;
+
+        if self.entries.last() != Some(&message) {
+            self.entries.push(message);
+        }
+
+        let extra = self.entries.len().saturating_sub(self.size);
+        self.entries.drain(..extra);
+
+        self.dirty = true;
+    }
+}
+
+fn write_history(path: &Path, data: &str) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), create_dir_all)
+        .and_then(|_| write(path, data));
+
+    if let Err(e) = result {
+        error!("Could not store chat history in {path:?}: {e}");
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<ChatScroll>()
+        .add_systems(OnEnter(AppState::Playing), load)
+        .add_systems(OnExit(AppState::Playing), (flush, cleanup).chain())
+        .add_systems(
+            Update,
+            (browse, scroll, save.run_if(resource_exists::<InputHistory>))
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+/// One file per server in `history`, the address made safe to be a file name.
+fn history_path(root: &Path, address: &str) -> PathBuf {
+    let name: String = address
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    root.join("history").join(format!("{name}.txt"))
+}
+
+fn load(
+    mut commands: Commands,
+    paths: Res<GamePaths>,
+    server: Res<CurrentServerAddress>,
+    settings: Res<ChatSettings>,
+) {
+    let path = history_path(&paths.root, &server.address);
+
+    let mut entries: Vec<String> = match read_to_string(&path) {
+        Ok(data) => data.lines().map(ToString::to_string).collect(),
+        Err(e) => {
+            info!("No chat history in {path:?}: {e}");
+            vec![]
+        }
+    };
+
+    let extra = entries.len().saturating_sub(settings.history_size);
+    entries.drain(..extra);
+
+    commands.insert_resource(InputHistory {
+        entries,
+        browsing: None,
+        path,
+        size: settings.history_size,
+        dirty: false,
+    });
+}
+
+/// Writes the history away from the frame, one write at a time so an older one never lands last.
+fn save(mut history: ResMut<InputHistory>, mut writing: Local<Option<Task<()>>>) {
+    if writing.as_ref().is_some_and(|task| !task.is_finished()) {
+        return;
+    }
+    *writing = None;
+
+    if !history.dirty {
+        return;
+    }
+    history.dirty = false;
+
+    let path = history.path.clone();
+    let data = history.entries.join("\n");
+    *writing = Some(IoTaskPool::get().spawn(async move { write_history(&path, &data) }));
+}
+
+/// Whatever `save` didn't get to yet is written before the history goes away.
+fn flush(history: Option<Res<InputHistory>>) {
+    if let Some(history) = history.filter(|history| history.dirty) {
+        write_history(&history.path, &history.entries.join("\n"));
+    }
+}
+
+fn cleanup(mut commands: Commands, mut scroll: ResMut<ChatScroll>) {
+    commands.remove_resource::<InputHistory>();
+    scroll.0 = 0;
+}
+
+/// Up and Down go through what was sent before.
+fn browse(
+    keys: Res<ButtonInput<KeyCode>>,
+    chat_open: ChatOpen,
+    mut editor: Query<&mut CosmicEditor, With<ChatInputText>>,
+    mut history: ResMut<InputHistory>,
+) {
+    if !chat_open.is_open() {
+        if history.browsing.is_some() {
+            history.browsing = None;
+        }
+        return;
+    }
+
+    if history.entries.is_empty() {
+        return;
+    }
+
+    let last = history.entries.len() - 1;
+    let browsing = if keys.just_pressed(KeyCode::ArrowUp) {
+        Some(history.browsing.map_or(last, |i| i.saturating_sub(1)))
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        // past the newest entry the input is empty again
+        history.browsing.and_then(|i| (i < last).then_some(i + 1))
+    } else {
+        return;
+    };
+
+    history.browsing = browsing;
+    let text = browsing.map_or("", |i| history.entries[i].as_str());
+
+    for mut editor in editor.iter_mut() {
+        chat::set_input(&mut editor, text);
+    }
+}
+
+/// The mouse wheel and PageUp/PageDown move through the scrollback of the open chat.
+fn scroll(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    chat_open: ChatOpen,
+    max_lines: Res<ChatMaxLines>,
+    chat_buffer: Res<ChatBuffer>,
+    mut scroll: ResMut<ChatScroll>,
+) {
+    let lines: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    if !chat_open.is_open() {
+        if scroll.0 != 0 {
+            scroll.0 = 0;
+        }
+        return;
+    }
+
+    let page = max_lines.open_lines.saturating_sub(1) as f32;
+    let mut delta = lines.round();
+    if keys.just_pressed(KeyCode::PageUp) {
+        delta += page;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        delta -= page;
+    }
+
+    if delta != 0.0 {
+        let lines = chat_buffer.buffer.len() + chat_buffer.shown_buffer.len();
+        let top = lines.saturating_sub(max_lines.open_lines);
+        scroll.0 = ((scroll.0 as f32 + delta).max(0.0) as usize).min(top);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::lang::{pieces, Piece, Translations};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum ChatComponent {
     Text(String),
@@ -8,12 +10,19 @@ pub enum ChatComponent {
     PlayerMessage(ChatObject),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatObject {
     #[serde(rename = "text", default)]
     text: String,
     #[serde(rename = "extra", default)]
     extra: Option<Vec<ExtraComponent>>,
+    #[serde(flatten)]
+    style: ChatStyle,
+}
+
+/// Formatting of a component, whatever is left out comes from its parent.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ChatStyle {
     #[serde(rename = "color", default)]
     color: Option<ChatColor>,
     #[serde(rename = "bold", default)]
@@ -26,27 +35,74 @@ pub struct ChatObject {
     strikethrough: Option<bool>,
     #[serde(rename = "obfuscated", default)]
     obfuscated: Option<bool>,
+    #[serde(rename = "clickEvent", default)]
+    click_event: Option<ClickEvent>,
+    #[serde(rename = "hoverEvent", default)]
+    hover_event: Option<HoverEvent>,
+}
+
+/// What clicking a piece of chat does.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClickEvent {
+    pub action: ClickAction,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    OpenUrl,
+    RunCommand,
+    SuggestCommand,
+    /// only means something in books
+    ChangePage,
+    #[serde(other)]
+    Unknown,
+}
+
+/// What is shown while hovering a piece of chat.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HoverEvent {
+    action: HoverAction,
+    value: Box<ChatComponent>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum HoverAction {
+    ShowText,
+    /// the value is the item in NBT text
+    ShowItem,
+    /// the value is `{name:...,type:...,id:...}` in NBT text
+    ShowEntity,
+    ShowAchievement,
+    #[serde(other)]
+    Unknown,
 }
 
 // We handle "extra" to allow both strings and ChatComponents
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 enum ExtraComponent {
     Text(String),
     Component(ChatComponent),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TranslateObject {
     #[serde(rename = "translate")]
     translate: String,
     #[serde(rename = "with", default)]
     with: Vec<ChatComponent>,
+    #[serde(rename = "extra", default)]
+    extra: Option<Vec<ExtraComponent>>,
+    #[serde(flatten)]
+    style: ChatStyle,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
-enum ChatColor {
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatColor {
     Black,
     DarkBlue,
     DarkGreen,
@@ -63,75 +119,480 @@ enum ChatColor {
     LightPurple,
     Yellow,
     White,
+    /// `reset`, or a colour we don't know, goes back to the default
+    #[serde(other)]
+    Reset,
 }
 
-impl ChatObject {
-    pub fn to_plain_text(&self) -> String {
-        let mut result = self.text.clone();
-
-        // Handle "extra" field which can contain either strings or ChatComponents
-        if let Some(extras) = &self.extra {
-            for extra in extras {
-                match extra {
-                    ExtraComponent::Text(text) => result.push_str(text),
-                    ExtraComponent::Component(component) => match component {
-                        ChatComponent::Text(text) => result.push_str(text),
-                        ChatComponent::PlayerMessage(obj) => result.push_str(&obj.to_plain_text()),
-                        ChatComponent::Translate(trans_obj) => {
-                            result.push_str(&trans_obj.to_plain_text())
-                        }
-                    },
+impl ChatColor {
+    /// The vanilla palette, none for `Reset`.
+    pub fn rgb(&self) -> Option<[u8; 3]> {
+        let rgb = match self {
+            ChatColor::Black => [0x00, 0x00, 0x00],
+            ChatColor::DarkBlue => [0x00, 0x00, 0xAA],
+            ChatColor::DarkGreen => [0x00, 0xAA, 0x00],
+            ChatColor::DarkAqua => [0x00, 0xAA, 0xAA],
+            ChatColor::DarkRed => [0xAA, 0x00, 0x00],
+            ChatColor::DarkPurple => [0xAA, 0x00, 0xAA],
+            ChatColor::Gold => [0xFF, 0xAA, 0x00],
+            ChatColor::Gray => [0xAA, 0xAA, 0xAA],
+            ChatColor::DarkGray => [0x55, 0x55, 0x55],
+            ChatColor::Blue => [0x55, 0x55, 0xFF],
+            ChatColor::Green => [0x55, 0xFF, 0x55],
+            ChatColor::Aqua => [0x55, 0xFF, 0xFF],
+            ChatColor::Red => [0xFF, 0x55, 0x55],
+            ChatColor::LightPurple => [0xFF, 0x55, 0xFF],
+            ChatColor::Yellow => [0xFF, 0xFF, 0x55],
+            ChatColor::White => [0xFF, 0xFF, 0xFF],
+            ChatColor::Reset => return None,
+        };
+
+        Some(rgb)
+    }
+}
+
+/// Formatting of a piece of text once every parent was taken into account,
+/// along with what clicking and hovering it does.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SpanStyle {
+    /// none is the default colour of wherever the text is shown
+    pub color: Option<ChatColor>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+    pub obfuscated: bool,
+    pub click: Option<ClickEvent>,
+    /// tooltip
+    pub hover: Option<Vec<Span>>,
+}
+
+/// Text that is formatted the same all the way through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+impl ChatStyle {
+    fn apply(&self, parent: &SpanStyle, lang: &Translations) -> SpanStyle {
+        SpanStyle {
+            color: match self.color {
+                Some(ChatColor::Reset) => None,
+                Some(color) => Some(color),
+                None => parent.color,
+            },
+            bold: self.bold.unwrap_or(parent.bold),
+            italic: self.italic.unwrap_or(parent.italic),
+            underlined: self.underlined.unwrap_or(parent.underlined),
+            strikethrough: self.strikethrough.unwrap_or(parent.strikethrough),
+            obfuscated: self.obfuscated.unwrap_or(parent.obfuscated),
+            click: self.click_event.clone().or_else(|| parent.click.clone()),
+            hover: match &self.hover_event {
+                Some(hover) => Some(hover.spans(lang)),
+                None => parent.hover.clone(),
+            },
+        }
+    }
+}
+
+/// Reads `key` out of NBT text like `{id:"minecraft:stone",Count:1b}`.
+fn nbt_field(nbt: &str, key: &str) -> Option<String> {
+    let pattern = format!("{key}:");
+
+    for (at, _) in nbt.match_indices(&pattern) {
+        // `id` shouldn't match the end of `uuid`
+        if !nbt[..at].ends_with(['{', ',', ' ']) {
+            continue;
+        }
+
+        let value = nbt[at + pattern.len()..].trim_start();
+        let Some(quoted) = value.strip_prefix('"') else {
+            return value.split([',', '}']).next().map(ToString::to_string);
+        };
+
+        let mut text = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => text.extend(chars.next()),
+                c => text.push(c),
+            }
+        }
+        return Some(text);
+    }
+
+    None
+}
+
+impl HoverEvent {
+    fn spans(&self, lang: &Translations) -> Vec<Span> {
+        let nbt = self.value.raw_text();
+        let mut spans = vec![];
+        // names can have legacy codes too
+        let mut push = |text: &str, color| {
+            let style = SpanStyle {
+                color,
+                ..Default::default()
+            };
+            push_span(text, &style, lang, &mut spans);
+        };
+
+        match self.action {
+            HoverAction::ShowItem => {
+                let name = nbt_field(&nbt, "Name").or_else(|| nbt_field(&nbt, "id"));
+                push(&name.unwrap_or(nbt), None);
+            }
+            HoverAction::ShowEntity => {
+                let name = nbt_field(&nbt, "name");
+                let kind = nbt_field(&nbt, "type");
+                let id = nbt_field(&nbt, "id");
+
+                let lines = [
+                    (name, None),
+                    (kind, Some(ChatColor::DarkGray)),
+                    (id, Some(ChatColor::DarkGray)),
+                ];
+                let mut separator = "";
+                for (line, color) in lines {
+                    if let Some(line) = line {
+                        push(separator, None);
+                        push(&line, color);
+                        separator = "\n";
+                    }
                 }
             }
+            // the value is the key of the achievement
+            HoverAction::ShowAchievement => push(lang.get(&nbt), Some(ChatColor::Green)),
+            HoverAction::ShowText | HoverAction::Unknown => return self.value.spans(lang),
         }
 
-        result
+        spans
+    }
+}
+
+fn push_span(text: &str, style: &SpanStyle, lang: &Translations, out: &mut Vec<Span>) {
+    // plenty of servers still put legacy codes in plain text
+    if text.contains(LEGACY_PREFIX) {
+        ChatComponent::from_legacy(text).write_spans(style, lang, out);
+    } else if !text.is_empty() {
+        out.push(Span {
+            text: text.to_string(),
+            style: style.clone(),
+        });
+    }
+}
+
+fn extra_spans(
+    extra: &Option<Vec<ExtraComponent>>,
+    style: &SpanStyle,
+    lang: &Translations,
+    out: &mut Vec<Span>,
+) {
+    for extra in extra.iter().flatten() {
+        match extra {
+            ExtraComponent::Text(text) => push_span(text, style, lang, out),
+            ExtraComponent::Component(component) => component.write_spans(style, lang, out),
+        }
     }
 }
 
 impl TranslateObject {
-    pub fn to_plain_text(&self) -> String {
-        match self.translate.as_str() {
-            // For server announcements
-            "chat.type.announcement" => {
-                if self.with.len() == 2 {
-                    let announcer = self.extract_plain_text(0);
-                    let message = self.extract_plain_text(1);
-                    format!("[Server Announcement] {}: {}", announcer, message)
-                } else {
-                    "[Server Announcement]".to_string()
-                }
-            }
-            // For player chat messages
-            "chat.type.text" => {
-                if self.with.len() == 2 {
-                    let player_name = self.extract_plain_text(0);
-                    let message = self.extract_plain_text(1);
-                    format!("[{}]: {}", player_name, message)
-                } else {
-                    "[Player Message]".to_string()
+    fn write_spans(&self, parent: &SpanStyle, lang: &Translations, out: &mut Vec<Span>) {
+        let style = self.style.apply(parent, lang);
+
+        for piece in pieces(lang.get(&self.translate)) {
+            match piece {
+                Piece::Text(text) => push_span(text, &style, lang, out),
+                Piece::Argument(i) => {
+                    if let Some(arg) = self.with.get(i) {
+                        arg.write_spans(&style, lang, out);
+                    }
                 }
             }
-            _ => self.translate.clone(),
         }
+
+        extra_spans(&self.extra, &style, lang, out);
     }
+}
+
+/// Starts a legacy formatting code, like `§a` or `§l`.
+const LEGACY_PREFIX: char = '§';
+
+impl ChatColor {
+    fn from_legacy(code: char) -> Option<Self> {
+        let color = match code {
+            '0' => ChatColor::Black,
+            '1' => ChatColor::DarkBlue,
+            '2' => ChatColor::DarkGreen,
+            '3' => ChatColor::DarkAqua,
+            '4' => ChatColor::DarkRed,
+            '5' => ChatColor::DarkPurple,
+            '6' => ChatColor::Gold,
+            '7' => ChatColor::Gray,
+            '8' => ChatColor::DarkGray,
+            '9' => ChatColor::Blue,
+            'a' => ChatColor::Green,
+            'b' => ChatColor::Aqua,
+            'c' => ChatColor::Red,
+            'd' => ChatColor::LightPurple,
+            'e' => ChatColor::Yellow,
+            'f' => ChatColor::White,
+            'r' => ChatColor::Reset,
+            _ => return None,
+        };
+
+        Some(color)
+    }
+}
 
-    pub fn extract_plain_text(&self, index: usize) -> String {
-        match &self.with.get(index) {
-            Some(ChatComponent::Text(text)) => text.clone(),
-            Some(ChatComponent::PlayerMessage(obj)) => obj.to_plain_text(),
-            Some(ChatComponent::Translate(trans_obj)) => trans_obj.to_plain_text(),
-            _ => String::new(),
+impl ChatStyle {
+    /// A colour code (or `§r`) clears bold and the others, like vanilla does.
+    fn colored(color: ChatColor) -> Self {
+        Self {
+            color: Some(color),
+            bold: Some(false),
+            italic: Some(false),
+            underlined: Some(false),
+            strikethrough: Some(false),
+            obfuscated: Some(false),
+            ..Default::default()
         }
     }
 }
 
 impl ChatComponent {
-    pub fn to_plain_text(&self) -> String {
+    /// Turns text with legacy `§` codes into components, a child per run of equally formatted text.
+    pub fn from_legacy(text: &str) -> Self {
+        let mut extra = vec![];
+        let mut style = ChatStyle::default();
+        let mut run = String::new();
+
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c != LEGACY_PREFIX {
+                run.push(c);
+                continue;
+            }
+
+            // unknown codes and a trailing § are dropped
+            let Some(code) = chars.next().map(|code| code.to_ascii_lowercase()) else {
+                break;
+            };
+
+            if !run.is_empty() {
+                extra.push(ExtraComponent::Component(ChatComponent::PlayerMessage(
+                    ChatObject {
+                        text: std::mem::take(&mut run),
+                        extra: None,
+                        style: style.clone(),
+                    },
+                )));
+            }
+
+            match code {
+                'k' => style.obfuscated = Some(true),
+                'l' => style.bold = Some(true),
+                'm' => style.strikethrough = Some(true),
+                'n' => style.underlined = Some(true),
+                'o' => style.italic = Some(true),
+                code => {
+                    if let Some(color) = ChatColor::from_legacy(code) {
+                        style = ChatStyle::colored(color);
+                    }
+                }
+            }
+        }
+
+        if !run.is_empty() {
+            extra.push(ExtraComponent::Component(ChatComponent::PlayerMessage(
+                ChatObject {
+                    text: run,
+                    extra: None,
+                    style,
+                },
+            )));
+        }
+
+        ChatComponent::PlayerMessage(ChatObject {
+            text: String::new(),
+            extra: Some(extra),
+            style: ChatStyle::default(),
+        })
+    }
+
+    /// Reads a chat component, text that isn't JSON is taken as it is.
+    pub fn parse(raw: &str) -> Self {
+        serde_json::from_str(raw).unwrap_or_else(|_| ChatComponent::Text(raw.to_string()))
+    }
+
+    /// The text split where its formatting changes, styles of parents carry over to children.
+    pub fn spans(&self, lang: &Translations) -> Vec<Span> {
+        let mut spans = vec![];
+        self.write_spans(&SpanStyle::default(), lang, &mut spans);
+        spans
+    }
+
+    fn write_spans(&self, parent: &SpanStyle, lang: &Translations, out: &mut Vec<Span>) {
         match self {
-            ChatComponent::Text(text) => text.clone(),
-            ChatComponent::PlayerMessage(obj) => obj.to_plain_text(),
-            ChatComponent::Translate(obj) => obj.to_plain_text(),
+            ChatComponent::Text(text) => push_span(text, parent, lang, out),
+            ChatComponent::PlayerMessage(obj) => {
+                let style = obj.style.apply(parent, lang);
+                push_span(&obj.text, &style, lang, out);
+                extra_spans(&obj.extra, &style, lang, out);
+            }
+            ChatComponent::Translate(obj) => obj.write_spans(parent, lang, out),
+        }
+    }
+
+    /// The text as sent, legacy codes included and without translating.
+    fn raw_text(&self) -> String {
+        let (text, extra) = match self {
+            ChatComponent::Text(text) => return text.clone(),
+            ChatComponent::PlayerMessage(obj) => (&obj.text, &obj.extra),
+            ChatComponent::Translate(obj) => (&obj.translate, &obj.extra),
+        };
+
+        let mut raw = text.clone();
+        for extra in extra.iter().flatten() {
+            match extra {
+                ExtraComponent::Text(text) => raw.push_str(text),
+                ExtraComponent::Component(component) => raw.push_str(&component.raw_text()),
+            }
         }
+        raw
     }
+
+    pub fn to_plain_text(&self, lang: &Translations) -> String {
+        self.spans(lang).into_iter().map(|span| span.text).collect()
+    }
+}
+
+#[cfg(test)]
+fn plain(json: &str) -> String {
+    serde_json::from_str::<ChatComponent>(json)
+        .unwrap()
+        .to_plain_text(&Translations::default())
+}
+
+#[test]
+fn translations_fill_in_arguments() {
+    assert_eq!(
+        plain(r#"{"translate":"chat.type.text","with":["Steve",{"text":"hi"}]}"#),
+        "[Steve]: hi"
+    );
+}
+
+#[test]
+fn missing_arguments_are_left_out() {
+    assert_eq!(
+        plain(r#"{"translate":"chat.type.text","with":["Steve"]}"#),
+        "[Steve]: "
+    );
+
+    // unknown keys are shown as they are
+    assert_eq!(
+        plain(r#"{"translate":"some.key","with":["x"]}"#),
+        "some.key"
+    );
+}
+
+#[cfg(test)]
+fn legacy(text: &str) -> Vec<Span> {
+    ChatComponent::from_legacy(text).spans(&Translations::default())
+}
+
+#[cfg(test)]
+fn span(text: &str, style: SpanStyle) -> Span {
+    Span {
+        text: text.to_string(),
+        style,
+    }
+}
+
+#[test]
+fn colour_codes_reset_formatting() {
+    let red = SpanStyle {
+        color: Some(ChatColor::Red),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        legacy("§lbold§cred"),
+        [
+            span(
+                "bold",
+                SpanStyle {
+                    bold: true,
+                    ..Default::default()
+                }
+            ),
+            span("red", red),
+        ]
+    );
+}
+
+#[test]
+fn reset_code_goes_back_to_plain_text() {
+    assert_eq!(
+        legacy("§a§ngreen§rplain"),
+        [
+            span(
+                "green",
+                SpanStyle {
+                    color: Some(ChatColor::Green),
+                    underlined: true,
+                    ..Default::default()
+                }
+            ),
+            span("plain", SpanStyle::default()),
+        ]
+    );
+}
+
+#[test]
+fn formatting_codes_stack() {
+    assert_eq!(
+        legacy("§6§k§LGold"),
+        [span(
+            "Gold",
+            SpanStyle {
+                color: Some(ChatColor::Gold),
+                obfuscated: true,
+                bold: true,
+                ..Default::default()
+            }
+        )]
+    );
+}
+
+#[test]
+fn unknown_codes_and_a_trailing_prefix_are_dropped() {
+    assert_eq!(
+        legacy("a§zb§"),
+        [
+            span("a", SpanStyle::default()),
+            span("b", SpanStyle::default())
+        ]
+    );
+    assert_eq!(legacy("§"), []);
+}
+
+#[test]
+fn legacy_codes_inside_json_text_are_parsed() {
+    let spans = serde_json::from_str::<ChatComponent>(r#"{"text":"§eHi"}"#)
+        .unwrap()
+        .spans(&Translations::default());
+
+    assert_eq!(
+        spans,
+        [span(
+            "Hi",
+            SpanStyle {
+                color: Some(ChatColor::Yellow),
+                ..Default::default()
+            }
+        )]
+    );
 }
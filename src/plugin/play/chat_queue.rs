@@ -0,0 +1,233 @@
+use super::chat::{ChatMessage, NewRawChatMessage};
+use crate::message::ClientMessage;
+use crate::resources::ChatSettings;
+use crate::state::AppState;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Longest chat packet the server accepts, counted in UTF-16 units like Java does.
+const MAX_MESSAGE_LENGTH: usize = 100;
+/// Messages that can go out back to back before the rate limit kicks in.
+const BURST: f32 = 5.0;
+
+/// Chat waiting to be sent, so typing fast doesn't get us kicked for spam.
+#[derive(Resource, Debug)]
+pub struct OutgoingChat {
+    pub queue: VecDeque<String>,
+    /// messages that may go out right now, refills at the configured rate
+    allowance: f32,
+}
+
+impl Default for OutgoingChat {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            allowance: BURST,
+        }
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<OutgoingChat>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (enqueue, send).chain().run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(mut outgoing: ResMut<OutgoingChat>) {
+    *outgoing = OutgoingChat::default();
+}
+
+fn text_length(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Byte index where `text` goes over `length` UTF-16 units.
+fn cut_at(text: &str, length: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        units += c.len_utf16();
+        if units > length {
+            return i;
+        }
+    }
+
+    text.len()
+}
+
+/// Splits `message` into packets, between words when it can.
+fn split_message(message: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+
+    for word in message.split(' ') {
+        let length = match current.is_empty() {
+            true => text_length(word),
+            false => text_length(&current) + 1 + text_length(word),
+        };
+
+        if length <= MAX_MESSAGE_LENGTH {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+            continue;
+        }
+
+        if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+
+        // a word too long for a packet of its own is cut
+        let mut word = word;
+        while text_length(word) > MAX_MESSAGE_LENGTH {
+            let (head, tail) = word.split_at(cut_at(word, MAX_MESSAGE_LENGTH));
+            parts.push(head.to_string());
+            word = tail;
+        }
+        current = word.to_string();
+    }
+
+    parts.push(current);
+
+    // runs of spaces are kept inside a packet, but not at its ends
+    parts
+        .iter()
+        .map(|part| part.trim_matches(' '))
+        .filter(|part| !part.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn enqueue(
+    mut messages: EventReader<ChatMessage>,
+    mut outgoing: ResMut<OutgoingChat>,
+    mut chat_writer: EventWriter<NewRawChatMessage>,
+) {
+    for message in messages.read() {
+        let message = message.message.trim();
+        if message.is_empty() {
+            continue;
+        }
+
+        let length = text_length(message);
+
+        // half a command would do something else
+        if message.starts_with('/') && length > MAX_MESSAGE_LENGTH {
+            warn!("Not sending a command of {length} characters");
+
+            let error = serde_json::json!({
+                "text": format!(
+                    "Commands can't be longer than {MAX_MESSAGE_LENGTH} characters, that one has {length}"
+                ),
+                "color": "red",
+            });
+            chat_writer.send(NewRawChatMessage {
+                raw_object: error.to_string(),
+            });
+            continue;
+        }
+
+        outgoing.queue.extend(split_message(message));
+    }
+}
+
+fn send(
+    time: Res<Time>,
+    settings: Res<ChatSettings>,
+    mut outgoing: ResMut<OutgoingChat>,
+    mut client_writer: EventWriter<ClientMessage>,
+) {
+    // the chat only redraws when the queue itself changes
+    let state = outgoing.bypass_change_detection();
+    if settings.messages_per_second > 0.0 {
+        let refill = time.delta_seconds() * settings.messages_per_second;
+        state.allowance = (state.allowance + refill).min(BURST);
+    } else {
+        // 0 turns the limit off
+        state.allowance = state.queue.len() as f32;
+    }
+
+    if state.queue.is_empty() || state.allowance < 1.0 {
+        return;
+    }
+
+    while outgoing.allowance >= 1.0 {
+        let Some(message) = outgoing.queue.pop_front() else {
+            break;
+        };
+
+        outgoing.allowance -= 1.0;
+        client_writer.send(ClientMessage::ChatMessage { message });
+    }
+}
+
+#[test]
+fn short_messages_are_one_packet() {
+    assert_eq!(split_message("hello there"), ["hello there"]);
+
+    let exact = "a".repeat(MAX_MESSAGE_LENGTH);
+    assert_eq!(split_message(&exact), [exact.as_str()]);
+}
+
+#[test]
+fn long_messages_split_between_words() {
+    let word = "abcd";
+    let message = [word; 30].join(" ");
+    let parts = split_message(&message);
+
+    // 20 words and their spaces fill 99 characters
+    assert_eq!(parts, [[word; 20].join(" "), [word; 10].join(" ")]);
+
+    let over = format!("{} b", "a".repeat(MAX_MESSAGE_LENGTH));
+    assert_eq!(
+        split_message(&over),
+        ["a".repeat(MAX_MESSAGE_LENGTH), "b".to_string()]
+    );
+}
+
+#[test]
+fn long_words_are_cut() {
+    let message = format!("hi {}", "a".repeat(250));
+    let lengths: Vec<_> = split_message(&message)
+        .iter()
+        .map(|part| part.len())
+        .collect();
+
+    assert_eq!(lengths, [2, 100, 100, 50]);
+}
+
+#[test]
+fn length_counts_utf16_units() {
+    // é is two bytes but one unit
+    let message = "é".repeat(150);
+    assert_eq!(split_message(&message), ["é".repeat(100), "é".repeat(50)]);
+
+    // emoji take two units and are never cut in half
+    let message = format!("a{}", "😀".repeat(50));
+    let parts = split_message(&message);
+
+    assert_eq!(parts, [format!("a{}", "😀".repeat(49)), "😀".to_string()]);
+    assert!(parts
+        .iter()
+        .all(|part| text_length(part) <= MAX_MESSAGE_LENGTH));
+}
+
+#[test]
+fn cut_stops_before_the_unit_past_the_length() {
+    assert_eq!(cut_at("abc", 2), 2);
+    assert_eq!(cut_at("abc", 5), 3);
+    assert_eq!(cut_at("é😀", 2), 2);
+    assert_eq!(cut_at("é😀", 3), 6);
+}
+
+#[test]
+fn runs_of_spaces_stay_inside_a_packet() {
+    assert_eq!(split_message("a   b"), ["a   b"]);
+
+    // the break lands in the spaces, neither packet starts or ends with them
+    let message = format!("{}     b", "a".repeat(98));
+    assert_eq!(split_message(&message), ["a".repeat(98), "b".to_string()]);
+}
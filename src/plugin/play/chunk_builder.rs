@@ -1,10 +1,15 @@
 use std::time::Instant;
 
+use super::block_atlas::{self, BlockAtlas};
+use super::block_builder::BlockLayer;
+use super::block_model::BlockModels;
 use super::chunk_cons::ChunkConstructor;
+use super::respawn::Respawned;
+use super::section_culling::{SectionGraphs, VisGraph};
 use crate::plugin::consts::WorldLayer;
-use crate::plugin::play::player::Player;
-use crate::plugin::play::world::{ActivePlayerChunks, ShownPlayerChunks, WorldChunkData};
+use crate::plugin::play::world::{self, ActivePlayerChunks, ShownPlayerChunks, WorldChunkData};
 use crate::state::AppState;
+use bevy::ecs::system::SystemParam;
 use bevy::pbr::wireframe::Wireframe;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, VertexAttributeValues};
@@ -18,6 +23,8 @@ use gyra_proto::smp;
 #[derive(Event, Debug)]
 pub struct RenderChunk {
     pub pos: ChunkVec2,
+    // indices of the sections whose cached mesh is stale
+    pub sections: Vec<usize>,
 }
 
 #[derive(Event, Debug)]
@@ -25,12 +32,21 @@ pub struct UnrenderChunk {
     pub pos: ChunkVec2,
 }
 
-#[derive(Event, Debug, Clone)]
-pub struct RenderedBlock {
+#[derive(Debug, Clone)]
+pub struct RenderedSection {
     pub mesh: Mesh,
-    pub material_id: u16,
     pub transform: Transform,
-    pub parent_chunk: ChunkVec2,
+    pub section: IVec3,
+    pub layer: BlockLayer,
+}
+
+/// The rebuilt section meshes of a column, replacing whatever was built for those sections.
+#[derive(Event, Debug, Clone)]
+pub struct RenderedColumn {
+    pub pos: ChunkVec2,
+    pub sections: Vec<RenderedSection>,
+    // rebuilt sections and their world generation when the mesh task started
+    pub generations: Vec<(usize, u64)>,
 }
 
 #[derive(Event, Debug)]
@@ -40,23 +56,82 @@ pub struct ChunkReceived {
 
 #[derive(Resource)]
 pub struct Materials {
-    pub blocks: HashMap<u16, Handle<StandardMaterial>>,
-    pub any_block: Handle<StandardMaterial>,
+    // every layer samples the same texture atlas
+    pub solid: Handle<StandardMaterial>,
+    pub cutout: Handle<StandardMaterial>,
+    pub translucent: Handle<StandardMaterial>,
 }
 
-#[derive(Resource)]
+impl Materials {
+    pub fn of(&self, layer: BlockLayer) -> &Handle<StandardMaterial> {
+        match layer {
+            BlockLayer::Solid => &self.solid,
+            BlockLayer::Cutout => &self.cutout,
+            BlockLayer::Translucent => &self.translucent,
+        }
+    }
+}
+
+/// Marks section meshes whose quads are sorted back to front around the camera.
+#[derive(Component, Debug)]
+struct TranslucentSection;
+
+#[derive(Resource, Default)]
 pub struct ChunkBuilderTasks {
-    pub tasks: Vec<Task<Vec<RenderedBlock>>>,
+    pub tasks: Vec<Task<BuiltColumn>>,
+    // columns with a mesh task in flight
+    pub pending: HashSet<ChunkVec2>,
 }
 
-impl Materials {
-    pub fn get_material_by_block_id(&self, id: u16) -> Handle<StandardMaterial> {
-        let block = self.blocks.get(&id);
-        if let Some(block) = block {
-            block.clone_weak()
-        } else {
-            self.any_block.clone_weak()
-        }
+pub struct BuiltColumn {
+    column: RenderedColumn,
+    graphs: Vec<(IVec3, VisGraph)>,
+}
+
+/// Spawned section meshes and the world generation they were built from.
+/// They stay around while out of view or out of render distance, and are only rebuilt once
+/// blocks or light of their section change.
+#[derive(Resource, Default)]
+pub struct SectionMeshCache {
+    sections: HashMap<IVec3, CachedSection>,
+}
+
+struct CachedSection {
+    generation: u64,
+    entities: Vec<Entity>,
+}
+
+impl SectionMeshCache {
+    /// Sections of a column never meshed, or meshed before their blocks or light changed.
+    fn stale_sections(&self, column: ChunkVec2, world: &WorldChunkData) -> Vec<usize> {
+        (0..16)
+            .filter(|section| {
+                let pos = IVec3::new(column.x, *section as _, column.z);
+                !self
+                    .sections
+                    .get(&pos)
+                    .is_some_and(|cached| cached.generation == world.generation(pos))
+            })
+            .collect()
+    }
+
+    /// Forgets a section, handing back the entities to despawn.
+    fn remove_section(&mut self, section: IVec3) -> Vec<Entity> {
+        self.sections
+            .remove(&section)
+            .map(|cached| cached.entities)
+            .unwrap_or_default()
+    }
+
+    /// Forgets a column, handing back the entities to despawn.
+    fn remove_column(&mut self, column: ChunkVec2) -> Vec<Entity> {
+        (0..16)
+            .filter_map(|section| {
+                self.sections
+                    .remove(&IVec3::new(column.x, section, column.z))
+            })
+            .flat_map(|cached| cached.entities)
+            .collect()
     }
 }
 
@@ -64,200 +139,180 @@ pub fn plugin(app: &mut App) {
     app.add_event::<ChunkReceived>()
         .add_event::<RenderChunk>()
         .add_event::<UnrenderChunk>()
-        .add_event::<RenderedBlock>()
-        .insert_resource(ChunkBuilderTasks { tasks: vec![] })
+        .add_event::<RenderedColumn>()
+        .insert_resource(ChunkBuilderTasks::default())
+        .insert_resource(SectionMeshCache::default())
         .add_systems(
             PreUpdate,
-            (download_chunks, chunk_scheduler).run_if(in_state(AppState::Playing)),
+            (
+                // the old world goes before chunks of the new one are stored
+                cleanup_chunks
+                    .before(download_chunks)
+                    .run_if(on_event::<Respawned>()),
+                download_chunks.before(world::update_active_chunks),
+                chunk_scheduler.after(world::update_active_chunks),
+            )
+                .run_if(in_state(AppState::Playing)),
         )
         .add_systems(
             Update,
-            (process_chunks, render_chunks).run_if(in_state(AppState::Playing)),
+            (process_chunks, render_chunks, sort_translucent_sections)
+                .chain()
+                .run_if(in_state(AppState::Playing)),
         )
         .add_systems(
             PostUpdate,
             unrender_chunks.run_if(in_state(AppState::Playing)),
         )
-        .add_systems(Startup, load_materials)
+        .add_systems(Startup, load_materials.after(block_atlas::load_block_atlas))
         .add_systems(OnExit(AppState::Playing), cleanup_chunks);
 }
 
+/// Everything kept about the chunks of the current world.
+#[derive(SystemParam)]
+struct ChunkState<'w> {
+    active_chunks: ResMut<'w, ActivePlayerChunks>,
+    shown: ResMut<'w, ShownPlayerChunks>,
+    world_data: ResMut<'w, WorldChunkData>,
+    graphs: ResMut<'w, SectionGraphs>,
+    cache: ResMut<'w, SectionMeshCache>,
+    tasks: ResMut<'w, ChunkBuilderTasks>,
+}
+
+impl ChunkState<'_> {
+    fn clear(&mut self) {
+        self.active_chunks.chunks.clear();
+        self.world_data.clear();
+        self.shown.renderized.clear();
+        self.graphs.clear();
+        self.cache.sections.clear();
+        self.tasks.tasks.clear();
+        self.tasks.pending.clear();
+    }
+}
+
 fn cleanup_chunks(
     mut commands: Commands,
-    mut active_chunks: ResMut<ActivePlayerChunks>,
-    loaded_q: Query<(Entity, &ParentChunk)>,
-    mut shown: ResMut<ShownPlayerChunks>,
-    mut world_data: ResMut<WorldChunkData>,
+    loaded_q: Query<Entity, With<SectionPos>>,
+    mut chunks: ChunkState,
 ) {
-    for (entity, _) in loaded_q.iter() {
+    for entity in loaded_q.iter() {
         commands.entity(entity).despawn_recursive();
     }
 
     // bye :c
-    active_chunks.chunks.clear();
-    world_data.loaded_column.clear();
-    shown.renderized.clear();
+    chunks.clear();
 }
 
-fn build_material_by_color(base_color: Color) -> StandardMaterial {
+fn build_block_material(atlas: Handle<Image>, alpha_mode: AlphaMode) -> StandardMaterial {
+    let opaque = alpha_mode == AlphaMode::Opaque;
+
     StandardMaterial {
-        base_color,
+        base_color_texture: Some(atlas),
+        alpha_mode,
 
-        alpha_mode: AlphaMode::Blend,
-        double_sided: true,
-        cull_mode: Some(Face::Back),
+        // plants are single quads and water is seen from below, so only opaque blocks cull
+        double_sided: !opaque,
+        cull_mode: if opaque { Some(Face::Back) } else { None },
         ..default()
     }
 }
 
-fn load_materials(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
-    let dirt = materials.add(build_material_by_color(Color::srgb_u8(138, 69, 58)));
-    let grass = materials.add(build_material_by_color(Color::srgb_u8(1, 200, 0)));
-    let endstone_material = materials.add(build_material_by_color(Color::srgb_u8(216, 214, 164)));
-    let netherbrick_material = materials.add(build_material_by_color(Color::srgb_u8(63, 42, 35)));
-    let any_block = materials.add(build_material_by_color(Color::srgb_u8(255, 255, 255)));
-    let bedrock = materials.add(build_material_by_color(Color::srgb_u8(0, 0, 0)));
-    let cobblestone = materials.add(build_material_by_color(Color::srgb_u8(150, 150, 150)));
-    let water = materials.add(build_material_by_color(Color::srgba_u8(0, 0, 255, 150)));
-    let jungle_wood = materials.add(build_material_by_color(Color::srgb_u8(139, 69, 19)));
-    let gravel = materials.add(build_material_by_color(Color::srgb_u8(104, 104, 104)));
-
-    let mut blocks = HashMap::new();
-
-    blocks.insert(121, endstone_material);
-    blocks.insert(112, netherbrick_material);
-    blocks.insert(2, grass);
-    blocks.insert(3, dirt);
-    blocks.insert(7, bedrock);
-    blocks.insert(1, cobblestone);
-    blocks.insert(9, water);
-    blocks.insert(17, jungle_wood);
-    blocks.insert(13, gravel);
-
-    commands.insert_resource(Materials { blocks, any_block });
+fn load_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    atlas: Res<BlockAtlas>,
+) {
+    let solid = materials.add(build_block_material(atlas.image.clone(), AlphaMode::Opaque));
+    let cutout = materials.add(build_block_material(
+        atlas.image.clone(),
+        AlphaMode::Mask(0.5),
+    ));
+    let translucent = materials.add(build_block_material(atlas.image.clone(), AlphaMode::Blend));
+
+    commands.insert_resource(Materials {
+        solid,
+        cutout,
+        translucent,
+    });
 }
 
+/// Chunk x, section index and chunk z of a section mesh.
 #[derive(Component, Debug)]
-struct ParentChunk {
-    pub of: ChunkVec2,
-}
-
-fn is_chunk_in_front(
-    player_pos: Vec3,
-    forward_dir: Vec3,
-    chunk_pos: ChunkVec2,
-    fov_radians: f32,
-) -> bool {
-    // Calculate the chunk center position
-    let chunk_center = Vec3::new(
-        chunk_pos.x as f32 * 16.0 + 8.0, // Chunk center X
-        player_pos.y,                    // Match Y with camera to simplify calculations
-        chunk_pos.z as f32 * 16.0 + 8.0, // Chunk center Z
-    );
-
-    // Vector from camera to the chunk
-    let to_chunk = chunk_center - player_pos;
-
-    // Normalize the vector from camera to chunk
-    let to_chunk_normalized = to_chunk.normalize();
-
-    // Calculate the dot product between the forward direction and the vector to the chunk
-    let dot_product = forward_dir.dot(to_chunk_normalized);
-    let cos_fov = fov_radians.cos();
-
-    // If the dot product is greater than the cosine of the FOV, the chunk is in front
-    dot_product > cos_fov
-}
+pub struct SectionPos(pub IVec3);
 
+/// Every column within render distance stays meshed, whether it is in view or not.
+/// What is actually drawn is decided per section by `section_culling`.
 fn chunk_scheduler(
     active: Res<ActivePlayerChunks>,
-    mut shown: ResMut<ShownPlayerChunks>,
-    player_q: Query<&Transform, (With<Player>, Changed<Transform>)>,
-    cam_q: Query<
-        &Projection,
-        (
-            With<crate::plugin::play::player::WorldModelCamera>,
-            Without<Player>,
-        ),
-    >,
-
+    world_data: Res<WorldChunkData>,
+    cache: Res<SectionMeshCache>,
+    shown: Res<ShownPlayerChunks>,
+    mut tasks: ResMut<ChunkBuilderTasks>,
     mut render_writer: EventWriter<RenderChunk>,
     mut unrender_writer: EventWriter<UnrenderChunk>,
 ) {
-    let projection = cam_q.single();
-
-    let Projection::Perspective(pespective) = projection else {
-        unreachable!("No perspective projection found");
-    };
-
-    let mut to_render_count = 0;
-    let mut to_unrender_count = 0;
-
-    // The part of the frustum that is in front of the player
-    let mut frustum = HashSet::new();
-    if let Ok(player) = player_q.get_single() {
-        let forward_dir = *player.forward();
-
-        for dist_chunk in active.chunks.keys() {
-            if is_chunk_in_front(
-                player.translation,
-                forward_dir,
-                *dist_chunk,
-                pespective.fov * 2.0,
-            ) {
-                frustum.insert(*dist_chunk);
-            }
+    let mut to_render = vec![];
+    for chunk in active.chunks.keys() {
+        if tasks.pending.contains(chunk) {
+            continue;
         }
-    }
 
-    let mut frustum_to_render = vec![];
-    for chunk in frustum.iter() {
-        if !shown.renderized.contains(chunk) {
-            frustum_to_render.push(RenderChunk { pos: *chunk });
-            to_render_count += 1;
+        let sections = cache.stale_sections(*chunk, &world_data);
+        if sections.is_empty() {
+            continue;
         }
-    }
-
-    render_writer.send_batch(frustum_to_render);
-
-    let mut culled_frustum = vec![];
 
-    for chunk in shown.renderized.iter() {
-        if !frustum.contains(chunk) {
-            unrender_writer.send(UnrenderChunk { pos: *chunk });
-            to_unrender_count += 1;
-            culled_frustum.push(*chunk);
-        }
+        tasks.pending.insert(*chunk);
+        to_render.push(RenderChunk {
+            pos: *chunk,
+            sections,
+        });
     }
 
-    shown.renderized.extend(frustum);
-
-    for chunk in culled_frustum.iter() {
-        shown.renderized.remove(chunk);
+    // only columns the world forgot lose their meshes
+    let to_unrender = shown
+        .renderized
+        .iter()
+        .filter(|chunk| !world_data.loaded_column.contains_key(*chunk))
+        .map(|chunk| UnrenderChunk { pos: *chunk })
+        .collect::<Vec<_>>();
+
+    if !to_render.is_empty() || !to_unrender.is_empty() {
+        info!(
+            "To render: {}, to unrender: {}",
+            to_render.len(),
+            to_unrender.len()
+        );
     }
 
-    if to_render_count > 0 || to_unrender_count > 0 {
-        info!("To render: {to_render_count}, to unrender: {to_unrender_count}");
-    }
+    render_writer.send_batch(to_render);
+    unrender_writer.send_batch(to_unrender);
 }
 
 fn unrender_chunks(
     mut commands: Commands,
-    loaded_q: Query<(Entity, &ParentChunk)>,
+    mut graphs: ResMut<SectionGraphs>,
+    mut cache: ResMut<SectionMeshCache>,
+    mut shown: ResMut<ShownPlayerChunks>,
     mut to_unrender: EventReader<UnrenderChunk>,
 ) {
-    for (chunk_pos, _) in to_unrender.par_read() {
-        for (entity, parent) in loaded_q.iter() {
-            if parent.of == chunk_pos.pos {
-                commands.entity(entity).despawn_recursive();
-            }
+    for chunk_pos in to_unrender.read() {
+        graphs.remove_column(chunk_pos.pos);
+        shown.renderized.remove(&chunk_pos.pos);
+
+        for entity in cache.remove_column(chunk_pos.pos) {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
 
 fn process_chunks(
-    mut rendered_writer: EventWriter<RenderedBlock>,
-    active_player_chunks: Res<ActivePlayerChunks>,
+    mut rendered_writer: EventWriter<RenderedColumn>,
+    mut graphs: ResMut<SectionGraphs>,
+    world_data: Res<WorldChunkData>,
+    atlas: Res<BlockAtlas>,
+    models: Res<BlockModels>,
     mut to_render: EventReader<RenderChunk>,
     mut tasks: ResMut<ChunkBuilderTasks>,
 ) {
@@ -273,7 +328,8 @@ fn process_chunks(
         for (x, z) in directions.iter() {
             let neighbor_pos = IVec3::new(pos.x + x, 0, pos.z + z);
             let chpos = ChunkVec2::new_local(neighbor_pos.x, neighbor_pos.z);
-            if let Some(neighbor) = active_player_chunks.chunks.get(&chpos) {
+            // columns share their sections, this doesn't copy any blocks
+            if let Some(neighbor) = world_data.loaded_column.get(&chpos) {
                 neighbors.insert(neighbor_pos, neighbor.clone());
             }
         }
@@ -282,45 +338,79 @@ fn process_chunks(
     };
 
     for (pos, _) in to_render.par_read() {
-        if let Some(column) = active_player_chunks.chunks.get(&pos.pos) {
-            let column = column.clone();
-            let parent_chunk = pos.pos;
-            let neighbors = build_neighbors(parent_chunk);
-
-            let task = poll.spawn(async move {
-                let mut constructor = ChunkConstructor::new(&column, neighbors);
-                let result = constructor.construct();
-
-                let mut to_send = vec![];
-
-                for (mesh_recipe, transform, id) in result {
-                    let mut mesh = Mesh::new(
-                        PrimitiveTopology::TriangleList,
-                        RenderAssetUsages::RENDER_WORLD,
-                    );
-
-                    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_recipe.vertices);
-                    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, mesh_recipe.normals);
-                    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, mesh_recipe.uv);
-                    mesh.insert_indices(Indices::U32(mesh_recipe.indices));
-
-                    to_send.push(RenderedBlock {
-                        mesh,
-                        material_id: id as _,
-                        transform,
-                        parent_chunk,
-                    });
-                }
+        let Some(column) = world_data.loaded_column.get(&pos.pos).cloned() else {
+            tasks.pending.remove(&pos.pos);
+            continue;
+        };
 
-                to_send
-            });
-            tasks.tasks.push(task);
-        }
+        let parent_chunk = pos.pos;
+        let sections = pos.sections.clone();
+        let generations = sections
+            .iter()
+            .map(|section| {
+                let section_pos = IVec3::new(parent_chunk.x, *section as _, parent_chunk.z);
+                (*section, world_data.generation(section_pos))
+            })
+            .collect();
+        let neighbors = build_neighbors(parent_chunk);
+        let layout = atlas.layout.clone();
+        let models = models.baked.clone();
+
+        let task = poll.spawn(async move {
+            let mut constructor = ChunkConstructor::new(&column, neighbors, layout, models);
+            let result = constructor.construct(&sections);
+
+            let mut to_send = vec![];
+            let column_graphs = constructor.visibility_graphs(&sections);
+
+            for section in result {
+                // translucent meshes stay on the CPU too, their quads get re-sorted
+                let usage = match section.layer {
+                    BlockLayer::Translucent => RenderAssetUsages::default(),
+                    _ => RenderAssetUsages::RENDER_WORLD,
+                };
+
+                let recipe = section.mesh;
+                let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, usage);
+
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, recipe.vertices);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, recipe.normals);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, recipe.uv);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, recipe.colors);
+                mesh.insert_indices(Indices::U32(recipe.indices));
+
+                to_send.push(RenderedSection {
+                    mesh,
+                    transform: section.transform,
+                    section: IVec3::new(parent_chunk.x, section.section as _, parent_chunk.z),
+                    layer: section.layer,
+                });
+            }
+
+            BuiltColumn {
+                column: RenderedColumn {
+                    pos: parent_chunk,
+                    sections: to_send,
+                    generations,
+                },
+                graphs: column_graphs
+                    .into_iter()
+                    .map(|(section, graph)| {
+                        (
+                            IVec3::new(parent_chunk.x, section as _, parent_chunk.z),
+                            graph,
+                        )
+                    })
+                    .collect(),
+            }
+        });
+        tasks.tasks.push(task);
     }
 
     let mut to_remove = vec![];
     let mut rendered = vec![];
-    for (idx, task) in tasks.tasks.iter_mut().enumerate() {
+    for idx in 0..tasks.tasks.len() {
+        let task = &mut tasks.tasks[idx];
         let status = if task.is_finished() {
             Some(block_on(task))
         } else {
@@ -329,7 +419,12 @@ fn process_chunks(
 
         match status {
             Some(res) => {
-                rendered.extend(res);
+                tasks.pending.remove(&res.column.pos);
+
+                for (pos, graph) in res.graphs {
+                    graphs.insert(pos, graph);
+                }
+                rendered.push(res.column);
 
                 to_remove.push(idx);
             }
@@ -349,30 +444,130 @@ fn render_chunks(
     mut meshes: ResMut<Assets<Mesh>>,
     mut commands: Commands,
     materials_pre: Res<Materials>,
-    mut to_render: EventReader<RenderedBlock>,
+    world_data: Res<WorldChunkData>,
+    mut cache: ResMut<SectionMeshCache>,
+    mut shown: ResMut<ShownPlayerChunks>,
+    mut to_render: EventReader<RenderedColumn>,
 ) {
-    let mut to_spawn = vec![];
-
-    for (block, _) in to_render.par_read() {
-        let block = block.to_owned();
-
-        to_spawn.push((
-            MaterialMeshBundle {
-                mesh: meshes.add(block.mesh),
-                material: materials_pre.get_material_by_block_id(block.material_id),
-                transform: block.transform,
-                ..Default::default()
-            },
-            WorldLayer,
-            ParentChunk {
-                of: block.parent_chunk,
-            },
-        ));
+    let mut rendered = 0;
+
+    for column in to_render.read() {
+        // unloaded while it was being meshed
+        if !world_data.loaded_column.contains_key(&column.pos) {
+            continue;
+        }
+
+        // only the rebuilt sections are replaced, the rest of the column stays as it is
+        for (idx, _) in column.generations.iter() {
+            let pos = IVec3::new(column.pos.x, *idx as _, column.pos.z);
+
+            for entity in cache.remove_section(pos) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let mut entities = HashMap::<IVec3, Vec<Entity>>::new();
+
+        for section in column.sections.iter() {
+            let section = section.to_owned();
+
+            let mut entity = commands.spawn((
+                MaterialMeshBundle {
+                    mesh: meshes.add(section.mesh),
+                    material: materials_pre.of(section.layer).clone_weak(),
+                    transform: section.transform,
+                    ..Default::default()
+                },
+                WorldLayer,
+                SectionPos(section.section),
+            ));
+
+            if section.layer == BlockLayer::Translucent {
+                entity.insert(TranslucentSection);
+            }
+
+            entities
+                .entry(section.section)
+                .or_default()
+                .push(entity.id());
+            rendered += 1;
+        }
+
+        for (idx, generation) in column.generations.iter() {
+            let pos = IVec3::new(column.pos.x, *idx as _, column.pos.z);
+
+            cache.sections.insert(
+                pos,
+                CachedSection {
+                    generation: *generation,
+                    entities: entities.remove(&pos).unwrap_or_default(),
+                },
+            );
+        }
+
+        shown.renderized.insert(column.pos);
+    }
+
+    if rendered > 0 {
+        info!("Rendering {rendered} section meshes");
     }
+}
+
+/// Bevy sorts whole sections back to front, the quads inside a section are sorted here,
+/// again whenever the camera enters another block.
+fn sort_translucent_sections(
+    mut meshes: ResMut<Assets<Mesh>>,
+    camera_q: Query<&GlobalTransform, With<crate::plugin::play::player::WorldModelCamera>>,
+    sections_q: Query<(&Handle<Mesh>, &Transform), With<TranslucentSection>>,
+    added_q: Query<(), Added<TranslucentSection>>,
+    mut last_block: Local<Option<IVec3>>,
+) {
+    let Ok(camera) = camera_q.get_single() else {
+        return;
+    };
+
+    let eye = camera.translation();
+    let block = eye.floor().as_ivec3();
+
+    if *last_block == Some(block) && added_q.is_empty() {
+        return;
+    }
+    *last_block = Some(block);
+
+    for (handle, transform) in sections_q.iter() {
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
 
-    if !to_spawn.is_empty() {
-        info!("Rendering {}", to_spawn.len());
-        commands.spawn_batch(to_spawn);
+        let local_eye = eye - transform.translation;
+
+        // every quad is four vertices, see BlockMesh
+        let mut quads = positions
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(idx, quad)| {
+                let center = quad.iter().map(|v| Vec3::from(*v)).sum::<Vec3>() / 4.0;
+                (idx as u32, center.distance_squared(local_eye))
+            })
+            .collect::<Vec<_>>();
+
+        quads.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        let indices = quads
+            .into_iter()
+            .flat_map(|(quad, _)| {
+                let base = quad * 4;
+                [base, base + 1, base + 2, base + 2, base + 3, base]
+            })
+            .collect::<Vec<_>>();
+
+        mesh.insert_indices(Indices::U32(indices));
     }
 }
 
@@ -383,9 +578,7 @@ fn download_chunks(
     for chunk_pkt in chunks_received.read() {
         let column = &chunk_pkt.smp_chunk;
 
-        chunk_data
-            .loaded_column
-            .insert(ChunkVec2::new_local(column.x, column.z), column.clone());
+        chunk_data.insert_column(column.clone());
 
         debug!(
             "Total chunks loaded until now: {}",
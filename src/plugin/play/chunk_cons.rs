@@ -7,8 +7,12 @@ use bevy::{
 };
 use gyra_proto::smp;
 use std::hash::Hash;
+use std::sync::Arc;
 
-use super::block_builder::Block;
+use super::block_atlas::AtlasLayout;
+use super::block_builder::{Block, BlockFace, BlockLayer, Shape};
+use super::block_model::{BakedModel, BakedModels};
+use super::section_culling::VisGraph;
 
 #[derive(Default, Debug, Clone)]
 pub struct BlockMesh {
@@ -16,24 +20,63 @@ pub struct BlockMesh {
     pub vertices: Vec<[f32; 3]>,
     // lighting normals
     pub normals: Vec<[f32; 3]>,
-    // uv coordinates
+    // uv coordinates inside the block atlas
     pub uv: Vec<[f32; 2]>,
+    // tint colors
+    pub colors: Vec<[f32; 4]>,
     // indices
     pub indices: Vec<u32>,
 }
 
+impl BlockMesh {
+    /// Copies `other` into this mesh, moved by `offset`.
+    pub fn append(&mut self, other: BlockMesh, offset: Vec3) {
+        let base_index = self.vertices.len() as u32;
+
+        self.vertices.extend(
+            other
+                .vertices
+                .into_iter()
+                .map(|vertex| (Vec3::from(vertex) + offset).to_array()),
+        );
+        self.normals.extend(other.normals);
+        self.uv.extend(other.uv);
+        self.colors.extend(other.colors);
+        self.indices
+            .extend(other.indices.into_iter().map(|index| base_index + index));
+    }
+}
+
+/// Everything of one section that is drawn in the same layer.
+#[derive(Debug, Clone)]
+pub struct SectionMesh {
+    pub section: usize,
+    pub layer: BlockLayer,
+    pub transform: Transform,
+    pub mesh: BlockMesh,
+}
+
 pub struct ChunkConstructor<'a> {
     pub column: &'a smp::ChunkColumn,
     pub pos: IVec3,
     pub neighbors: HashMap<IVec3, smp::ChunkColumn>,
+    pub atlas: Arc<AtlasLayout>,
+    pub models: Arc<BakedModels>,
 }
 
 impl<'a> ChunkConstructor<'a> {
-    pub fn new(column: &'a smp::ChunkColumn, neighbors: HashMap<IVec3, smp::ChunkColumn>) -> Self {
+    pub fn new(
+        column: &'a smp::ChunkColumn,
+        neighbors: HashMap<IVec3, smp::ChunkColumn>,
+        atlas: Arc<AtlasLayout>,
+        models: Arc<BakedModels>,
+    ) -> Self {
         Self {
             column,
             pos: IVec3::new(column.x, 0, column.z),
             neighbors,
+            atlas,
+            models,
         }
     }
 
@@ -42,7 +85,8 @@ impl<'a> ChunkConstructor<'a> {
         if pos.x >= 0 && pos.x < 16 && pos.y >= 0 && pos.y < 16 && pos.z >= 0 && pos.z < 16 {
             let column_section = self.column.sections[section].as_ref()?;
             let id = column_section.block_id(pos.x as _, pos.y as _, pos.z as _);
-            Some(Block::from_id(id))
+            let metadata = column_section.metadata(pos.x as _, pos.y as _, pos.z as _);
+            Some(Block::new(id, metadata))
         } else {
             let chunk_offset = IVec3::new(
                 if pos.x < 0 {
@@ -71,7 +115,12 @@ impl<'a> ChunkConstructor<'a> {
                     neighbor_pos.y as _,
                     neighbor_pos.z as _,
                 );
-                Some(Block::from_id(id))
+                let metadata = neighbor_section.metadata(
+                    neighbor_pos.x as _,
+                    neighbor_pos.y as _,
+                    neighbor_pos.z as _,
+                );
+                Some(Block::new(id, metadata))
             } else {
                 None
             }
@@ -79,23 +128,88 @@ impl<'a> ChunkConstructor<'a> {
     }
 
     fn build_block_mesh(&self, block: &Block, pos: IVec3, section: usize) -> BlockMesh {
-        if !block.shape().is_solid() {
-            return BlockMesh::default();
+        match block.shape() {
+            Shape::Air => BlockMesh::default(),
+            Shape::Cube => self.build_cube_mesh(block, pos, section),
+            Shape::Model => {
+                let world_pos = self.pos * 16 + IVec3::new(0, section as i32 * 16, 0) + pos;
+                let model = block
+                    .state(|face| {
+                        self.block_at(section, pos + face.normal())
+                            .unwrap_or_default()
+                    })
+                    .and_then(|state| self.models.get(&state, world_pos));
+
+                match model {
+                    Some(model) => self.build_model_mesh(block, model, pos, section),
+                    // no resource pack, or a block it has no model for
+                    None => self.build_cube_mesh(block, pos, section),
+                }
+            }
+        }
+    }
+
+    fn build_model_mesh(
+        &self,
+        block: &Block,
+        model: &BakedModel,
+        pos: IVec3,
+        section: usize,
+    ) -> BlockMesh {
+        let mut mesh = BlockMesh::default();
+
+        for quad in model.quads.iter() {
+            if let Some(cull) = quad.cull {
+                if let Some(adjacent_block) = self.block_at(section, pos + cull.normal()) {
+                    if adjacent_block.culls_face_of(block) {
+                        continue;
+                    }
+                }
+            }
+
+            let base_index = mesh.vertices.len() as u32;
+
+            mesh.vertices.extend_from_slice(&quad.vertices);
+            mesh.normals.extend_from_slice(&[quad.normal; 4]);
+            mesh.uv.extend_from_slice(&quad.uv);
+
+            let tint = match quad.tinted {
+                true => block.tint(quad.face).unwrap_or([1.0; 4]),
+                false => [1.0; 4],
+            };
+            mesh.colors.extend_from_slice(&[tint; 4]);
+
+            mesh.indices.extend_from_slice(&[
+                base_index,
+                base_index + 1,
+                base_index + 2,
+                //----
+                base_index + 2,
+                base_index + 3,
+                base_index,
+            ]);
         }
 
+        mesh
+    }
+
+    fn build_cube_mesh(&self, block: &Block, pos: IVec3, section: usize) -> BlockMesh {
         let mut vertices = Vec::with_capacity(24);
         let mut normals = Vec::with_capacity(24);
         let mut uv = Vec::with_capacity(24);
+        let mut colors = Vec::with_capacity(24);
         let mut indices = Vec::with_capacity(36);
 
-        const NATURAL_UV: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        // Vertices go bottom-left, bottom-right, top-right, top-left, and textures start at the top.
+        const NATURAL_UV: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+        const NO_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
 
         let base_index = vertices.len() as u32;
 
         let directions = [
             // Front
             (
-                IVec3::new(0, 0, 1),
+                BlockFace::South,
                 [0.0, 0.0, 1.0],
                 [
                     [0.0, 0.0, 1.0],
@@ -106,7 +220,7 @@ impl<'a> ChunkConstructor<'a> {
             ),
             // Back
             (
-                IVec3::new(0, 0, -1),
+                BlockFace::North,
                 [0.0, 0.0, -1.0],
                 [
                     [1.0, 0.0, 0.0],
@@ -118,7 +232,7 @@ impl<'a> ChunkConstructor<'a> {
 
             // Top
             (
-                IVec3::new(0, 1, 0),
+                BlockFace::Up,
                 [0.0, 1.0, 0.0],
                 [
                     [0.0, 1.0, 1.0],
@@ -130,7 +244,7 @@ impl<'a> ChunkConstructor<'a> {
 
             // Bottom
             (
-                IVec3::new(0, -1, 0),
+                BlockFace::Down,
                 [0.0, -1.0, 0.0],
                 [
                     [0.0, 0.0, 0.0],
@@ -142,7 +256,7 @@ impl<'a> ChunkConstructor<'a> {
 
             // Left
             (
-                IVec3::new(-1, 0, 0),
+                BlockFace::West,
                 [-1.0, 0.0, 0.0],
                 [
                     [0.0, 0.0, 0.0],
@@ -154,7 +268,7 @@ impl<'a> ChunkConstructor<'a> {
 
             // Right
             (
-                IVec3::new(1, 0, 0),
+                BlockFace::East,
                 [1.0, 0.0, 0.0],
                 [
                     [1.0, 0.0, 1.0],
@@ -165,7 +279,7 @@ impl<'a> ChunkConstructor<'a> {
             ),
         ];
 
-        for (offset, normal, face_vertices) in directions.iter() {
+        for (face, normal, face_vertices) in directions.iter() {
             let idx = base_index + vertices.len() as u32;
 
             let mut add_face = |face_vertices: &[[f32; 3]],
@@ -177,10 +291,18 @@ impl<'a> ChunkConstructor<'a> {
                     normals.push(normal);
                 }
 
-                for &uv_coord in uv_coords {
-                    uv.push(uv_coord);
+                // map the natural coordinates into the tile of the atlas
+                let tile = self.atlas.uv(block.texture(*face));
+                for &[u, v] in uv_coords {
+                    uv.push([
+                        tile.min.x + u * tile.width(),
+                        tile.min.y + v * tile.height(),
+                    ]);
                 }
 
+                let tint = block.tint(*face).unwrap_or(NO_TINT);
+                colors.extend_from_slice(&[tint; 4]);
+
                 indices.extend_from_slice(&[
                     base_index,
                     base_index + 1,
@@ -192,10 +314,10 @@ impl<'a> ChunkConstructor<'a> {
                 ]);
             };
 
-            let adjacent_pos = pos + *offset;
+            let adjacent_pos = pos + face.normal();
 
             if let Some(adjacent_block) = self.block_at(section, adjacent_pos) {
-                if adjacent_block.shape().is_solid() {
+                if adjacent_block.culls_face_of(block) {
                     continue;
                 }
             }
@@ -207,11 +329,30 @@ impl<'a> ChunkConstructor<'a> {
             vertices,
             normals,
             uv,
+            colors,
             indices,
         }
     }
 
-    pub fn construct(&mut self) -> Vec<(BlockMesh, Transform, u16)> {
+    /// Visgraphs of every section this column has, empty ones are left out.
+    pub fn visibility_graphs(&self, sections: &[usize]) -> Vec<(usize, VisGraph)> {
+        sections
+            .iter()
+            .copied()
+            .filter(|idx| self.column.sections[*idx].is_some())
+            .map(|idx| {
+                let graph = VisGraph::compute(|pos| {
+                    self.block_at(idx, pos)
+                        .is_some_and(|block| block.is_opaque())
+                });
+
+                (idx, graph)
+            })
+            .collect()
+    }
+
+    /// Meshes the given sections of the column, the others are left out.
+    pub fn construct(&mut self, sections: &[usize]) -> Vec<SectionMesh> {
         let mut meshes = Vec::new();
 
         let cull_directions = vec![
@@ -228,6 +369,7 @@ impl<'a> ChunkConstructor<'a> {
             .sections
             .iter()
             .enumerate()
+            .filter(|(idx, _)| sections.contains(idx))
             .filter_map(|(idx, section)| Some((idx, section.as_ref()?)))
         {
             info!("Rendering section: {idx}");
@@ -237,18 +379,20 @@ impl<'a> ChunkConstructor<'a> {
                     (0..16).map(move |z| {
                         let pos = IVec3::new(x, y, z);
                         let block_id = section.block_id(x as _, y as _, z as _);
-                        let block = Block::from_id(block_id);
+                        let metadata = section.metadata(x as _, y as _, z as _);
+                        let block = Block::new(block_id, metadata);
 
-                        (pos, block, block_id)
+                        (pos, block)
                     })
                 })
             });
 
-            let section_pos = self.pos.as_vec3().with_y(idx as _) * 16.0;
+            // centred, so the transparent pass sorts sections by their middle
+            let section_center = self.pos.as_vec3().with_y(idx as _) * 16.0 + 8.0;
 
             let blocks = blocks
                 .into_iter()
-                .filter(|(_, block, _)| block.shape().is_visible());
+                .filter(|(_, block)| block.shape().is_visible());
 
             // The check
             /*
@@ -261,41 +405,44 @@ impl<'a> ChunkConstructor<'a> {
 
             let mut edge = HashMap::new();
 
-            for (block_pos, block, id) in blocks {
-                let mut has_adjacent_air = false;
-                let mut has_adjacent_solid = false;
-
-                for direction in cull_directions.iter() {
-                    // we need to check if there is a air adjacent to the block
+            for (block_pos, block) in blocks {
+                // one face that isn't hidden is enough, stacked cactus and hanging vines
+                // may not touch a full cube at all
+                let has_visible_face = cull_directions.iter().any(|direction| {
                     let adjacent_pos = block_pos + *direction;
 
-                    if let Some(adjacent_block) = self.block_at(idx, adjacent_pos) {
-                        if adjacent_block.shape().is_visible() {
-                            has_adjacent_solid = true;
-                        } else {
-                            has_adjacent_air = true;
-                        }
-                    } else {
-                        has_adjacent_air = true;
-                    }
-                }
+                    // slabs, torches, glass and water don't hide what is behind them
+                    self.block_at(idx, adjacent_pos)
+                        .is_none_or(|adjacent_block| !adjacent_block.culls_face_of(&block))
+                });
 
-                if has_adjacent_air && has_adjacent_solid {
-                    edge.insert(block_pos, (block, id));
+                if has_visible_face {
+                    edge.insert(block_pos, block);
                 }
             }
 
-            for (pos, (block, id)) in edge {
+            let mut layers = HashMap::<BlockLayer, BlockMesh>::new();
+
+            for (pos, block) in edge {
                 let mesh = self.build_block_mesh(&block, pos, idx);
 
                 if mesh.vertices.is_empty() {
                     continue;
                 }
 
-                let pos = section_pos + pos.as_vec3();
-                let transform = Transform::from_translation(pos);
+                layers
+                    .entry(block.layer())
+                    .or_default()
+                    .append(mesh, pos.as_vec3() - 8.0);
+            }
 
-                meshes.push((mesh, transform, id));
+            for (layer, mesh) in layers {
+                meshes.push(SectionMesh {
+                    section: idx,
+                    layer,
+                    transform: Transform::from_translation(section_center),
+                    mesh,
+                });
             }
         }
 
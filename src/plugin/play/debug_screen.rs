@@ -137,6 +137,7 @@ fn update_fps(diagnostics: Res<DiagnosticsStore>, mut fps_text: Query<&mut Text,
 fn update_position_data(
     mut position_text: Query<&mut Text, With<PositionText>>,
     player_transform: Query<&Transform, With<player::Player>>,
+    world_data: Res<WorldChunkData>,
 ) {
     let mut position_text = position_text.single_mut();
 
@@ -148,6 +149,21 @@ fn update_position_data(
         pos.x, pos.y, pos.z, rot.x, rot.y, rot.z, rot.w,
         (pos.x / 16.0).floor(), (pos.z / 16.0).floor()
     );
+
+    let block_pos = pos.floor().as_ivec3();
+    let below = block_pos - IVec3::Y;
+
+    if let (Some(block), Some(light), Some(biome), Some(height)) = (
+        world_data.block_at(below),
+        world_data.light_at(block_pos),
+        world_data.biome_at(block_pos.x, block_pos.z),
+        world_data.heightmap_at(block_pos.x, block_pos.z),
+    ) {
+        position_text.sections[1].value += &format!(
+            ", on: {}:{}, lc: {}/{}, bm: {biome}, hm: {height}",
+            block.id, block.metadata, light.sky, light.block
+        );
+    }
 }
 
 fn spawn(mut commands: Commands) {
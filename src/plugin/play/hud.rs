@@ -0,0 +1,425 @@
+use super::inventory::{self, Inventory, ARMOR_START, HOTBAR_START};
+use super::inventory_screen::{HOTBAR_KEYS, SLOT_SIZE};
+use super::player::GameMode;
+use crate::components::MainCamera;
+use crate::message::{ClientMessage, ServerMessage};
+use crate::plugin::CursorState;
+use crate::state::AppState;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy_cosmic_edit::FocusedWidget;
+
+const BAR_WIDTH: f32 = 178.0;
+const BAR_HEIGHT: f32 = 8.0;
+
+/// Health, food and experience, from `UpdateHealth` and `SetExperience`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerStatus {
+    pub health: f32,
+    pub food: i32,
+    pub saturation: f32,
+    /// progress towards the next level, from 0 to 1
+    pub experience: f32,
+    pub level: i32,
+    pub total_experience: i32,
+}
+
+impl Default for PlayerStatus {
+    fn default() -> Self {
+        Self {
+            health: 20.0,
+            food: 20,
+            saturation: 5.0,
+            experience: 0.0,
+            level: 0,
+            total_experience: 0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stat {
+    Health,
+    Food,
+    Armor,
+    Experience,
+}
+
+/// The filled part of a bar.
+#[derive(Component)]
+struct StatBar(Stat);
+
+/// Holds a bar, hidden when the game mode doesn't need it.
+#[derive(Component)]
+struct SurvivalOnly;
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct HotbarSlot(u8);
+
+#[derive(Component)]
+struct HotbarLabel(u8);
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PlayerStatus>()
+        .add_systems(OnEnter(AppState::Playing), spawn)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                select_slot,
+                update_hotbar,
+                receive_status.before(update_bars),
+                update_bars,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut status: ResMut<PlayerStatus>,
+    hud: Query<Entity, With<Hud>>,
+) {
+    *status = PlayerStatus::default();
+
+    for entity in hud.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn receive_status(mut server_reader: EventReader<ServerMessage>, mut status: ResMut<PlayerStatus>) {
+    for message in server_reader.read() {
+        match message {
+            // the server sends health again once the player is back
+            ServerMessage::Respawn { .. } => *status = PlayerStatus::default(),
+
+            ServerMessage::UpdateHealth {
+                health,
+                food,
+                saturation,
+            } => {
+                status.health = *health;
+                status.food = *food;
+                status.saturation = *saturation;
+            }
+
+            ServerMessage::SetExperience { bar, level, total } => {
+                status.experience = *bar;
+                status.level = *level;
+                status.total_experience = *total;
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// Armour points an item gives when worn, out of 20.
+fn armor_points(id: u16) -> u8 {
+    // helmet, chestplate, leggings, boots
+    let points = match id {
+        298..=301 => [1, 3, 2, 1],
+        302..=305 => [2, 5, 4, 1],
+        306..=309 => [2, 6, 5, 2],
+        310..=313 => [3, 8, 6, 3],
+        314..=317 => [2, 5, 3, 1],
+        _ => return 0,
+    };
+
+    points[(id - 298) as usize % 4]
+}
+
+fn spawn_bar(parent: &mut ChildBuilder, stat: Stat, width: f32, color: Color) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(BAR_HEIGHT),
+                margin: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..default()
+        })
+        .with_children(|p| {
+            p.spawn((
+                StatBar(stat),
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn spawn_crosshair(parent: &mut ChildBuilder) {
+    for (width, height) in [(16.0, 2.0), (2.0, 16.0)] {
+        parent.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(width),
+                height: Val::Px(height),
+                left: Val::Percent(50.0),
+                top: Val::Percent(50.0),
+                margin: UiRect {
+                    left: Val::Px(-width / 2.0),
+                    top: Val::Px(-height / 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            background_color: Color::srgba(1.0, 1.0, 1.0, 0.8).into(),
+            ..default()
+        });
+    }
+}
+
+fn spawn_hotbar(parent: &mut ChildBuilder) {
+    parent.spawn(NodeBundle::default()).with_children(|p| {
+        for index in 0..9 {
+            p.spawn((
+                HotbarSlot(index),
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(SLOT_SIZE),
+                        height: Val::Px(SLOT_SIZE),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+                    border_color: Color::srgba(0.3, 0.3, 0.3, 0.8).into(),
+                    ..default()
+                },
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    HotbarLabel(index),
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center),
+                ));
+            });
+        }
+    });
+}
+
+fn spawn(mut commands: Commands, camera: Query<Entity, With<MainCamera>>) {
+    let mut hud = commands.spawn((
+        Hud,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                align_items: AlignItems::Center,
+                padding: UiRect::bottom(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
+    if let Ok(camera) = camera.get_single() {
+        hud.insert(TargetCamera(camera));
+    }
+
+    hud.with_children(|p| {
+        spawn_crosshair(p);
+
+        // armour over health, food next to it, like vanilla
+        p.spawn((SurvivalOnly, NodeBundle::default()))
+            .with_children(|p| {
+                spawn_bar(p, Stat::Armor, BAR_WIDTH, Color::srgb(0.75, 0.75, 0.8));
+                p.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(BAR_WIDTH),
+                        ..default()
+                    },
+                    ..default()
+                });
+            });
+
+        p.spawn((SurvivalOnly, NodeBundle::default()))
+            .with_children(|p| {
+                spawn_bar(p, Stat::Health, BAR_WIDTH, Color::srgb(0.85, 0.1, 0.1));
+                spawn_bar(p, Stat::Food, BAR_WIDTH, Color::srgb(0.7, 0.45, 0.15));
+            });
+
+        p.spawn((
+            SurvivalOnly,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
+                LevelText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::srgb(0.5, 1.0, 0.3),
+                        ..default()
+                    },
+                ),
+            ));
+
+            spawn_bar(
+                p,
+                Stat::Experience,
+                SLOT_SIZE * 9.0,
+                Color::srgb(0.5, 1.0, 0.3),
+            );
+        });
+
+        spawn_hotbar(p);
+    });
+}
+
+/// The mouse wheel and number keys pick the hotbar slot in hand.
+fn select_slot(
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor_state: Res<CursorState>,
+    focused_widget: Res<FocusedWidget>,
+    mut wheel: EventReader<MouseWheel>,
+    mut inventory: ResMut<Inventory>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    let scrolled: f32 = wheel.read().map(|event| event.y).sum();
+
+    // number keys swap items while a window is shown
+    if !cursor_state.is_locked || focused_widget.0.is_some() {
+        return;
+    }
+
+    let mut selected = inventory.selected;
+
+    if let Some(key) = HOTBAR_KEYS.iter().position(|key| keys.just_pressed(*key)) {
+        selected = key as u8;
+    }
+
+    // scrolling up goes left
+    if scrolled != 0.0 {
+        selected = (selected as i32 - scrolled.signum() as i32).rem_euclid(9) as u8;
+    }
+
+    if selected != inventory.selected {
+        inventory.selected = selected;
+        message_writer.send(ClientMessage::HeldItemChange { slot: selected });
+    }
+}
+
+fn update_hotbar(
+    inventory: Res<Inventory>,
+    game_mode: Res<GameMode>,
+    mut hud: Query<&mut Visibility, With<Hud>>,
+    mut slots: Query<(&HotbarSlot, &mut BorderColor)>,
+    mut labels: Query<(&HotbarLabel, &mut Text)>,
+    spawned: Query<(), Added<HotbarLabel>>,
+) {
+    // spectators don't hold anything
+    let visibility = if *game_mode == GameMode::Spectator {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+
+    for mut hud_visibility in hud.iter_mut() {
+        if *hud_visibility != visibility {
+            *hud_visibility = visibility;
+        }
+    }
+
+    if !inventory.is_changed() && spawned.is_empty() {
+        return;
+    }
+
+    for (slot, mut border) in slots.iter_mut() {
+        border.0 = if slot.0 == inventory.selected {
+            Color::WHITE
+        } else {
+            Color::srgba(0.3, 0.3, 0.3, 0.8)
+        };
+    }
+
+    for (label, mut text) in labels.iter_mut() {
+        let slot = &inventory.player[HOTBAR_START + label.0 as usize];
+        text.sections[0].value = inventory::label(slot);
+    }
+}
+
+fn update_bars(
+    status: Res<PlayerStatus>,
+    inventory: Res<Inventory>,
+    game_mode: Res<GameMode>,
+    mut bars: Query<(&StatBar, &mut Style)>,
+    mut groups: Query<&mut Visibility, With<SurvivalOnly>>,
+    mut level: Query<&mut Text, With<LevelText>>,
+    spawned: Query<(), Added<StatBar>>,
+) {
+    let changed = status.is_changed() || inventory.is_changed() || game_mode.is_changed();
+    if !changed && spawned.is_empty() {
+        return;
+    }
+
+    // creative players can't be hurt and don't get hungry
+    let survival = matches!(*game_mode, GameMode::Survival | GameMode::Adventure);
+    for mut visibility in groups.iter_mut() {
+        *visibility = if survival {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let armor: u8 = inventory.player[ARMOR_START..ARMOR_START + 4]
+        .iter()
+        .filter_map(|slot| slot.0.as_ref())
+        .map(|item| armor_points(item.id))
+        .sum();
+
+    for (bar, mut style) in bars.iter_mut() {
+        let fill = match bar.0 {
+            Stat::Health => status.health / 20.0,
+            Stat::Food => status.food as f32 / 20.0,
+            Stat::Armor => armor as f32 / 20.0,
+            Stat::Experience => status.experience,
+        };
+
+        style.width = Val::Percent(fill.clamp(0.0, 1.0) * 100.0);
+    }
+
+    for mut text in level.iter_mut() {
+        text.sections[0].value = if status.level > 0 {
+            status.level.to_string()
+        } else {
+            String::new()
+        };
+    }
+}
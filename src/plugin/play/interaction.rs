@@ -0,0 +1,444 @@
+use super::block_builder::{Block, BlockFace};
+use super::physics::{self, PlayerBody, Tick};
+use super::player::{self, GameMode, Player};
+use super::raycast::{RaycastHit, TargetBlock};
+use super::world::WorldChunkData;
+use crate::message::{ClientMessage, DiggingStatus, ServerMessage};
+use crate::plugin::CursorState;
+use crate::state::AppState;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use gyra_proto::slot::Slot;
+use gyra_proto::smp::NetworkBlock;
+use std::collections::HashMap;
+
+// ticks to wait after breaking a block before digging the next one
+const DIG_COOLDOWN: u8 = 5;
+// ticks between placements while the button is held
+const PLACE_COOLDOWN: u8 = 4;
+// how long a guess waits for the server before it is undone
+const PREDICTION_TICKS: u8 = 40;
+
+/// What the player holds, used for dig speed and sent along with placements.
+#[derive(Resource, Debug, Default)]
+pub struct HeldItem {
+    pub slot: Slot,
+}
+
+/// A change made before the server agreed to it.
+#[derive(Debug, Clone, Copy)]
+struct Prediction {
+    block: NetworkBlock,
+    previous: NetworkBlock,
+    ticks_left: u8,
+}
+
+#[derive(Resource, Debug, Default)]
+struct Interaction {
+    /// block being dug and the face it was hit on
+    digging: Option<(IVec3, BlockFace)>,
+    progress: f32,
+    dig_cooldown: u8,
+    place_cooldown: u8,
+    predictions: HashMap<IVec3, Prediction>,
+}
+
+impl Interaction {
+    fn predict(&mut self, world: &mut WorldChunkData, position: IVec3, block: NetworkBlock) {
+        let Some(previous) = world.set_block(position, block) else {
+            return;
+        };
+
+        // keep the oldest known server state if this block was guessed already
+        let previous = self
+            .predictions
+            .get(&position)
+            .map_or(previous, |prediction| prediction.previous);
+
+        self.predictions.insert(
+            position,
+            Prediction {
+                block,
+                previous,
+                ticks_left: PREDICTION_TICKS,
+            },
+        );
+    }
+
+    /// The server is always right, guesses it disagrees with are undone.
+    fn apply_change(&mut self, world: &mut WorldChunkData, position: IVec3, block: NetworkBlock) {
+        if let Some(prediction) = self.predictions.remove(&position) {
+            if prediction.block != block {
+                info!(
+                    "Server rejected the change at {position}, it is {block:?} rather than {:?}",
+                    prediction.block
+                );
+            }
+        }
+
+        world.set_block(position, block);
+    }
+
+    /// Takes back guesses the server never answered.
+    fn expire_predictions(&mut self, world: &mut WorldChunkData) {
+        self.predictions.retain(|position, prediction| {
+            prediction.ticks_left = prediction.ticks_left.saturating_sub(1);
+            if prediction.ticks_left > 0 {
+                return true;
+            }
+
+            if world.block_at(*position) == Some(prediction.block) {
+                world.set_block(*position, prediction.previous);
+            }
+
+            false
+        });
+    }
+
+    /// One tick of digging, `speed` is how much of the block breaks each tick.
+    fn dig(
+        &mut self,
+        world: &mut WorldChunkData,
+        hit: &RaycastHit,
+        speed: f32,
+        creative: bool,
+        message_writer: &mut EventWriter<ClientMessage>,
+    ) {
+        let digging = |status| ClientMessage::Digging {
+            status,
+            position: hit.pos,
+            face: hit.face as u8,
+        };
+
+        if creative {
+            message_writer.send(digging(DiggingStatus::Started));
+            self.predict(world, hit.pos, NetworkBlock::AIR);
+            self.dig_cooldown = DIG_COOLDOWN;
+            return;
+        }
+
+        if self.digging == Some((hit.pos, hit.face)) {
+            self.progress += speed;
+
+            if self.progress >= 1.0 {
+                message_writer.send(digging(DiggingStatus::Finished));
+                self.predict(world, hit.pos, NetworkBlock::AIR);
+                self.digging = None;
+                self.progress = 0.0;
+                self.dig_cooldown = DIG_COOLDOWN;
+            }
+
+            return;
+        }
+
+        self.cancel_digging(message_writer);
+        message_writer.send(digging(DiggingStatus::Started));
+
+        // soft blocks break right away, the server does the same on its side
+        if speed >= 1.0 {
+            self.predict(world, hit.pos, NetworkBlock::AIR);
+        } else {
+            self.digging = Some((hit.pos, hit.face));
+        }
+    }
+
+    fn cancel_digging(&mut self, message_writer: &mut EventWriter<ClientMessage>) {
+        if let Some((position, face)) = self.digging.take() {
+            message_writer.send(ClientMessage::Digging {
+                status: DiggingStatus::Cancelled,
+                position,
+                face: face as u8,
+            });
+        }
+
+        self.progress = 0.0;
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<HeldItem>()
+        .init_resource::<Interaction>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (receive, interact.run_if(on_event::<Tick>()))
+                .chain()
+                .after(player::movement)
+                .after(physics::simulate)
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(mut interaction: ResMut<Interaction>, mut held_item: ResMut<HeldItem>) {
+    *interaction = Interaction::default();
+    *held_item = HeldItem::default();
+}
+
+fn receive(
+    mut server_reader: EventReader<ServerMessage>,
+    mut interaction: ResMut<Interaction>,
+    mut world_data: ResMut<WorldChunkData>,
+) {
+    for message in server_reader.read() {
+        match message {
+            // guesses about the old world mean nothing in the new one
+            ServerMessage::Respawn { .. } => *interaction = Interaction::default(),
+            ServerMessage::BlockChange { position, block } => {
+                interaction.apply_change(&mut world_data, *position, *block);
+            }
+            _ => {}
+        }
+    }
+}
+
+// blocks placing into replaces, like grass, fluids and the first snow layer
+fn is_replaceable(block: Block) -> bool {
+    matches!(block.id, 0 | 8..=11 | 31 | 32 | 51 | 106) || block.id == 78 && block.metadata & 7 == 0
+}
+
+// right clicking these uses them rather than placing against them
+fn is_usable(block: Block) -> bool {
+    matches!(
+        block.id,
+        23 | 25
+            | 26
+            | 54
+            | 58
+            | 61
+            | 62
+            | 64
+            | 69
+            | 77
+            | 84
+            | 92..=94
+            | 96
+            | 107
+            | 116
+            | 117
+            | 118
+            | 130
+            | 138
+            | 143
+            | 145
+            | 146
+            | 149
+            | 150
+            | 151
+            | 154
+            | 158
+            | 178
+            | 183..=187
+            | 193..=197
+    )
+}
+
+fn to_block(block: NetworkBlock) -> Block {
+    Block::new(block.id, block.metadata)
+}
+
+/// The buttons and what the game mode lets them do.
+#[derive(SystemParam)]
+struct Controls<'w> {
+    buttons: Res<'w, ButtonInput<MouseButton>>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    cursor_state: Res<'w, CursorState>,
+    game_mode: Res<'w, GameMode>,
+}
+
+/// Digs with the left button and places with the right one, once per tick like vanilla.
+fn interact(
+    controls: Controls,
+    target: Res<TargetBlock>,
+    held_item: Res<HeldItem>,
+    player_q: Query<&PlayerBody, With<Player>>,
+    mut interaction: ResMut<Interaction>,
+    mut world_data: ResMut<WorldChunkData>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    let Ok(body) = player_q.get_single() else {
+        return;
+    };
+
+    let Controls {
+        buttons,
+        keys,
+        cursor_state,
+        game_mode,
+    } = controls;
+
+    interaction.expire_predictions(&mut world_data);
+
+    let locked = cursor_state.is_locked && *game_mode != GameMode::Spectator;
+    let held = held_item.slot.0.as_ref();
+
+    // digging
+    interaction.dig_cooldown = interaction.dig_cooldown.saturating_sub(1);
+
+    match target.hit {
+        Some(hit) if locked && buttons.pressed(MouseButton::Left) => {
+            message_writer.send(ClientMessage::SwingArm);
+
+            if interaction.dig_cooldown == 0 && *game_mode != GameMode::Adventure {
+                let speed = to_block(hit.block).dig_progress(
+                    held.map(|item| item.id),
+                    body.in_water,
+                    body.on_ground,
+                );
+                let creative = *game_mode == GameMode::Creative;
+
+                interaction.dig(&mut world_data, &hit, speed, creative, &mut message_writer);
+            }
+        }
+        _ => interaction.cancel_digging(&mut message_writer),
+    }
+
+    // placing
+    interaction.place_cooldown = interaction.place_cooldown.saturating_sub(1);
+
+    if !locked || !buttons.pressed(MouseButton::Right) || interaction.place_cooldown > 0 {
+        return;
+    }
+
+    interaction.place_cooldown = PLACE_COOLDOWN;
+
+    let Some(hit) = target.hit else {
+        // using the held item on nothing, like eating
+        if held.is_some() {
+            message_writer.send(ClientMessage::PlaceBlock {
+                position: IVec3::splat(-1),
+                face: 255,
+                held_item: held_item.slot.clone(),
+                cursor: Vec3::ZERO,
+            });
+        }
+
+        return;
+    };
+
+    message_writer.send(ClientMessage::SwingArm);
+    message_writer.send(ClientMessage::PlaceBlock {
+        position: hit.pos,
+        face: hit.face as u8,
+        held_item: held_item.slot.clone(),
+        cursor: hit.point - hit.pos.as_vec3(),
+    });
+
+    let clicked = to_block(hit.block);
+    let sneaking = keys.pressed(KeyCode::ShiftLeft);
+
+    // only plain block items can be guessed, the server decides the rest
+    let Some(item) = held.filter(|item| item.id > 0 && item.id < 256) else {
+        return;
+    };
+
+    if *game_mode == GameMode::Adventure || is_usable(clicked) && !sneaking {
+        return;
+    }
+
+    let position = if is_replaceable(clicked) {
+        hit.pos
+    } else {
+        hit.pos + hit.face.normal()
+    };
+
+    let replaces = world_data.block_at(position).map(to_block);
+    if !replaces.is_some_and(is_replaceable) {
+        return;
+    }
+
+    let block = NetworkBlock {
+        id: item.id,
+        metadata: (item.damage & 15) as u8,
+    };
+
+    // don't place a block inside the player
+    let player_box = body.bounding_box();
+    let blocked = to_block(block)
+        .collision_boxes(|_| Block::default())
+        .iter()
+        .any(|shape| shape.offset(position.as_dvec3()).intersects(&player_box));
+
+    if !blocked {
+        interaction.predict(&mut world_data, position, block);
+    }
+}
+
+#[cfg(test)]
+const STONE: NetworkBlock = NetworkBlock { id: 1, metadata: 0 };
+#[cfg(test)]
+const DIRT: NetworkBlock = NetworkBlock { id: 3, metadata: 0 };
+
+#[cfg(test)]
+fn test_world() -> WorldChunkData {
+    use gyra_proto::smp::ChunkColumn;
+
+    let mut world = WorldChunkData::default();
+    world.insert_column(ChunkColumn::from_sections(vec![], 0, 0, 0));
+    world.set_block(IVec3::new(4, 64, 4), STONE);
+    world
+}
+
+#[test]
+fn refused_change_is_rolled_back() {
+    let mut world = test_world();
+    let mut interaction = Interaction::default();
+    let position = IVec3::new(4, 64, 4);
+
+    interaction.predict(&mut world, position, NetworkBlock::AIR);
+    assert_eq!(world.block_at(position), Some(NetworkBlock::AIR));
+
+    interaction.apply_change(&mut world, position, STONE);
+
+    assert_eq!(world.block_at(position), Some(STONE));
+    assert!(interaction.predictions.is_empty());
+}
+
+#[test]
+fn accepted_change_is_kept() {
+    let mut world = test_world();
+    let mut interaction = Interaction::default();
+    let position = IVec3::new(4, 65, 4);
+
+    interaction.predict(&mut world, position, DIRT);
+    interaction.apply_change(&mut world, position, DIRT);
+
+    // nothing is left to expire
+    for _ in 0..PREDICTION_TICKS {
+        interaction.expire_predictions(&mut world);
+    }
+
+    assert_eq!(world.block_at(position), Some(DIRT));
+}
+
+#[test]
+fn unanswered_guess_is_undone() {
+    let mut world = test_world();
+    let mut interaction = Interaction::default();
+    let position = IVec3::new(4, 64, 4);
+
+    interaction.predict(&mut world, position, NetworkBlock::AIR);
+
+    for _ in 1..PREDICTION_TICKS {
+        interaction.expire_predictions(&mut world);
+    }
+    assert_eq!(world.block_at(position), Some(NetworkBlock::AIR));
+
+    interaction.expire_predictions(&mut world);
+    assert_eq!(world.block_at(position), Some(STONE));
+}
+
+#[test]
+fn repeated_guesses_roll_back_to_the_server_block() {
+    let mut world = test_world();
+    let mut interaction = Interaction::default();
+    let position = IVec3::new(4, 64, 4);
+
+    interaction.predict(&mut world, position, NetworkBlock::AIR);
+    interaction.predict(&mut world, position, DIRT);
+
+    for _ in 0..PREDICTION_TICKS {
+        interaction.expire_predictions(&mut world);
+    }
+
+    assert_eq!(world.block_at(position), Some(STONE));
+}
@@ -0,0 +1,969 @@
+use super::chat_proto::ChatComponent;
+use super::interaction::HeldItem;
+use super::lang::Translations;
+use crate::message::{ClientMessage, ServerMessage};
+use crate::state::AppState;
+use bevy::prelude::*;
+use gyra_proto::slot::{ItemStack, Slot};
+use std::ops::Range;
+
+/// The player's own window, it is always open even when it isn't shown.
+pub const PLAYER_WINDOW: u8 = 0;
+/// Slots of the player window: crafting result, 2x2 grid, armour, main and hotbar.
+pub const PLAYER_SLOTS: usize = 45;
+pub const ARMOR_START: usize = 5;
+pub const MAIN_START: usize = 9;
+pub const HOTBAR_START: usize = 36;
+// a container window lists the main inventory and the hotbar after its own slots
+const INVENTORY_SLOTS: usize = PLAYER_SLOTS - MAIN_START;
+// clicking here throws the cursor away
+const OUTSIDE: i16 = -999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    Player,
+    Chest {
+        rows: u8,
+    },
+    Furnace,
+    CraftingTable,
+    /// anything else is shown as rows of slots
+    Other {
+        slots: u8,
+    },
+}
+
+impl WindowKind {
+    pub fn from_open_window(kind: &str, slot_count: u8) -> Self {
+        match kind {
+            "minecraft:chest" => WindowKind::Chest {
+                rows: slot_count / 9,
+            },
+            "minecraft:furnace" => WindowKind::Furnace,
+            // the server sends 0 slots for crafting tables
+            "minecraft:crafting_table" => WindowKind::CraftingTable,
+            _ => WindowKind::Other { slots: slot_count },
+        }
+    }
+
+    /// Slots of the window itself, without the player's inventory.
+    pub fn slots(&self) -> usize {
+        match self {
+            WindowKind::Player => PLAYER_SLOTS,
+            WindowKind::Chest { rows } => *rows as usize * 9,
+            WindowKind::Furnace => 3,
+            WindowKind::CraftingTable => 10,
+            WindowKind::Other { slots } => *slots as usize,
+        }
+    }
+
+    /// The slot items are taken out of but never put into.
+    pub fn result_slot(&self) -> Option<usize> {
+        match self {
+            WindowKind::Player | WindowKind::CraftingTable => Some(0),
+            WindowKind::Furnace => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// A window the server opened, like a chest.
+#[derive(Debug, Clone)]
+pub struct Container {
+    pub id: u8,
+    pub kind: WindowKind,
+    pub title: String,
+    pub slots: Vec<Slot>,
+}
+
+/// How a slot was clicked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Click {
+    /// left or right button, `None` is outside the window
+    Pick { slot: Option<usize>, right: bool },
+    /// moves the stack to the other part of the window
+    Shift { slot: usize },
+    /// swaps the slot with a hotbar slot, 0 to 8
+    Hotbar { slot: usize, key: u8 },
+    /// spreads the cursor evenly over the slots, or one each with the right button
+    Drag { slots: Vec<usize>, right: bool },
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    player: Vec<Slot>,
+    container: Option<Vec<Slot>>,
+    cursor: Slot,
+}
+
+/// A click the server hasn't confirmed yet and how things were before it.
+#[derive(Debug, Clone)]
+struct Pending {
+    window_id: u8,
+    /// the last action number the click used
+    action: i16,
+    before: Snapshot,
+}
+
+#[derive(Resource, Debug)]
+pub struct Inventory {
+    /// numbered like the player window
+    pub player: Vec<Slot>,
+    pub container: Option<Container>,
+    /// what the mouse carries
+    pub cursor: Slot,
+    /// hotbar slot in hand, 0 to 8
+    pub selected: u8,
+    last_action: i16,
+    pending: Vec<Pending>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            player: vec![Slot::default(); PLAYER_SLOTS],
+            container: None,
+            cursor: Slot::default(),
+            selected: 0,
+            last_action: 0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// How many of an item fit in one slot.
+pub fn max_stack(id: u16) -> u8 {
+    match id {
+        // tools, weapons, armour, buckets with something in them, vehicles, food in bowls,
+        // potions, books that were written in and records
+        256..=259
+        | 261
+        | 267..=279
+        | 282..=286
+        | 290..=294
+        | 298..=317
+        | 326..=329
+        | 333
+        | 335
+        | 342
+        | 343
+        | 346
+        | 354
+        | 355
+        | 359
+        | 373
+        | 386
+        | 387
+        | 398
+        | 403
+        | 407
+        | 408
+        | 413
+        | 417..=419
+        | 422
+        | 2256..=2267 => 1,
+        // signs, buckets, snowballs, eggs, ender pearls, armor stands and banners
+        323 | 325 | 332 | 344 | 368 | 416 | 425 => 16,
+        _ => 64,
+    }
+}
+
+fn stacks_with(a: &ItemStack, b: &ItemStack) -> bool {
+    a.id == b.id && a.damage == b.damage && a.nbt == b.nbt
+}
+
+// the server may send stacks of 0, they are no different from an empty slot
+fn non_empty(slot: Slot) -> Slot {
+    Slot(slot.0.filter(|item| item.count > 0))
+}
+
+fn with_count(item: &ItemStack, count: u8) -> Option<ItemStack> {
+    (count > 0).then(|| ItemStack {
+        count,
+        ..item.clone()
+    })
+}
+
+/// What a slot shows until there are item icons: the item id and how many there are.
+pub fn label(slot: &Slot) -> String {
+    match &slot.0 {
+        Some(item) if item.count > 1 => format!("{}\n{}", item.id, item.count),
+        Some(item) => item.id.to_string(),
+        None => String::new(),
+    }
+}
+
+impl Inventory {
+    pub fn held(&self) -> &Slot {
+        &self.player[HOTBAR_START + self.selected as usize]
+    }
+
+    pub fn window_id(&self) -> u8 {
+        self.container.as_ref().map_or(PLAYER_WINDOW, |c| c.id)
+    }
+
+    pub fn window_kind(&self) -> WindowKind {
+        self.container
+            .as_ref()
+            .map_or(WindowKind::Player, |c| c.kind)
+    }
+
+    /// Slots of the window shown, the player's inventory included.
+    pub fn window_len(&self) -> usize {
+        match &self.container {
+            Some(container) => container.slots.len() + INVENTORY_SLOTS,
+            None => PLAYER_SLOTS,
+        }
+    }
+
+    pub fn slot(&self, index: usize) -> Option<&Slot> {
+        match &self.container {
+            Some(container) if index < container.slots.len() => container.slots.get(index),
+            Some(container) => self.player.get(index - container.slots.len() + MAIN_START),
+            None => self.player.get(index),
+        }
+    }
+
+    fn slot_mut(&mut self, index: usize) -> Option<&mut Slot> {
+        let own = self.container.as_ref().map_or(0, |c| c.slots.len());
+
+        match &mut self.container {
+            Some(container) if index < own => container.slots.get_mut(index),
+            Some(_) => self.player.get_mut(index - own + MAIN_START),
+            None => self.player.get_mut(index),
+        }
+    }
+
+    /// Where hotbar slot `key` is in the window shown.
+    pub fn hotbar_slot(&self, key: u8) -> usize {
+        self.window_len() - 9 + key as usize
+    }
+
+    fn is_result(&self, index: usize) -> bool {
+        self.window_kind().result_slot() == Some(index)
+    }
+
+    pub fn open(&mut self, id: u8, kind: WindowKind, title: String) {
+        self.container = Some(Container {
+            id,
+            kind,
+            title,
+            slots: vec![Slot::default(); kind.slots()],
+        });
+        self.pending.clear();
+    }
+
+    /// Back to the player window, whatever the cursor held is dropped by the server.
+    pub fn close(&mut self) {
+        self.container = None;
+        self.cursor = Slot::default();
+        self.pending.clear();
+    }
+
+    pub fn set_items(&mut self, id: u8, slots: Vec<Slot>) {
+        if id == PLAYER_WINDOW {
+            for (slot, item) in self.player.iter_mut().zip(slots) {
+                *slot = non_empty(item);
+            }
+
+            return;
+        }
+
+        let Some(container) = self.container.as_mut().filter(|c| c.id == id) else {
+            return;
+        };
+
+        let mut slots = slots.into_iter().map(non_empty);
+        for slot in container.slots.iter_mut() {
+            *slot = slots.next().unwrap_or_default();
+        }
+
+        for (slot, item) in self.player[MAIN_START..].iter_mut().zip(slots) {
+            *slot = item;
+        }
+    }
+
+    pub fn set_slot(&mut self, id: i8, index: i16, item: Slot) {
+        let item = non_empty(item);
+
+        match (id, index) {
+            (-1, -1) => self.cursor = item,
+            (0, 0..) => {
+                if let Some(slot) = self.player.get_mut(index as usize) {
+                    *slot = item;
+                }
+            }
+            (1.., 0..) if id as u8 == self.window_id() => {
+                if let Some(slot) = self.slot_mut(index as usize) {
+                    *slot = item;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The server's answer to a click, a refused one is undone along with every click after
+    /// it and has to be acknowledged.
+    pub fn confirm(&mut self, id: i8, action: i16, accepted: bool) -> Option<ClientMessage> {
+        if accepted {
+            self.pending
+                .retain(|pending| pending.window_id != id as u8 || pending.action > action);
+            return None;
+        }
+
+        let refused = self
+            .pending
+            .iter()
+            .position(|pending| pending.window_id == id as u8 && pending.action >= action);
+
+        if let Some(refused) = refused {
+            let before = self.pending[refused].before.clone();
+            info!("Server refused click {action}, rolling back");
+
+            self.player = before.player;
+            self.cursor = before.cursor;
+            if let (Some(container), Some(slots)) = (&mut self.container, before.container) {
+                container.slots = slots;
+            }
+        }
+
+        self.pending.clear();
+
+        // vanilla answers refusals as accepted, the whole window is sent again after this
+        Some(ClientMessage::ConfirmTransaction {
+            id,
+            action,
+            accepted: true,
+        })
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            player: self.player.clone(),
+            container: self.container.as_ref().map(|c| c.slots.clone()),
+            cursor: self.cursor.clone(),
+        }
+    }
+
+    /// Guesses what the click does and returns the packets that tell the server about it.
+    pub fn click(&mut self, click: Click) -> Vec<ClientMessage> {
+        let before = self.snapshot();
+        let id = self.window_id();
+
+        // (slot, button, mode, item in the slot before)
+        let clicks: Vec<(i16, i8, i8, Slot)> = match click {
+            Click::Pick { slot: None, right } => {
+                self.throw(right);
+                vec![(OUTSIDE, right as i8, 0, Slot::default())]
+            }
+            Click::Pick {
+                slot: Some(slot),
+                right,
+            } => {
+                let item = self.slot(slot).cloned().unwrap_or_default();
+                self.pick(slot, right);
+                vec![(slot as i16, right as i8, 0, item)]
+            }
+            Click::Shift { slot } => {
+                let item = self.slot(slot).cloned().unwrap_or_default();
+                self.shift(slot);
+                vec![(slot as i16, 0, 1, item)]
+            }
+            Click::Hotbar { slot, key } => {
+                self.swap_with_hotbar(slot, key);
+                vec![(slot as i16, key as i8, 2, Slot::default())]
+            }
+            Click::Drag { slots, right } => {
+                self.drag(&slots, right);
+
+                // start, one per slot and end, the right button counts from 4
+                let offset = if right { 4 } else { 0 };
+                let mut clicks = vec![(OUTSIDE, offset, 5, Slot::default())];
+                clicks.extend(
+                    slots
+                        .iter()
+                        .map(|slot| (*slot as i16, offset + 1, 5, Slot::default())),
+                );
+                clicks.push((OUTSIDE, offset + 2, 5, Slot::default()));
+                clicks
+            }
+        };
+
+        let messages: Vec<_> = clicks
+            .into_iter()
+            .map(|(slot, button, mode, item)| {
+                self.last_action = self.last_action.wrapping_add(1);
+                ClientMessage::ClickWindow {
+                    id,
+                    slot,
+                    button,
+                    action: self.last_action,
+                    mode,
+                    item,
+                }
+            })
+            .collect();
+
+        self.pending.push(Pending {
+            window_id: id,
+            action: self.last_action,
+            before,
+        });
+
+        messages
+    }
+
+    fn throw(&mut self, one: bool) {
+        let Some(cursor) = self.cursor.0.take() else {
+            return;
+        };
+
+        if one {
+            self.cursor.0 = with_count(&cursor, cursor.count.saturating_sub(1));
+        }
+    }
+
+    fn pick(&mut self, index: usize, right: bool) {
+        let result = self.is_result(index);
+        let mut cursor = self.cursor.0.take();
+        let Some(slot) = self.slot_mut(index) else {
+            self.cursor.0 = cursor;
+            return;
+        };
+
+        match (slot.0.take(), cursor.take()) {
+            (None, None) => {}
+            // picking up, the right button takes half
+            (Some(item), None) => {
+                let taken = if right && !result {
+                    item.count.div_ceil(2)
+                } else {
+                    item.count
+                };
+
+                cursor = with_count(&item, taken);
+                slot.0 = with_count(&item, item.count.saturating_sub(taken));
+            }
+            (None, Some(held)) if result => cursor = Some(held),
+            // putting down, the right button puts one
+            (None, Some(held)) => {
+                let put = if right { 1 } else { held.count };
+
+                slot.0 = with_count(&held, put);
+                cursor = with_count(&held, held.count.saturating_sub(put));
+            }
+            (Some(item), Some(held)) if stacks_with(&item, &held) => {
+                let max = max_stack(item.id);
+
+                if result {
+                    // the whole result or nothing
+                    let total = held.count.saturating_add(item.count);
+                    if total <= max {
+                        cursor = with_count(&held, total);
+                    } else {
+                        slot.0 = Some(item);
+                        cursor = Some(held);
+                    }
+                } else {
+                    let room = max.saturating_sub(item.count);
+                    let put = if right { 1 } else { held.count }.min(room);
+
+                    slot.0 = with_count(&item, item.count + put);
+                    cursor = with_count(&held, held.count.saturating_sub(put));
+                }
+            }
+            (Some(item), Some(held)) if result => {
+                slot.0 = Some(item);
+                cursor = Some(held);
+            }
+            (Some(item), Some(held)) => {
+                slot.0 = Some(held);
+                cursor = Some(item);
+            }
+        }
+
+        self.cursor.0 = cursor;
+    }
+
+    /// Where a shift click moves a slot, and whether it fills them from the end.
+    fn shift_target(&self, index: usize) -> (Range<usize>, bool) {
+        let len = self.window_len();
+        let own = len - INVENTORY_SLOTS;
+        let main = own..len - 9;
+        let hotbar = len - 9..len;
+
+        match self.window_kind() {
+            WindowKind::Player if index < MAIN_START => (MAIN_START..len, index == 0),
+            _ if index < own => (own..len, true),
+            WindowKind::Chest { .. } | WindowKind::Other { .. } => (0..own, false),
+            WindowKind::Furnace => (0..1, false),
+            _ if main.contains(&index) => (hotbar, false),
+            _ => (main, false),
+        }
+    }
+
+    fn shift(&mut self, index: usize) {
+        let (target, reverse) = self.shift_target(index);
+        let Some(mut item) = self.slot_mut(index).and_then(|slot| slot.0.take()) else {
+            return;
+        };
+
+        let max = max_stack(item.id);
+        let order: Vec<usize> = if reverse {
+            target.rev().collect()
+        } else {
+            target.collect()
+        };
+
+        // onto matching stacks first, then into empty slots
+        for fill_empty in [false, true] {
+            for &other in &order {
+                if item.count == 0 {
+                    break;
+                }
+
+                let Some(slot) = self.slot_mut(other) else {
+                    continue;
+                };
+
+                match &mut slot.0 {
+                    Some(existing) if !fill_empty && stacks_with(existing, &item) => {
+                        let moved = item.count.min(max.saturating_sub(existing.count));
+                        existing.count += moved;
+                        item.count -= moved;
+                    }
+                    None if fill_empty => {
+                        slot.0 = Some(item.clone());
+                        item.count = 0;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(slot) = self.slot_mut(index) {
+            slot.0 = with_count(&item, item.count);
+        }
+    }
+
+    fn swap_with_hotbar(&mut self, index: usize, key: u8) {
+        let hotbar = self.hotbar_slot(key);
+        if index == hotbar || index >= self.window_len() {
+            return;
+        }
+
+        let item = self.slot_mut(index).and_then(|slot| slot.0.take());
+        let other = self.slot_mut(hotbar).and_then(|slot| slot.0.take());
+
+        // nothing goes into a result slot
+        let (item, other) = if self.is_result(index) && other.is_some() {
+            (other, item)
+        } else {
+            (item, other)
+        };
+
+        if let Some(slot) = self.slot_mut(hotbar) {
+            slot.0 = item;
+        }
+
+        if let Some(slot) = self.slot_mut(index) {
+            slot.0 = other;
+        }
+    }
+
+    fn drag(&mut self, slots: &[usize], right: bool) {
+        let Some(mut held) = self.cursor.0.take() else {
+            return;
+        };
+
+        let max = max_stack(held.id);
+        let each = if right {
+            1
+        } else {
+            (held.count as usize / slots.len().max(1)) as u8
+        };
+
+        for &index in slots {
+            if held.count == 0 || self.is_result(index) {
+                continue;
+            }
+
+            let Some(slot) = self.slot_mut(index) else {
+                continue;
+            };
+
+            match &mut slot.0 {
+                Some(existing) if stacks_with(existing, &held) => {
+                    let put = each.min(max.saturating_sub(existing.count)).min(held.count);
+                    existing.count += put;
+                    held.count -= put;
+                }
+                None => {
+                    let put = each.min(held.count);
+                    slot.0 = with_count(&held, put);
+                    held.count -= put;
+                }
+                _ => {}
+            }
+        }
+
+        self.cursor.0 = with_count(&held, held.count);
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Inventory>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (receive, update_held_item)
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn receive(
+    mut server_reader: EventReader<ServerMessage>,
+    mut inventory: ResMut<Inventory>,
+    mut client_writer: EventWriter<ClientMessage>,
+    lang: Res<Translations>,
+) {
+    for message in server_reader.read() {
+        match message {
+            ServerMessage::OpenWindow {
+                id,
+                kind,
+                title,
+                slot_count,
+            } => {
+                let title = serde_json::from_str::<ChatComponent>(title)
+                    .map_or_else(|_| title.clone(), |title| title.to_plain_text(&lang));
+                let kind = WindowKind::from_open_window(kind, *slot_count);
+
+                inventory.open(*id, kind, title);
+            }
+
+            ServerMessage::CloseWindow { id } => {
+                if inventory.window_id() == *id {
+                    inventory.close();
+                }
+            }
+
+            ServerMessage::WindowItems { id, slots } => {
+                inventory.set_items(*id, slots.clone());
+            }
+
+            ServerMessage::SetSlot { id, slot, item } => {
+                inventory.set_slot(*id, *slot, item.clone());
+            }
+
+            ServerMessage::ConfirmTransaction {
+                id,
+                action,
+                accepted,
+            } => {
+                if let Some(reply) = inventory.confirm(*id, *action, *accepted) {
+                    client_writer.send(reply);
+                }
+            }
+
+            ServerMessage::HeldItemChange { slot } => {
+                inventory.selected = *slot;
+            }
+
+            _ => {}
+        }
+    }
+}
+
+fn update_held_item(inventory: Res<Inventory>, mut held_item: ResMut<HeldItem>) {
+    if inventory.is_changed() && held_item.slot != *inventory.held() {
+        held_item.slot = inventory.held().clone();
+    }
+}
+
+fn cleanup(mut inventory: ResMut<Inventory>) {
+    *inventory = Inventory::default();
+}
+
+#[cfg(test)]
+fn stack(id: u16, count: u8) -> Slot {
+    Slot(Some(ItemStack {
+        id,
+        count,
+        damage: 0,
+        nbt: None,
+    }))
+}
+
+#[cfg(test)]
+fn count(slot: &Slot) -> u8 {
+    slot.0.as_ref().map_or(0, |item| item.count)
+}
+
+/// slot, button and mode of each click packet
+#[cfg(test)]
+fn sent(messages: &[ClientMessage]) -> Vec<(i16, i8, i8)> {
+    messages
+        .iter()
+        .map(|message| match message {
+            ClientMessage::ClickWindow {
+                slot, button, mode, ..
+            } => (*slot, *button, *mode),
+            _ => panic!("not a click"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn left(slot: usize) -> Click {
+    Click::Pick {
+        slot: Some(slot),
+        right: false,
+    }
+}
+
+#[cfg(test)]
+fn right(slot: usize) -> Click {
+    Click::Pick {
+        slot: Some(slot),
+        right: true,
+    }
+}
+
+#[test]
+fn left_click_picks_up_and_puts_down() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 10);
+
+    let messages = inventory.click(left(MAIN_START));
+    assert_eq!(sent(&messages), [(MAIN_START as i16, 0, 0)]);
+    assert_eq!(inventory.cursor, stack(1, 10));
+    assert_eq!(inventory.player[MAIN_START], Slot(None));
+
+    inventory.click(left(MAIN_START + 1));
+    assert_eq!(inventory.cursor, Slot(None));
+    assert_eq!(inventory.player[MAIN_START + 1], stack(1, 10));
+}
+
+#[test]
+fn right_click_takes_half_and_puts_one() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 7);
+
+    inventory.click(right(MAIN_START));
+    assert_eq!(count(&inventory.cursor), 4);
+    assert_eq!(count(&inventory.player[MAIN_START]), 3);
+
+    inventory.click(right(MAIN_START + 1));
+    assert_eq!(count(&inventory.cursor), 3);
+    assert_eq!(count(&inventory.player[MAIN_START + 1]), 1);
+
+    inventory.click(right(MAIN_START));
+    assert_eq!(count(&inventory.cursor), 2);
+    assert_eq!(count(&inventory.player[MAIN_START]), 4);
+}
+
+#[test]
+fn stacks_merge_up_to_the_maximum_and_others_swap() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 60);
+    inventory.cursor = stack(1, 10);
+
+    inventory.click(left(MAIN_START));
+    assert_eq!(count(&inventory.player[MAIN_START]), 64);
+    assert_eq!(count(&inventory.cursor), 6);
+
+    inventory.player[MAIN_START + 1] = stack(3, 2);
+    inventory.click(left(MAIN_START + 1));
+    assert_eq!(inventory.player[MAIN_START + 1], stack(1, 6));
+    assert_eq!(inventory.cursor, stack(3, 2));
+}
+
+#[test]
+fn result_slot_is_taken_whole() {
+    let mut inventory = Inventory::default();
+    inventory.player[0] = stack(5, 4);
+    inventory.cursor = stack(5, 62);
+
+    // 66 doesn't fit on the cursor, so nothing happens
+    inventory.click(right(0));
+    assert_eq!(count(&inventory.player[0]), 4);
+    assert_eq!(count(&inventory.cursor), 62);
+}
+
+#[test]
+fn shift_click_moves_between_hotbar_and_main() {
+    let mut inventory = Inventory::default();
+    inventory.player[HOTBAR_START] = stack(1, 10);
+    inventory.player[MAIN_START + 3] = stack(1, 60);
+
+    let messages = inventory.click(Click::Shift { slot: HOTBAR_START });
+    assert_eq!(sent(&messages), [(HOTBAR_START as i16, 0, 1)]);
+
+    // onto the matching stack first, the rest into the first empty slot
+    assert_eq!(count(&inventory.player[MAIN_START + 3]), 64);
+    assert_eq!(count(&inventory.player[MAIN_START]), 6);
+    assert_eq!(inventory.player[HOTBAR_START], Slot(None));
+
+    inventory.click(Click::Shift { slot: MAIN_START });
+    assert_eq!(count(&inventory.player[HOTBAR_START]), 6);
+}
+
+#[test]
+fn shift_click_out_of_a_chest_fills_from_the_hotbar_end() {
+    let mut inventory = Inventory::default();
+    inventory.open(1, WindowKind::Chest { rows: 3 }, String::new());
+    inventory.set_slot(1, 0, stack(1, 5));
+
+    inventory.click(Click::Shift { slot: 0 });
+    assert_eq!(count(&inventory.player[PLAYER_SLOTS - 1]), 5);
+
+    inventory.click(Click::Shift {
+        slot: inventory.window_len() - 1,
+    });
+    assert_eq!(count(&inventory.container.as_ref().unwrap().slots[0]), 5);
+}
+
+#[test]
+fn number_keys_swap_with_the_hotbar() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 3);
+    inventory.player[HOTBAR_START + 2] = stack(4, 8);
+
+    let messages = inventory.click(Click::Hotbar {
+        slot: MAIN_START,
+        key: 2,
+    });
+    assert_eq!(sent(&messages), [(MAIN_START as i16, 2, 2)]);
+    assert_eq!(inventory.player[MAIN_START], stack(4, 8));
+    assert_eq!(inventory.player[HOTBAR_START + 2], stack(1, 3));
+}
+
+#[test]
+fn drag_spreads_evenly() {
+    let mut inventory = Inventory {
+        cursor: stack(1, 10),
+        ..Default::default()
+    };
+    let slots = vec![MAIN_START, MAIN_START + 1, MAIN_START + 2];
+
+    let messages = inventory.click(Click::Drag {
+        slots: slots.clone(),
+        right: false,
+    });
+
+    assert_eq!(
+        sent(&messages),
+        [
+            (OUTSIDE, 0, 5),
+            (MAIN_START as i16, 1, 5),
+            (MAIN_START as i16 + 1, 1, 5),
+            (MAIN_START as i16 + 2, 1, 5),
+            (OUTSIDE, 2, 5),
+        ]
+    );
+    for slot in slots {
+        assert_eq!(count(&inventory.player[slot]), 3);
+    }
+    assert_eq!(count(&inventory.cursor), 1);
+}
+
+#[test]
+fn right_drag_puts_one_in_each() {
+    let mut inventory = Inventory {
+        cursor: stack(1, 10),
+        ..Default::default()
+    };
+
+    let messages = inventory.click(Click::Drag {
+        slots: vec![MAIN_START, MAIN_START + 1],
+        right: true,
+    });
+
+    let buttons: Vec<_> = sent(&messages).iter().map(|click| click.1).collect();
+    assert_eq!(buttons, [4, 5, 5, 6]);
+    assert_eq!(count(&inventory.player[MAIN_START]), 1);
+    assert_eq!(count(&inventory.player[MAIN_START + 1]), 1);
+    assert_eq!(count(&inventory.cursor), 8);
+}
+
+#[test]
+fn clicks_use_increasing_action_numbers() {
+    let mut inventory = Inventory {
+        cursor: stack(1, 2),
+        ..Default::default()
+    };
+
+    let actions: Vec<_> = inventory
+        .click(Click::Drag {
+            slots: vec![MAIN_START],
+            right: false,
+        })
+        .into_iter()
+        .chain(inventory.click(left(MAIN_START + 1)))
+        .map(|message| match message {
+            ClientMessage::ClickWindow { action, .. } => action,
+            _ => panic!("not a click"),
+        })
+        .collect();
+
+    assert_eq!(actions, [1, 2, 3, 4]);
+}
+
+#[test]
+fn refused_click_is_rolled_back_and_acknowledged() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 10);
+
+    inventory.click(left(MAIN_START));
+    inventory.click(left(MAIN_START + 1));
+    assert_eq!(count(&inventory.player[MAIN_START + 1]), 10);
+
+    // the first click is refused, so the one after it goes too
+    let answer = inventory.confirm(0, 1, false);
+    assert!(matches!(
+        answer,
+        Some(ClientMessage::ConfirmTransaction {
+            id: 0,
+            action: 1,
+            accepted: true,
+        })
+    ));
+    assert_eq!(inventory.player[MAIN_START], stack(1, 10));
+    assert_eq!(inventory.player[MAIN_START + 1], Slot(None));
+    assert_eq!(inventory.cursor, Slot(None));
+}
+
+#[test]
+fn accepted_clicks_are_kept() {
+    let mut inventory = Inventory::default();
+    inventory.player[MAIN_START] = stack(1, 10);
+
+    inventory.click(left(MAIN_START));
+    inventory.click(left(MAIN_START + 1));
+
+    assert!(inventory.confirm(0, 1, true).is_none());
+
+    // refusing the second one only undoes that one
+    inventory.confirm(0, 2, false);
+    assert_eq!(inventory.cursor, stack(1, 10));
+    assert_eq!(inventory.player[MAIN_START + 1], Slot(None));
+}
+
+#[test]
+fn empty_stacks_from_the_server_are_empty_slots() {
+    let mut inventory = Inventory::default();
+    inventory.set_slot(-1, -1, stack(1, 0));
+    inventory.set_items(PLAYER_WINDOW, vec![stack(1, 0); PLAYER_SLOTS]);
+
+    assert_eq!(inventory.cursor, Slot(None));
+    assert_eq!(inventory.player[MAIN_START], Slot(None));
+
+    // nothing to throw
+    inventory.click(Click::Pick {
+        slot: None,
+        right: true,
+    });
+    assert_eq!(inventory.cursor, Slot(None));
+}
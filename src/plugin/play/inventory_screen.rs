@@ -0,0 +1,487 @@
+use super::inventory::{label, Click, Inventory, WindowKind, ARMOR_START, PLAYER_WINDOW};
+use crate::message::ClientMessage;
+use crate::plugin::CursorState;
+use crate::state::AppState;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy::window::PrimaryWindow;
+use bevy_cosmic_edit::FocusedWidget;
+use std::ops::Range;
+
+pub const SLOT_SIZE: f32 = 40.0;
+const SLOT_COLOR: Color = Color::srgba(0.55, 0.55, 0.55, 0.9);
+const HOVERED_SLOT_COLOR: Color = Color::srgba(0.75, 0.75, 0.75, 0.9);
+
+/// Number keys for the hotbar slots, in the inventory and while playing.
+pub const HOTBAR_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Component)]
+struct InventoryScreen;
+
+/// Clicks here aren't outside the window.
+#[derive(Component)]
+struct InventoryPanel;
+
+/// A slot of the window shown.
+#[derive(Component)]
+struct SlotButton(usize);
+
+#[derive(Component)]
+struct SlotLabel(usize);
+
+/// What the mouse carries, follows the pointer.
+#[derive(Component)]
+struct CursorItem;
+
+#[derive(Debug)]
+struct Drag {
+    right: bool,
+    slots: Vec<usize>,
+}
+
+#[derive(Resource, Debug, Default)]
+struct Screen {
+    /// the window shown, if any
+    shown: Option<(u8, WindowKind)>,
+    /// slots the mouse went over while holding a button with something on the cursor
+    drag: Option<Drag>,
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Screen>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                toggle,
+                build_screen,
+                click_slots,
+                update_slots,
+                follow_cursor,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut screen: ResMut<Screen>,
+    roots: Query<Entity, With<InventoryScreen>>,
+) {
+    *screen = Screen::default();
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+/// E opens the player's inventory, E or escape closes whatever is shown.
+/// Windows the server opens and closes are shown and hidden on their own.
+fn toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    focused_widget: Res<FocusedWidget>,
+    mut screen: ResMut<Screen>,
+    mut inventory: ResMut<Inventory>,
+    mut cursor_state: ResMut<CursorState>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    let window = inventory.container.as_ref().map(|c| (c.id, c.kind));
+
+    if let Some(window) = window {
+        if screen.shown != Some(window) {
+            screen.shown = Some(window);
+            cursor_state.is_locked = false;
+            return;
+        }
+    } else if screen.shown.is_some_and(|(id, _)| id != PLAYER_WINDOW) {
+        screen.shown = None;
+        screen.drag = None;
+        cursor_state.is_locked = true;
+        return;
+    }
+
+    // typing in chat
+    let pressed_e = keys.just_pressed(KeyCode::KeyE) && focused_widget.0.is_none();
+
+    if screen.shown.is_none() {
+        if pressed_e {
+            screen.shown = Some((PLAYER_WINDOW, WindowKind::Player));
+            cursor_state.is_locked = false;
+        }
+
+        return;
+    }
+
+    if pressed_e || keys.just_pressed(KeyCode::Escape) {
+        message_writer.send(ClientMessage::CloseWindow {
+            id: inventory.window_id(),
+        });
+
+        inventory.close();
+        screen.shown = None;
+        screen.drag = None;
+        cursor_state.is_locked = true;
+    }
+}
+
+fn spawn_slot(parent: &mut ChildBuilder, index: usize) {
+    parent
+        .spawn((
+            SlotButton(index),
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(SLOT_SIZE),
+                    height: Val::Px(SLOT_SIZE),
+                    margin: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: SLOT_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
+                SlotLabel(index),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            ));
+        });
+}
+
+fn spawn_grid(parent: &mut ChildBuilder, slots: Range<usize>, columns: usize) {
+    let slots: Vec<usize> = slots.collect();
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|p| {
+            for row in slots.chunks(columns) {
+                p.spawn(NodeBundle::default()).with_children(|p| {
+                    for &index in row {
+                        spawn_slot(p, index);
+                    }
+                });
+            }
+        });
+}
+
+fn spawn_row(parent: &mut ChildBuilder, spawn: impl FnOnce(&mut ChildBuilder)) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(spawn);
+}
+
+fn spawn_arrow(parent: &mut ChildBuilder) {
+    parent.spawn(TextBundle::from_section(
+        "->",
+        TextStyle {
+            font_size: 24.0,
+            color: Color::WHITE,
+            ..default()
+        },
+    ));
+}
+
+/// The window's own slots, laid out like vanilla.
+fn spawn_window_slots(parent: &mut ChildBuilder, kind: WindowKind) {
+    match kind {
+        WindowKind::Player => spawn_row(parent, |p| {
+            spawn_grid(p, ARMOR_START..ARMOR_START + 4, 1);
+            spawn_grid(p, 1..5, 2);
+            spawn_arrow(p);
+            spawn_grid(p, 0..1, 1);
+        }),
+        WindowKind::CraftingTable => spawn_row(parent, |p| {
+            spawn_grid(p, 1..10, 3);
+            spawn_arrow(p);
+            spawn_grid(p, 0..1, 1);
+        }),
+        WindowKind::Furnace => spawn_row(parent, |p| {
+            spawn_grid(p, 0..2, 1);
+            spawn_arrow(p);
+            spawn_grid(p, 2..3, 1);
+        }),
+        kind => spawn_grid(parent, 0..kind.slots(), 9),
+    }
+}
+
+fn build_screen(
+    mut commands: Commands,
+    screen: Res<Screen>,
+    inventory: Res<Inventory>,
+    roots: Query<Entity, With<InventoryScreen>>,
+    mut built: Local<Option<(u8, WindowKind)>>,
+) {
+    if *built == screen.shown {
+        return;
+    }
+
+    *built = screen.shown;
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let Some((_, kind)) = screen.shown else {
+        return;
+    };
+
+    let title = match &inventory.container {
+        Some(container) => container.title.clone(),
+        None => "Inventory".to_string(),
+    };
+
+    // the player's main inventory and hotbar come after the window's own slots
+    let own = match kind {
+        WindowKind::Player => ARMOR_START + 4,
+        kind => kind.slots(),
+    };
+
+    commands
+        .spawn((
+            InventoryScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.4).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|p| {
+            p.spawn((
+                InventoryPanel,
+                Interaction::default(),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.2, 0.2, 0.2, 0.95).into(),
+                    ..default()
+                },
+            ))
+            .with_children(|p| {
+                p.spawn(TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+
+                spawn_window_slots(p, kind);
+                spawn_grid(p, own..own + 27, 9);
+                spawn_grid(p, own + 27..own + 36, 9);
+            });
+
+            p.spawn((
+                CursorItem,
+                TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    focus_policy: FocusPolicy::Pass,
+                    z_index: ZIndex::Global(11),
+                    ..default()
+                }
+                .with_text_justify(JustifyText::Center),
+            ));
+        });
+}
+
+/// Left and right clicks, shift clicks, number keys and dragging over several slots.
+fn click_slots(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    slots: Query<(&Interaction, &SlotButton)>,
+    panel: Query<&Interaction, With<InventoryPanel>>,
+    mut screen: ResMut<Screen>,
+    mut inventory: ResMut<Inventory>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    if screen.shown.is_none() {
+        return;
+    }
+
+    let hovered = slots
+        .iter()
+        .find(|(interaction, _)| **interaction != Interaction::None)
+        .map(|(_, slot)| slot.0);
+    let over_panel = panel
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+
+    if let Some(slot) = hovered {
+        for (key, code) in HOTBAR_KEYS.iter().enumerate() {
+            if keys.just_pressed(*code) {
+                let click = Click::Hotbar {
+                    slot,
+                    key: key as u8,
+                };
+                message_writer.send_batch(inventory.click(click));
+            }
+        }
+    }
+
+    if let Some(drag) = &mut screen.drag {
+        if let Some(slot) = hovered.filter(|slot| !drag.slots.contains(slot)) {
+            drag.slots.push(slot);
+        }
+
+        let button = if drag.right {
+            MouseButton::Right
+        } else {
+            MouseButton::Left
+        };
+
+        if buttons.just_released(button) {
+            let Drag { right, slots } = screen.drag.take().unwrap();
+
+            // one slot is an ordinary click
+            let click = if slots.len() > 1 {
+                Click::Drag { slots, right }
+            } else {
+                Click::Pick {
+                    slot: slots.first().copied(),
+                    right,
+                }
+            };
+
+            message_writer.send_batch(inventory.click(click));
+        }
+
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for (button, right) in [(MouseButton::Left, false), (MouseButton::Right, true)] {
+        if !buttons.just_pressed(button) {
+            continue;
+        }
+
+        let click = match hovered {
+            Some(slot) if shift => Click::Shift { slot },
+            // holding something might be the start of a drag
+            Some(slot) if inventory.cursor.0.is_some() => {
+                screen.drag = Some(Drag {
+                    right,
+                    slots: vec![slot],
+                });
+                return;
+            }
+            Some(slot) => Click::Pick {
+                slot: Some(slot),
+                right,
+            },
+            None if over_panel => continue,
+            None => Click::Pick { slot: None, right },
+        };
+
+        message_writer.send_batch(inventory.click(click));
+    }
+}
+
+fn update_slots(
+    inventory: Res<Inventory>,
+    screen: Res<Screen>,
+    mut labels: Query<(&SlotLabel, &mut Text), Without<CursorItem>>,
+    mut buttons: Query<(&SlotButton, &Interaction, &mut BackgroundColor)>,
+    mut cursor: Query<&mut Text, With<CursorItem>>,
+) {
+    for (label_of, mut text) in labels.iter_mut() {
+        let value = inventory.slot(label_of.0).map(label).unwrap_or_default();
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+
+    let dragged = screen.drag.as_ref().map_or(&[][..], |drag| &drag.slots);
+    for (slot, interaction, mut color) in buttons.iter_mut() {
+        let highlighted = *interaction != Interaction::None || dragged.contains(&slot.0);
+        let wanted = if highlighted {
+            HOVERED_SLOT_COLOR
+        } else {
+            SLOT_COLOR
+        };
+
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+
+    for mut text in cursor.iter_mut() {
+        let value = label(&inventory.cursor);
+        if text.sections.first().map(|section| &section.value) != Some(&value) {
+            *text = Text::from_section(
+                value,
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::srgb(1.0, 1.0, 0.6),
+                    ..default()
+                },
+            );
+        }
+    }
+}
+
+fn follow_cursor(
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut cursor: Query<&mut Style, With<CursorItem>>,
+) {
+    let Some(position) = window_q
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for mut style in cursor.iter_mut() {
+        style.left = Val::Px(position.x + 8.0);
+        style.top = Val::Px(position.y + 8.0);
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fs::read;
+use std::path::Path;
+
+use crate::resource_pack::ResourcePack;
+use crate::resources::{GamePaths, Language, ResourcePackLocation};
+
+const LANG_FOLDER: &str = "assets/minecraft/lang";
+
+/// What translation keys say in the chosen language.
+#[derive(Resource, Debug)]
+pub struct Translations {
+    entries: HashMap<String, String>,
+}
+
+impl Default for Translations {
+    /// Just enough to read chat without a lang file.
+    fn default() -> Self {
+        let entries = [
+            ("chat.type.text", "[%s]: %s"),
+            ("chat.type.announcement", "[Server Announcement] %s: %s"),
+        ];
+
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Translations {
+    /// Adds the entries of a 1.8 `.lang` file, `key=value` per line.
+    fn extend(&mut self, data: &str) {
+        for line in data.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                self.entries.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// The text of `key`, or the key itself when it has no translation.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.entries.get(key).map_or(key, String::as_str)
+    }
+}
+
+/// Part of a translation, split where the `with` arguments go.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    /// index into `with`
+    Argument(usize),
+}
+
+/// Splits `template` at `%s` and `%1$s`, `%%` is a plain `%`.
+pub fn pieces(template: &str) -> Vec<Piece<'_>> {
+    let bytes = template.as_bytes();
+    let mut pieces = vec![];
+    let mut next_argument = 0;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+
+        let digits = bytes[i + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let mut end = i + 1 + digits;
+
+        let position = match digits {
+            0 => None,
+            _ if bytes.get(end) == Some(&b'$') => {
+                end += 1;
+                template[i + 1..i + 1 + digits]
+                    .parse::<usize>()
+                    .ok()
+                    .map(|n| n.saturating_sub(1))
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        match bytes.get(end) {
+            Some(b's' | b'd') => {
+                pieces.push(Piece::Text(&template[start..i]));
+                pieces.push(Piece::Argument(position.unwrap_or_else(|| {
+                    next_argument += 1;
+                    next_argument - 1
+                })));
+            }
+            // keeps the first %, drops the second
+            Some(b'%') if digits == 0 => pieces.push(Piece::Text(&template[start..=i])),
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+
+        start = end + 1;
+        i = start;
+    }
+
+    pieces.push(Piece::Text(&template[start..]));
+    pieces.retain(|piece| *piece != Piece::Text(""));
+    pieces
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Translations>()
+        .add_systems(Startup, load);
+}
+
+fn read_from_pack(path: &Path, name: &str) -> crate::error::Result<Vec<u8>> {
+    ResourcePack::open(path)?.read(name)
+}
+
+/// Reads the lang file from the resource pack, or from `lang` in the game root.
+fn load(
+    mut translations: ResMut<Translations>,
+    location: Res<ResourcePackLocation>,
+    paths: Res<GamePaths>,
+    language: Res<Language>,
+) {
+    let name = format!("{}.lang", language.locale);
+
+    let from_pack = location.path.as_ref().and_then(|path| {
+        read_from_pack(path, &format!("{LANG_FOLDER}/{name}"))
+            .inspect_err(|e| warn!("Could not read {name} from {path:?}: {e}"))
+            .ok()
+    });
+
+    let data = match from_pack {
+        Some(data) => data,
+        None => {
+            let path = paths.root.join("lang").join(&name);
+            match read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Could not read {path:?}: {e}, translation keys are shown as they are");
+                    return;
+                }
+            }
+        }
+    };
+
+    translations.extend(&String::from_utf8_lossy(&data));
+    info!("Loaded {} translations", translations.entries.len());
+}
+
+#[test]
+fn placeholders_take_arguments_in_order() {
+    assert_eq!(
+        pieces("%s gave %s to %s"),
+        [
+            Piece::Argument(0),
+            Piece::Text(" gave "),
+            Piece::Argument(1),
+            Piece::Text(" to "),
+            Piece::Argument(2),
+        ]
+    );
+}
+
+#[test]
+fn positional_placeholders_reorder() {
+    assert_eq!(
+        pieces("%2$s hit %1$s, %s"),
+        [
+            Piece::Argument(1),
+            Piece::Text(" hit "),
+            Piece::Argument(0),
+            Piece::Text(", "),
+            Piece::Argument(0),
+        ]
+    );
+}
+
+#[test]
+fn numbers_are_arguments_too() {
+    assert_eq!(
+        pieces("%d of %d"),
+        [Piece::Argument(0), Piece::Text(" of "), Piece::Argument(1)]
+    );
+}
+
+#[test]
+fn percent_signs_stay_text() {
+    assert_eq!(
+        pieces("100%% sure"),
+        [Piece::Text("100%"), Piece::Text(" sure")]
+    );
+    assert_eq!(pieces("all 100%"), [Piece::Text("all 100%")]);
+    assert_eq!(pieces("%5 and %x"), [Piece::Text("%5 and %x")]);
+}
+
+#[test]
+fn lang_files_add_and_override_entries() {
+    let mut translations = Translations::default();
+    translations.extend("# comment\nchat.type.text=<%s> %s\nitem.apple.name=Apple\nbroken line");
+
+    assert_eq!(translations.get("chat.type.text"), "<%s> %s");
+    assert_eq!(translations.get("item.apple.name"), "Apple");
+    assert_eq!(translations.get("missing.key"), "missing.key");
+}
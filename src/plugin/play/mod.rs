@@ -1,18 +1,44 @@
-use crate::message::{ClientMessage, ServerMessage};
+use crate::message::ServerMessage;
 use crate::plugin::play::chat::ChatComponent;
 use crate::plugin::transport::NetworkTransport;
 use crate::plugin::CursorState;
 use crate::resources::DisconnectedReason;
 use crate::state::AppState;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
+mod biome;
+mod block_atlas;
 mod block_builder;
+mod block_collision;
+mod block_hardness;
+mod block_model;
+mod block_state;
+mod button;
 mod chat;
-mod chat_proto;
+mod chat_events;
+mod chat_history;
+pub mod chat_proto;
+mod chat_queue;
 mod chunk_builder;
 mod chunk_cons;
 mod debug_screen;
+mod hud;
+mod interaction;
+mod inventory;
+mod inventory_screen;
+pub mod lang;
+mod physics;
 mod player;
+mod random;
+mod raycast;
+mod respawn;
+pub mod rich_text;
+mod section_culling;
+mod sky;
+mod sync;
+mod tab_complete;
+mod weather;
 mod world;
 
 pub struct PlayPlugin;
@@ -22,13 +48,32 @@ impl Plugin for PlayPlugin {
         app.add_systems(OnEnter(AppState::Playing), startup)
             .add_systems(
                 FixedUpdate,
-                (handle_server_messages, chat_message_sender).run_if(in_state(AppState::Playing)),
+                handle_server_messages.run_if(in_state(AppState::Playing)),
             )
             .add_plugins(world::plugin)
+            .add_plugins(sky::plugin)
+            .add_plugins(weather::plugin)
+            .add_plugins(block_atlas::plugin)
+            .add_plugins(block_model::plugin)
+            .add_plugins(lang::plugin)
             .add_plugins(chat::plugin)
+            .add_plugins(chat_events::plugin)
+            .add_plugins(chat_history::plugin)
+            .add_plugins(chat_queue::plugin)
+            .add_plugins(tab_complete::plugin)
+            .add_plugins(rich_text::plugin)
             .add_plugins(player::plugin)
+            .add_plugins(physics::plugin)
+            .add_plugins(raycast::plugin)
+            .add_plugins(interaction::plugin)
+            .add_plugins(inventory::plugin)
+            .add_plugins(inventory_screen::plugin)
+            .add_plugins(hud::plugin)
+            .add_plugins(respawn::plugin)
+            .add_plugins(sync::plugin)
             .add_plugins(debug_screen::plugin)
             .add_plugins(chunk_builder::plugin)
+            .add_plugins(section_culling::plugin)
             .add_systems(OnExit(AppState::Playing), cleanup);
     }
 }
@@ -43,47 +88,85 @@ pub fn cleanup(mut cursor_state: ResMut<CursorState>) {
     cursor_state.is_locked = false;
 }
 
-pub fn chat_message_sender(
-    mut chat_reader: EventReader<chat::ChatMessage>,
-    mut client_message_writer: EventWriter<ClientMessage>,
-) {
-    for message in chat_reader.read() {
-        client_message_writer.send(ClientMessage::ChatMessage {
-            message: message.message.clone(),
-        });
-    }
+/// Events the server's messages are turned into here, the other plugins read theirs themselves.
+#[derive(SystemParam)]
+struct ServerEvents<'w> {
+    chat: EventWriter<'w, chat::NewRawChatMessage>,
+    chunks: EventWriter<'w, chunk_builder::ChunkReceived>,
+    teleports: EventWriter<'w, player::Teleport>,
+    respawns: EventWriter<'w, respawn::Respawned>,
 }
 
 fn handle_server_messages(
     mut server_reader: EventReader<ServerMessage>,
     mut commands: Commands,
     mut app_state: ResMut<NextState<AppState>>,
-    mut chat_writer: EventWriter<chat::NewRawChatMessage>,
-    mut chunk_writer: EventWriter<chunk_builder::ChunkReceived>,
-    mut player_transform: Query<&mut Transform, With<player::Player>>,
-    mut last_location: Local<Option<Vec3>>,
+    mut events: ServerEvents,
+    player_q: Query<(&Transform, &physics::PlayerBody), With<player::Player>>,
 ) {
     for message in server_reader.read() {
         match message {
             ServerMessage::DisconnectedOnLogin { why: _ } => {}
 
-            ServerMessage::PlayerPositionAndLook { position, .. } => {
-                let mut transform = player_transform.single_mut();
+            ServerMessage::PlayerPositionAndLook {
+                position,
+                yaw,
+                pitch,
+                relative,
+            } => {
+                let Ok((transform, body)) = player_q.get_single() else {
+                    continue;
+                };
+                let (current_yaw, current_pitch) = player::minecraft_rotation(transform.rotation);
 
-                if let Some(last_location) = last_location.as_ref() {
-                    let distance = position.distance(*last_location);
-                    if distance >= 5.0 {
-                        transform.translation = position.to_owned();
+                // the server sends where the feet are
+                let mut position = *position;
+                for axis in 0..3 {
+                    if relative & (1 << axis) != 0 {
+                        position[axis] += body.position[axis];
                     }
-                } else {
-                    transform.translation = position.to_owned();
                 }
 
-                *last_location = Some(position.to_owned());
+                let rotate = |bit: u8, angle: f32, current: f32| {
+                    if relative & bit != 0 {
+                        angle + current
+                    } else {
+                        angle
+                    }
+                };
+                let yaw = rotate(0x08, *yaw, current_yaw);
+                let pitch = rotate(0x10, *pitch, current_pitch);
+
+                info!("Teleported to {position} looking at {yaw} {pitch}");
+                events.teleports.send(player::Teleport {
+                    position,
+                    yaw,
+                    pitch,
+                });
             }
 
             ServerMessage::GameReady { base } => {
                 info!("Game is ready!");
+                commands.insert_resource(player::GameMode::from_id(base.game_mode));
+                commands.insert_resource(world::Dimension::from_id(base.dimension as i32));
+            }
+
+            ServerMessage::Respawn {
+                dimension,
+                game_mode,
+            } => {
+                info!("Respawning in dimension {dimension}");
+                commands.insert_resource(player::GameMode::from_id(*game_mode));
+                commands.insert_resource(world::Dimension::from_id(*dimension));
+                commands.insert_resource(respawn::DownloadingTerrain::default());
+
+                // sent along with the chunks, so the old ones go before the new ones come
+                events.respawns.send(respawn::Respawned);
+            }
+
+            ServerMessage::ChangeGameState { reason: 3, value } => {
+                info!("Game mode changed to {value}");
+                commands.insert_resource(player::GameMode::from_id(*value as u8));
             }
 
             ServerMessage::Disconnected { why } => {
@@ -93,17 +176,19 @@ fn handle_server_messages(
             }
 
             ServerMessage::NewChunk { chunk } => {
-                chunk_writer.send(chunk_builder::ChunkReceived {
+                events.chunks.send(chunk_builder::ChunkReceived {
                     smp_chunk: chunk.clone(),
                 });
             }
 
             ServerMessage::ChatMessage { message } => {
                 info!("Chat message: {}", message);
-                chat_writer.send(chat::NewRawChatMessage {
+                events.chat.send(chat::NewRawChatMessage {
                     raw_object: message.clone(),
                 });
             }
+
+            _ => {}
         }
     }
 }
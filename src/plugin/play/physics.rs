@@ -0,0 +1,958 @@
+use super::block_builder::{Block, BlockFace};
+use super::player::{self, Player};
+use super::respawn::DownloadingTerrain;
+use super::world::{OnGround, WorldChunkData};
+use crate::state::AppState;
+use bevy::math::DVec3;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Vanilla moves entities 20 times a second, every constant below is per tick.
+pub const TICK: Duration = Duration::from_millis(50);
+
+/// Sent in a frame where a tick has passed, for everything else that goes at vanilla's pace.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Tick;
+
+/// Time gathered towards the next tick, the camera is placed between ticks by it.
+#[derive(Resource, Debug, Default)]
+pub struct TickClock {
+    accumulated: Duration,
+}
+
+impl TickClock {
+    /// How far the next tick is, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        self.accumulated.as_secs_f64() / TICK.as_secs_f64()
+    }
+}
+
+pub const PLAYER_WIDTH: f64 = 0.6;
+pub const PLAYER_HEIGHT: f64 = 1.8;
+pub const EYE_HEIGHT: f64 = 1.62;
+pub const SNEAK_EYE_HEIGHT: f64 = 1.54;
+
+const STEP_HEIGHT: f64 = 0.6;
+const GRAVITY: f64 = 0.08;
+const VERTICAL_DRAG: f64 = 0.98;
+const AIR_FRICTION: f64 = 0.91;
+// keeps ground acceleration the same on blocks of any slipperiness
+const GROUND_ACCELERATION: f64 = 0.16277136;
+const WALK_SPEED: f64 = 0.1;
+const AIR_SPEED: f64 = 0.02;
+const SPRINT_MULTIPLIER: f64 = 1.3;
+const SNEAK_MULTIPLIER: f64 = 0.3;
+const INPUT_DRAG: f64 = 0.98;
+const JUMP_VELOCITY: f64 = 0.42;
+const SPRINT_JUMP_BOOST: f64 = 0.2;
+const JUMP_COOLDOWN: u8 = 10;
+const SPRINT_THRESHOLD: f64 = 0.8;
+// ticks a second tap on forward has to start sprinting
+const SPRINT_DOUBLE_TAP: u8 = 7;
+// sneaking checks for a floor in steps this big
+const EDGE_STEP: f64 = 0.05;
+const MIN_VELOCITY: f64 = 0.005;
+const SWIM_SPEED: f64 = 0.02;
+const SWIM_UP: f64 = 0.04;
+const WATER_DRAG: f64 = 0.8;
+const LAVA_DRAG: f64 = 0.5;
+const FLUID_GRAVITY: f64 = 0.02;
+// flowing water pushes this much per tick
+const WATER_PUSH: f64 = 0.014;
+// swimming against a ledge hops out of the fluid
+const LEDGE_JUMP: f64 = 0.3;
+const CLIMB_SPEED: f64 = 0.2;
+const MAX_CLIMB_SPEED: f64 = 0.15;
+const WEB_SLOWDOWN: DVec3 = DVec3::new(0.25, 0.05, 0.25);
+const SOUL_SAND_SLOWDOWN: f64 = 0.4;
+
+/// An axis aligned box in world space, like vanilla's `AxisAlignedBB`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl BoundingBox {
+    pub fn new(min: DVec3, max: DVec3) -> Self {
+        Self { min, max }
+    }
+
+    /// The box of a player standing at `feet`.
+    pub fn of_player(feet: DVec3) -> Self {
+        let half = PLAYER_WIDTH / 2.0;
+
+        Self::new(
+            feet - DVec3::new(half, 0.0, half),
+            feet + DVec3::new(half, PLAYER_HEIGHT, half),
+        )
+    }
+
+    pub fn offset(&self, by: DVec3) -> Self {
+        Self::new(self.min + by, self.max + by)
+    }
+
+    /// Grows the box towards `by`, covering everything it passes through.
+    pub fn stretch(&self, by: DVec3) -> Self {
+        Self::new(
+            self.min + by.min(DVec3::ZERO),
+            self.max + by.max(DVec3::ZERO),
+        )
+    }
+
+    /// Moves every side by `by` towards the middle, negative values grow the box.
+    pub fn shrink(&self, by: DVec3) -> Self {
+        Self::new(self.min + by, self.max - by)
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.cmplt(other.max).all() && self.max.cmpgt(other.min).all()
+    }
+
+    /// How far `other` can move by `offset` along `axis` before it hits this box.
+    fn clip(&self, other: &BoundingBox, axis: usize, mut offset: f64) -> f64 {
+        let overlaps = (0..3)
+            .filter(|other_axis| *other_axis != axis)
+            .all(|a| other.max[a] > self.min[a] && other.min[a] < self.max[a]);
+
+        if !overlaps {
+            return offset;
+        }
+
+        if offset > 0.0 && other.max[axis] <= self.min[axis] {
+            offset = offset.min(self.min[axis] - other.max[axis]);
+        } else if offset < 0.0 && other.min[axis] >= self.max[axis] {
+            offset = offset.max(self.max[axis] - other.min[axis]);
+        }
+
+        offset
+    }
+}
+
+fn clip_all(boxes: &[BoundingBox], moving: &BoundingBox, axis: usize, offset: f64) -> f64 {
+    boxes.iter().fold(offset, |offset, obstacle| {
+        obstacle.clip(moving, axis, offset)
+    })
+}
+
+/// The block at `pos`, air if it isn't loaded.
+fn block_at(world: &WorldChunkData, pos: IVec3) -> Block {
+    world
+        .block_at(pos)
+        .map_or_else(Block::default, |block| Block::new(block.id, block.metadata))
+}
+
+/// Blocks touched by `area` along with their position.
+fn blocks_in<'a>(
+    world: &'a WorldChunkData,
+    area: &BoundingBox,
+) -> impl Iterator<Item = (IVec3, Block)> + 'a {
+    world
+        .blocks_in(area.min.floor().as_ivec3(), area.max.floor().as_ivec3())
+        .map(|(pos, block)| (pos, Block::new(block.id, block.metadata)))
+}
+
+// close to vanilla's `Material.blocksMovement`
+fn is_solid(world: &WorldChunkData, pos: IVec3) -> bool {
+    !block_at(world, pos)
+        .collision_boxes(|_| Block::default())
+        .is_empty()
+}
+
+/// Which way the fluid at `pos` flows, like vanilla's `BlockLiquid.getFlowVector`.
+fn flow_vector(world: &WorldChunkData, pos: IVec3, fluid: Block) -> DVec3 {
+    const SIDES: [BlockFace; 4] = [
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::East,
+    ];
+
+    // how far the fluid is from its source, falling fluid counts as a source
+    let decay = |pos: IVec3| {
+        let block = block_at(world, pos);
+        let level = if block.metadata >= 8 {
+            0
+        } else {
+            block.metadata as i32
+        };
+        (block.is_fluid() && block.is_lava() == fluid.is_lava()).then_some(level)
+    };
+
+    let own = decay(pos).unwrap_or(0);
+    let mut flow = DVec3::ZERO;
+
+    for face in SIDES {
+        let side = pos + face.normal();
+        let difference = match decay(side) {
+            Some(level) => level - own,
+            // pours down into the gap next to it
+            None if !is_solid(world, side) => match decay(side - IVec3::Y) {
+                Some(level) => level - (own - 8),
+                None => continue,
+            },
+            None => continue,
+        };
+
+        flow += face.normal().as_dvec3() * difference as f64;
+    }
+
+    // falling down the side of a wall
+    if fluid.metadata >= 8
+        && SIDES.into_iter().any(|face| {
+            let side = pos + face.normal();
+            is_solid(world, side) || is_solid(world, side + IVec3::Y)
+        })
+    {
+        flow = flow.normalize_or_zero() + DVec3::NEG_Y * 6.0;
+    }
+
+    flow.normalize_or_zero()
+}
+
+/// Collision boxes of the loaded blocks that intersect `area`, unloaded blocks have none.
+pub fn collision_boxes(world: &WorldChunkData, area: &BoundingBox) -> Vec<BoundingBox> {
+    // fences and walls stick out of the block below
+    let min = area.min.floor().as_ivec3() - IVec3::Y;
+    let max = area.max.floor().as_ivec3();
+
+    world
+        .blocks_in(min, max)
+        .flat_map(|(pos, block)| {
+            let block = Block::new(block.id, block.metadata);
+            let neighbor = |face: BlockFace| block_at(world, pos + face.normal());
+
+            block
+                .collision_boxes(neighbor)
+                .into_iter()
+                .map(move |shape| shape.offset(pos.as_dvec3()))
+        })
+        .filter(|shape| shape.intersects(area))
+        .collect()
+}
+
+/// What the player asks for during one tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MovementInput {
+    /// 1 forwards, -1 backwards
+    pub forward: f64,
+    /// 1 to the right, -1 to the left
+    pub strafe: f64,
+    pub jump: bool,
+    pub sneak: bool,
+    pub sprint: bool,
+    /// radians around +Y, 0 looks towards -Z like Bevy's forward
+    pub yaw: f64,
+}
+
+/// Where the player is and how it moves, stepped every `TICK`.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct PlayerBody {
+    /// feet, in the middle of the bottom of the box
+    pub position: DVec3,
+    /// position one tick ago, the camera is interpolated between the two
+    pub previous: DVec3,
+    /// blocks per tick
+    pub velocity: DVec3,
+    pub on_ground: bool,
+    pub collided_horizontally: bool,
+    pub sprinting: bool,
+    pub sneaking: bool,
+    pub in_water: bool,
+    pub in_lava: bool,
+    in_web: bool,
+    jump_ticks: u8,
+    sprint_tap_ticks: u8,
+    walked_forward: bool,
+}
+
+impl PlayerBody {
+    pub fn new(position: DVec3) -> Self {
+        Self {
+            position,
+            previous: position,
+            velocity: DVec3::ZERO,
+            on_ground: false,
+            collided_horizontally: false,
+            sprinting: false,
+            sneaking: false,
+            in_water: false,
+            in_lava: false,
+            in_web: false,
+            jump_ticks: 0,
+            sprint_tap_ticks: 0,
+            walked_forward: false,
+        }
+    }
+
+    /// Moves the body without interpolating and stops it.
+    pub fn teleport(&mut self, position: DVec3) {
+        self.position = position;
+        self.previous = position;
+        self.velocity = DVec3::ZERO;
+    }
+
+    pub fn eye_height(&self) -> f64 {
+        if self.sneaking {
+            SNEAK_EYE_HEIGHT
+        } else {
+            EYE_HEIGHT
+        }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::of_player(self.position)
+    }
+
+    /// One tick of vanilla 1.8 player movement.
+    pub fn tick(&mut self, world: &WorldChunkData, input: &MovementInput) {
+        self.previous = self.position;
+
+        // hold still until the terrain below arrives
+        if world.block_at(self.position.floor().as_ivec3()).is_none() {
+            self.velocity = DVec3::ZERO;
+            return;
+        }
+
+        self.update_fluids(world);
+        self.sneaking = input.sneak;
+
+        let mut forward = input.forward;
+        let mut strafe = input.strafe;
+
+        if self.sneaking {
+            forward *= SNEAK_MULTIPLIER;
+            strafe *= SNEAK_MULTIPLIER;
+        }
+
+        self.update_sprinting(forward, input.sprint);
+
+        self.jump_ticks = self.jump_ticks.saturating_sub(1);
+
+        for axis in 0..3 {
+            if self.velocity[axis].abs() < MIN_VELOCITY {
+                self.velocity[axis] = 0.0;
+            }
+        }
+
+        if !input.jump {
+            self.jump_ticks = 0;
+        } else if self.in_water || self.in_lava {
+            self.velocity.y += SWIM_UP;
+        } else if self.on_ground && self.jump_ticks == 0 {
+            self.jump(input.yaw);
+            self.jump_ticks = JUMP_COOLDOWN;
+        }
+
+        self.move_with_heading(world, strafe * INPUT_DRAG, forward * INPUT_DRAG, input.yaw);
+    }
+
+    /// Checks which fluids the player is in and lets flowing water push it.
+    fn update_fluids(&mut self, world: &WorldChunkData) {
+        // only counts once the water reaches above the feet
+        let area = self.bounding_box().shrink(DVec3::new(0.001, 0.401, 0.001));
+        let mut flow = DVec3::ZERO;
+        self.in_water = false;
+
+        for (pos, block) in blocks_in(world, &area) {
+            if block.is_water() && area.max.y >= pos.y as f64 + block.fluid_height() {
+                self.in_water = true;
+                flow += flow_vector(world, pos, block);
+            }
+        }
+
+        self.velocity += flow.normalize_or_zero() * WATER_PUSH;
+
+        let area = self.bounding_box().shrink(DVec3::new(0.1, 0.4, 0.1));
+        self.in_lava = blocks_in(world, &area).any(|(_, block)| block.is_lava());
+    }
+
+    fn update_sprinting(&mut self, forward: f64, sprint_key: bool) {
+        let walks_forward = forward >= SPRINT_THRESHOLD;
+        self.sprint_tap_ticks = self.sprint_tap_ticks.saturating_sub(1);
+
+        // tapping forward twice
+        if self.on_ground && !self.sneaking && !self.walked_forward && walks_forward {
+            if self.sprint_tap_ticks == 0 && !sprint_key {
+                self.sprint_tap_ticks = SPRINT_DOUBLE_TAP;
+            } else {
+                self.sprinting = true;
+            }
+        }
+
+        if walks_forward && sprint_key {
+            self.sprinting = true;
+        }
+
+        if !walks_forward || self.collided_horizontally {
+            self.sprinting = false;
+        }
+
+        self.walked_forward = walks_forward;
+    }
+
+    fn jump(&mut self, yaw: f64) {
+        self.velocity.y = JUMP_VELOCITY;
+
+        if self.sprinting {
+            self.velocity += facing(yaw) * SPRINT_JUMP_BOOST;
+        }
+    }
+
+    fn move_with_heading(&mut self, world: &WorldChunkData, strafe: f64, forward: f64, yaw: f64) {
+        if self.in_water || self.in_lava {
+            self.swim(world, strafe, forward, yaw);
+            return;
+        }
+
+        let friction = if self.on_ground {
+            self.ground(world).slipperiness() * AIR_FRICTION
+        } else {
+            AIR_FRICTION
+        };
+
+        let sprint = if self.sprinting {
+            SPRINT_MULTIPLIER
+        } else {
+            1.0
+        };
+        let speed = if self.on_ground {
+            WALK_SPEED * sprint * GROUND_ACCELERATION / friction.powi(3)
+        } else {
+            AIR_SPEED * sprint
+        };
+
+        self.accelerate(strafe, forward, speed, yaw);
+
+        if self.on_ladder(world) {
+            self.velocity.x = self.velocity.x.clamp(-MAX_CLIMB_SPEED, MAX_CLIMB_SPEED);
+            self.velocity.z = self.velocity.z.clamp(-MAX_CLIMB_SPEED, MAX_CLIMB_SPEED);
+            self.velocity.y = self.velocity.y.max(-MAX_CLIMB_SPEED);
+
+            // sneaking holds on
+            if self.sneaking && self.velocity.y < 0.0 {
+                self.velocity.y = 0.0;
+            }
+        }
+
+        self.move_and_collide(world, self.velocity);
+
+        if self.collided_horizontally && self.on_ladder(world) {
+            self.velocity.y = CLIMB_SPEED;
+        }
+
+        self.velocity.y = (self.velocity.y - GRAVITY) * VERTICAL_DRAG;
+        self.velocity.x *= friction;
+        self.velocity.z *= friction;
+    }
+
+    fn swim(&mut self, world: &WorldChunkData, strafe: f64, forward: f64, yaw: f64) {
+        let start_y = self.position.y;
+
+        self.accelerate(strafe, forward, SWIM_SPEED, yaw);
+        self.move_and_collide(world, self.velocity);
+
+        self.velocity *= if self.in_water { WATER_DRAG } else { LAVA_DRAG };
+        self.velocity.y -= FLUID_GRAVITY;
+
+        if self.collided_horizontally {
+            let ledge = self.bounding_box().offset(DVec3::new(
+                self.velocity.x,
+                self.velocity.y + STEP_HEIGHT - self.position.y + start_y,
+                self.velocity.z,
+            ));
+            let free = collision_boxes(world, &ledge).is_empty()
+                && !blocks_in(world, &ledge).any(|(_, block)| block.is_fluid());
+
+            if free {
+                self.velocity.y = LEDGE_JUMP;
+            }
+        }
+    }
+
+    /// The block the player stands on.
+    fn ground(&self, world: &WorldChunkData) -> Block {
+        block_at(world, self.position.floor().as_ivec3() - IVec3::Y)
+    }
+
+    fn on_ladder(&self, world: &WorldChunkData) -> bool {
+        block_at(world, self.position.floor().as_ivec3()).is_climbable()
+    }
+
+    fn accelerate(&mut self, strafe: f64, forward: f64, speed: f64, yaw: f64) {
+        let length = strafe * strafe + forward * forward;
+
+        if length < 1.0e-4 {
+            return;
+        }
+
+        let scale = speed / length.sqrt().max(1.0);
+        let right = DVec3::new(yaw.cos(), 0.0, -yaw.sin());
+
+        self.velocity += (facing(yaw) * forward + right * strafe) * scale;
+    }
+
+    fn has_floor(&self, world: &WorldChunkData, x: f64, z: f64) -> bool {
+        let area = self.bounding_box().offset(DVec3::new(x, -1.0, z));
+        !collision_boxes(world, &area).is_empty()
+    }
+
+    /// Moves by `movement` as far as blocks allow, stepping up ledges up to `STEP_HEIGHT`.
+    fn move_and_collide(&mut self, world: &WorldChunkData, mut movement: DVec3) {
+        if self.in_web {
+            self.in_web = false;
+            movement *= WEB_SLOWDOWN;
+            self.velocity = DVec3::ZERO;
+        }
+
+        // sneaking keeps the player from walking off an edge
+        if self.on_ground && self.sneaking {
+            let approach = |value: f64| {
+                if value.abs() < EDGE_STEP {
+                    0.0
+                } else {
+                    value - EDGE_STEP * value.signum()
+                }
+            };
+
+            while movement.x != 0.0 && !self.has_floor(world, movement.x, 0.0) {
+                movement.x = approach(movement.x);
+            }
+
+            while movement.z != 0.0 && !self.has_floor(world, 0.0, movement.z) {
+                movement.z = approach(movement.z);
+            }
+
+            while movement.x != 0.0
+                && movement.z != 0.0
+                && !self.has_floor(world, movement.x, movement.z)
+            {
+                movement.x = approach(movement.x);
+                movement.z = approach(movement.z);
+            }
+        }
+
+        let start = self.bounding_box();
+        let boxes = collision_boxes(world, &start.stretch(movement));
+
+        let (mut moved, mut bounds) = slide(&boxes, start, movement, [1, 0, 2]);
+
+        let falling = movement.y != moved.y && movement.y < 0.0;
+        let blocked = movement.x != moved.x || movement.z != moved.z;
+
+        if (self.on_ground || falling) && blocked {
+            let up = DVec3::new(movement.x, STEP_HEIGHT, movement.z);
+            let boxes = collision_boxes(world, &start.stretch(up));
+
+            // rise as far as the path ahead allows, or as far as the spot we stand on allows
+            let ahead = clip_all(
+                &boxes,
+                &start.stretch(DVec3::new(movement.x, 0.0, movement.z)),
+                1,
+                STEP_HEIGHT,
+            );
+            let candidates = [ahead, clip_all(&boxes, &start, 1, STEP_HEIGHT)].map(|rise| {
+                let raised = start.offset(DVec3::Y * rise);
+                let (step, bounds) = slide(&boxes, raised, movement.with_y(0.0), [0, 2, 1]);
+                (step.with_y(rise), bounds)
+            });
+
+            let horizontal = |moved: DVec3| moved.x * moved.x + moved.z * moved.z;
+            let (mut step, mut step_bounds) =
+                if horizontal(candidates[0].0) > horizontal(candidates[1].0) {
+                    candidates[0]
+                } else {
+                    candidates[1]
+                };
+
+            // and back down onto whatever is there
+            let down = clip_all(&boxes, &step_bounds, 1, -step.y);
+            step_bounds = step_bounds.offset(DVec3::Y * down);
+            step.y += down;
+
+            if horizontal(step) > horizontal(moved) {
+                moved = step;
+                bounds = step_bounds;
+            }
+        }
+
+        self.position = DVec3::new(
+            (bounds.min.x + bounds.max.x) / 2.0,
+            bounds.min.y,
+            (bounds.min.z + bounds.max.z) / 2.0,
+        );
+
+        self.collided_horizontally = movement.x != moved.x || movement.z != moved.z;
+        self.on_ground = movement.y != moved.y && movement.y < 0.0;
+
+        for axis in 0..3 {
+            if movement[axis] != moved[axis] {
+                self.velocity[axis] = 0.0;
+            }
+        }
+
+        // blocks that act on whoever is inside them
+        let inside = self.bounding_box().shrink(DVec3::splat(0.001));
+        for (_, block) in blocks_in(world, &inside) {
+            match block.id {
+                30 => self.in_web = true,
+                88 => {
+                    self.velocity.x *= SOUL_SAND_SLOWDOWN;
+                    self.velocity.z *= SOUL_SAND_SLOWDOWN;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Moves `bounds` one axis at a time in `order`, returning how far it got on each.
+fn slide(
+    boxes: &[BoundingBox],
+    mut bounds: BoundingBox,
+    movement: DVec3,
+    order: [usize; 3],
+) -> (DVec3, BoundingBox) {
+    let mut moved = DVec3::ZERO;
+
+    for axis in order {
+        moved[axis] = clip_all(boxes, &bounds, axis, movement[axis]);
+
+        let mut offset = DVec3::ZERO;
+        offset[axis] = moved[axis];
+        bounds = bounds.offset(offset);
+    }
+
+    (moved, bounds)
+}
+
+/// Horizontal direction the player looks at.
+fn facing(yaw: f64) -> DVec3 {
+    DVec3::new(-yaw.sin(), 0.0, -yaw.cos())
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<Tick>()
+        .init_resource::<TickClock>()
+        .add_systems(PreUpdate, count_ticks.run_if(in_state(AppState::Playing)))
+        .add_systems(
+            Update,
+            simulate
+                .after(player::move_camera)
+                .run_if(in_state(AppState::Playing))
+                .run_if(not(player::is_flying))
+                .run_if(not(resource_exists::<DownloadingTerrain>)),
+        );
+}
+
+fn count_ticks(time: Res<Time>, mut ticks: EventWriter<Tick>, mut clock: ResMut<TickClock>) {
+    clock.accumulated += time.delta();
+
+    // don't try to catch up after a long hitch
+    if clock.accumulated > TICK * 10 {
+        clock.accumulated = TICK;
+    }
+
+    while clock.accumulated >= TICK {
+        clock.accumulated -= TICK;
+        ticks.send(Tick);
+    }
+}
+
+fn read_input(keys: &ButtonInput<KeyCode>, transform: &Transform) -> MovementInput {
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        keys.pressed(positive) as i32 as f64 - keys.pressed(negative) as i32 as f64
+    };
+
+    MovementInput {
+        forward: axis(KeyCode::KeyW, KeyCode::KeyS),
+        strafe: axis(KeyCode::KeyD, KeyCode::KeyA),
+        jump: keys.pressed(KeyCode::Space),
+        sneak: keys.pressed(KeyCode::ShiftLeft),
+        sprint: keys.pressed(KeyCode::ControlLeft),
+        yaw: transform.rotation.to_euler(EulerRot::YXZ).0 as f64,
+    }
+}
+
+/// Steps the player once per `Tick` and places the camera between the last two.
+pub(crate) fn simulate(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    clock: Res<TickClock>,
+    mut ticks: EventReader<Tick>,
+    world_data: Res<WorldChunkData>,
+    mut player_q: Query<(Entity, &mut Transform, &mut PlayerBody, Has<OnGround>), With<Player>>,
+) {
+    let Ok((entity, mut transform, mut body, marked)) = player_q.get_single_mut() else {
+        return;
+    };
+
+    for _ in ticks.read() {
+        let input = read_input(&keys, &transform);
+        body.tick(&world_data, &input);
+    }
+
+    let feet = body.previous.lerp(body.position, clock.progress());
+    transform.translation = (feet + DVec3::Y * body.eye_height()).as_vec3();
+
+    if body.on_ground != marked {
+        if body.on_ground {
+            commands.entity(entity).insert(OnGround);
+        } else {
+            commands.entity(entity).remove::<OnGround>();
+        }
+    }
+}
+
+// a stone floor with its top at y 64, loaded a chunk around the origin chunk
+#[cfg(test)]
+fn test_world() -> WorldChunkData {
+    use gyra_proto::smp::ChunkColumn;
+
+    let mut world = WorldChunkData::default();
+    for x in -1..=1 {
+        for z in -1..=1 {
+            world.insert_column(ChunkColumn::from_sections(vec![], 0, x, z));
+        }
+    }
+
+    for x in -16..32 {
+        for z in -16..32 {
+            place(&mut world, IVec3::new(x, 63, z), 1, 0);
+        }
+    }
+
+    world
+}
+
+#[cfg(test)]
+fn place(world: &mut WorldChunkData, pos: IVec3, id: u16, metadata: u8) {
+    use gyra_proto::smp::NetworkBlock;
+
+    world.set_block(pos, NetworkBlock { id, metadata });
+}
+
+#[cfg(test)]
+fn run(body: &mut PlayerBody, world: &WorldChunkData, input: MovementInput, ticks: usize) {
+    for _ in 0..ticks {
+        body.tick(world, &input);
+    }
+}
+
+#[cfg(test)]
+const FORWARD: MovementInput = MovementInput {
+    forward: 1.0,
+    strafe: 0.0,
+    jump: false,
+    sneak: false,
+    sprint: false,
+    yaw: 0.0,
+};
+
+#[test]
+fn falls_onto_the_ground() {
+    let world = test_world();
+    let mut body = PlayerBody::new(DVec3::new(8.5, 70.0, 8.5));
+
+    run(&mut body, &world, MovementInput::default(), 40);
+
+    assert!(body.on_ground);
+    assert_eq!(body.position.y, 64.0);
+}
+
+#[test]
+fn walks_at_vanilla_speed() {
+    let world = test_world();
+    let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 20.5));
+
+    run(&mut body, &world, FORWARD, 20);
+    let start = body.position;
+    run(&mut body, &world, FORWARD, 20);
+
+    // 4.317 blocks a second
+    let speed = start.distance(body.position);
+    assert!((speed - 4.317).abs() < 0.01, "{speed}");
+}
+
+#[test]
+fn jumps_a_bit_over_a_block() {
+    let world = test_world();
+    let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 8.5));
+    let jump = MovementInput {
+        jump: true,
+        ..default()
+    };
+
+    body.tick(&world, &MovementInput::default());
+
+    let mut peak: f64 = 0.0;
+    for _ in 0..12 {
+        body.tick(&world, &jump);
+        peak = peak.max(body.position.y - 64.0);
+    }
+
+    assert!((peak - 1.2492).abs() < 0.001, "{peak}");
+}
+
+#[test]
+fn steps_onto_slabs_but_not_blocks() {
+    let mut world = test_world();
+    for z in 0..=6 {
+        place(&mut world, IVec3::new(8, 64, z), 44, 0);
+    }
+    place(&mut world, IVec3::new(10, 64, 6), 1, 0);
+
+    let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 8.5));
+    run(&mut body, &world, FORWARD, 20);
+    assert_eq!(body.position.y, 64.5);
+
+    let mut body = PlayerBody::new(DVec3::new(10.5, 64.0, 8.5));
+    run(&mut body, &world, FORWARD, 20);
+    assert_eq!(body.position.y, 64.0);
+    assert_eq!(body.position.z, 7.3);
+}
+
+#[test]
+fn sneaking_stops_at_edges() {
+    let mut world = test_world();
+    for x in -16..32 {
+        place(&mut world, IVec3::new(x, 63, 5), 0, 0);
+    }
+
+    let sneak = MovementInput {
+        sneak: true,
+        ..FORWARD
+    };
+    let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 8.5));
+    run(&mut body, &world, sneak, 100);
+
+    assert!(body.on_ground);
+    assert!(body.position.z > 5.69, "{}", body.position.z);
+}
+
+// water from y 64 to 70 around the origin
+#[cfg(test)]
+fn pool(world: &mut WorldChunkData, id: u16) {
+    for x in 0..16 {
+        for y in 64..70 {
+            for z in 0..16 {
+                place(world, IVec3::new(x, y, z), id, 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn sinks_in_water_and_swims_up() {
+    let mut world = test_world();
+    pool(&mut world, 9);
+
+    let mut body = PlayerBody::new(DVec3::new(8.5, 67.0, 8.5));
+    run(&mut body, &world, MovementInput::default(), 10);
+    assert!(body.in_water);
+    assert!(body.position.y < 67.0);
+    // sinking is slow
+    assert!(body.velocity.y > -0.1);
+
+    let swim = MovementInput {
+        jump: true,
+        ..default()
+    };
+    let sunk = body.position.y;
+    run(&mut body, &world, swim, 10);
+    assert!(body.position.y > sunk);
+}
+
+#[test]
+fn lava_is_slower_than_water() {
+    let distance = |fluid| {
+        let mut world = test_world();
+        pool(&mut world, fluid);
+
+        let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 15.5));
+        run(&mut body, &world, FORWARD, 20);
+        15.5 - body.position.z
+    };
+
+    let water = distance(9);
+    let lava = distance(11);
+    assert!(lava < water && lava > 0.0, "{lava} {water}");
+}
+
+#[test]
+fn flowing_water_pushes() {
+    let mut world = test_world();
+    place(&mut world, IVec3::new(8, 64, 8), 9, 0);
+    place(&mut world, IVec3::new(9, 64, 8), 8, 1);
+    place(&mut world, IVec3::new(10, 64, 8), 8, 2);
+    place(&mut world, IVec3::new(11, 64, 8), 8, 3);
+
+    let mut body = PlayerBody::new(DVec3::new(9.5, 64.0, 8.5));
+    run(&mut body, &world, MovementInput::default(), 5);
+
+    assert!(body.in_water);
+    assert!(body.position.x > 9.5);
+}
+
+#[test]
+fn climbs_ladders_and_holds_on_while_sneaking() {
+    let mut world = test_world();
+    for y in 64..74 {
+        place(&mut world, IVec3::new(8, y, 6), 1, 0);
+        // hangs on the block to the north
+        place(&mut world, IVec3::new(8, y, 7), 65, 3);
+    }
+
+    let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 7.5));
+    run(&mut body, &world, FORWARD, 20);
+    // about 0.12 a tick once it touches the ladder
+    assert!(body.position.y > 65.5, "{}", body.position.y);
+
+    let hold = MovementInput {
+        sneak: true,
+        ..default()
+    };
+    run(&mut body, &world, hold, 2);
+    let held = body.position.y;
+    run(&mut body, &world, hold, 20);
+    assert_eq!(body.position.y, held);
+
+    // letting go slides down no faster than the clamp
+    let start = body.position.y;
+    run(&mut body, &world, MovementInput::default(), 10);
+    assert!(start - body.position.y <= MAX_CLIMB_SPEED * 10.0 + 1e-9);
+}
+
+#[test]
+fn soul_sand_slows_walking() {
+    let distance = |floor| {
+        let mut world = test_world();
+        for x in 0..16 {
+            for z in 0..16 {
+                place(&mut world, IVec3::new(x, 63, z), floor, 0);
+            }
+        }
+
+        let mut body = PlayerBody::new(DVec3::new(8.5, 64.0, 15.5));
+        run(&mut body, &world, FORWARD, 20);
+        15.5 - body.position.z
+    };
+
+    let stone = distance(1);
+    let soul_sand = distance(88);
+    assert!(soul_sand < stone * 0.6, "{soul_sand} {stone}");
+}
+
+#[test]
+fn cobwebs_slow_falling() {
+    let mut world = test_world();
+    for y in 64..72 {
+        place(&mut world, IVec3::new(8, y, 8), 30, 0);
+    }
+
+    let mut body = PlayerBody::new(DVec3::new(8.5, 72.0, 8.5));
+    run(&mut body, &world, MovementInput::default(), 20);
+
+    // falling freely would have landed by now
+    assert!(body.position.y > 68.0, "{}", body.position.y);
+}
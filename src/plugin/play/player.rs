@@ -1,10 +1,12 @@
 use crate::components::MainCamera;
-use crate::message::ClientMessage;
 use crate::plugin::consts::WorldLayer;
+use crate::plugin::play::physics::{PlayerBody, EYE_HEIGHT};
+use crate::plugin::CursorState;
 use crate::state::AppState;
 use bevy::color::palettes::css::WHITE;
 use bevy::core_pipeline::motion_blur::{MotionBlur, MotionBlurBundle};
 use bevy::input::mouse::MouseMotion;
+use bevy::math::DVec3;
 use bevy::pbr::wireframe::{WireframeConfig, WireframePlugin};
 use bevy::prelude::*;
 use bevy::render::view::{
@@ -17,6 +19,55 @@ pub(crate) struct Player;
 #[derive(Debug, Component)]
 pub(crate) struct WorldModelCamera;
 
+/// Game mode of the player, from `JoinGame`.
+#[derive(Debug, Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum GameMode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn from_id(id: u8) -> Self {
+        // bit 3 is the hardcore flag
+        match id & 0x7 {
+            1 => GameMode::Creative,
+            2 => GameMode::Adventure,
+            3 => GameMode::Spectator,
+            _ => GameMode::Survival,
+        }
+    }
+
+    /// Creative and spectator players fly through everything.
+    pub fn flies(&self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+
+    /// How far away blocks can be reached.
+    pub fn reach(&self) -> f32 {
+        match self {
+            GameMode::Creative => 5.0,
+            _ => 4.5,
+        }
+    }
+}
+
+pub(crate) fn is_flying(game_mode: Res<GameMode>) -> bool {
+    game_mode.flies()
+}
+
+/// The server moved the player, the body stops and the camera jumps there.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct Teleport {
+    /// feet
+    pub position: DVec3,
+    /// degrees, as Minecraft counts them
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
 #[derive(Debug, Resource)]
 struct PlayerEntity {
     entity: Entity,
@@ -24,6 +75,8 @@ struct PlayerEntity {
 
 pub fn plugin(app: &mut App) {
     app.add_plugins(WireframePlugin)
+        .init_resource::<GameMode>()
+        .add_event::<Teleport>()
         .insert_resource(WireframeConfig {
             global: false,
             default_color: WHITE.into(),
@@ -32,7 +85,12 @@ pub fn plugin(app: &mut App) {
         .add_systems(OnExit(AppState::Playing), cleanup)
         .add_systems(
             Update,
-            (movement, move_camera).run_if(in_state(AppState::Playing)),
+            (
+                movement.after(move_camera).run_if(is_flying),
+                move_camera.after(apply_teleport),
+                apply_teleport,
+            )
+                .run_if(in_state(AppState::Playing)),
         );
 }
 
@@ -56,14 +114,13 @@ fn cleanup(mut commands: Commands, player: Res<PlayerEntity>) {
     commands.remove_resource::<PlayerEntity>();
 }
 
-fn movement(
+/// Noclip flying for creative and spectator, everyone else walks with `physics`.
+pub(crate) fn movement(
     keys: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
-    mut player: Query<&mut Transform, With<Player>>,
-    mut message_writer: EventWriter<ClientMessage>,
-    mut old_position: Local<Vec3>,
+    mut player: Query<(&mut Transform, &mut PlayerBody), With<Player>>,
 ) {
-    let mut transform = player.single_mut();
+    let (mut transform, mut body) = player.single_mut();
 
     let mut direction = Vec3::ZERO;
 
@@ -94,21 +151,17 @@ fn movement(
     let new_movement = direction.normalize_or_zero() * time.delta().as_secs_f32() * 20.0;
     transform.translation += new_movement;
 
-    if old_position.distance(transform.translation) > 1.0 {
-        message_writer.send(ClientMessage::Moved {
-            x: transform.translation.x as _,
-            feet_y: (transform.translation.y - 1.62) as _,
-            z: transform.translation.z as _,
-            on_ground: false,
-        });
-        *old_position = transform.translation;
-    }
+    // physics carries on from here when the game mode changes
+    body.sneaking = false;
+    body.on_ground = false;
+    body.teleport(transform.translation.as_dvec3() - DVec3::Y * EYE_HEIGHT);
 }
 
 fn startup(mut commands: Commands) {
     let entity = commands
         .spawn((
             Player,
+            PlayerBody::new(DVec3::new(0.0, 1.0 - EYE_HEIGHT, 0.0)),
             SpatialBundle {
                 transform: Transform::from_xyz(0.0, 1.0, 0.0),
                 ..default()
@@ -145,11 +198,17 @@ fn startup(mut commands: Commands) {
     commands.insert_resource(PlayerEntity { entity });
 }
 
-fn move_camera(
+pub(crate) fn move_camera(
+    cursor_state: Res<CursorState>,
     mut mouse_motion: EventReader<MouseMotion>,
     mut player: Query<&mut Transform, With<Player>>,
-    mut message_writer: EventWriter<ClientMessage>,
 ) {
+    // the pointer is free for menus
+    if !cursor_state.is_locked {
+        mouse_motion.clear();
+        return;
+    }
+
     let mut transform = player.single_mut();
     for motion in mouse_motion.read() {
         let yaw = -motion.delta.x * 0.003;
@@ -158,11 +217,41 @@ fn move_camera(
         // Order of rotations is important, see <https://gamedev.stackexchange.com/a/136175/103059>
         transform.rotate_y(yaw);
         transform.rotate_local_x(pitch);
-
-        message_writer.send(ClientMessage::Look {
-            yaw,
-            pitch,
-            on_ground: false, // TODO
-        });
     }
 }
+
+pub(crate) fn apply_teleport(
+    mut teleports: EventReader<Teleport>,
+    mut player: Query<(&mut Transform, &mut PlayerBody), With<Player>>,
+) {
+    let Some(teleport) = teleports.read().last() else {
+        return;
+    };
+
+    let (mut transform, mut body) = player.single_mut();
+
+    body.teleport(teleport.position);
+    transform.translation = (teleport.position + DVec3::Y * body.eye_height()).as_vec3();
+    transform.rotation = from_minecraft_rotation(teleport.yaw, teleport.pitch);
+}
+
+/// Yaw and pitch in degrees as Minecraft counts them: yaw 0 looks south (+Z) and grows
+/// towards west, positive pitch looks down. Bevy looks towards -Z and pitches up.
+pub(crate) fn minecraft_rotation(rotation: Quat) -> (f32, f32) {
+    let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+
+    (
+        (180.0 - yaw.to_degrees()).rem_euclid(360.0),
+        -pitch.to_degrees(),
+    )
+}
+
+/// The inverse of `minecraft_rotation`.
+pub(crate) fn from_minecraft_rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_euler(
+        EulerRot::YXZ,
+        (180.0 - yaw).to_radians(),
+        -pitch.to_radians(),
+        0.0,
+    )
+}
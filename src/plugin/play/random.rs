@@ -0,0 +1,33 @@
+/// Cheap pseudo random numbers for effects, the same seed always gives the same numbers.
+#[derive(Debug, Clone, Copy)]
+pub struct XorShift(u32);
+
+impl XorShift {
+    pub fn new(seed: u32) -> Self {
+        // zero would only ever give zeroes
+        Self(seed.max(1))
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// From 0 to 1.
+    pub fn next_f32(&mut self) -> f32 {
+        self.next_u32() as f32 / u32::MAX as f32
+    }
+
+    /// From `min` to `max`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next_f32() * (max - min)
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self::new(0x9E37_79B9)
+    }
+}
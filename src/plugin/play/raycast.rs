@@ -0,0 +1,235 @@
+use super::block_builder::{Block, BlockFace};
+use super::physics;
+use super::player::{self, GameMode, Player};
+use super::world::WorldChunkData;
+use crate::plugin::consts::WorldLayer;
+use crate::state::AppState;
+use bevy::color::palettes::css::BLACK;
+use bevy::pbr::wireframe::{Wireframe, WireframeColor};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use gyra_proto::smp::NetworkBlock;
+
+/// The outline is a bit bigger than a block so it doesn't z-fight with its faces.
+const OUTLINE_GROW: f32 = 0.002;
+
+/// Where a ray stopped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    /// world position of the block
+    pub pos: IVec3,
+    pub block: NetworkBlock,
+    /// face the ray came in through
+    pub face: BlockFace,
+    /// exact point on that face
+    pub point: Vec3,
+    pub distance: f32,
+}
+
+/// The block the crosshair points at, if any is within reach.
+#[derive(Resource, Debug, Default)]
+pub struct TargetBlock {
+    pub hit: Option<RaycastHit>,
+}
+
+#[derive(Component)]
+struct BlockOutline;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<TargetBlock>()
+        .add_systems(OnEnter(AppState::Playing), spawn_outline)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (update_target, update_outline)
+                .chain()
+                .after(player::movement)
+                .after(physics::simulate)
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+// fluids can't be targeted, like in vanilla
+fn is_targetable(block: &NetworkBlock) -> bool {
+    let block = Block::new(block.id, block.metadata);
+    block.id != 0 && !block.is_fluid()
+}
+
+/// Walks the blocks along a ray one at a time (Amanatides & Woo), up to `reach` blocks.
+/// Unloaded columns are passed through like air.
+pub fn raycast(
+    world: &WorldChunkData,
+    origin: Vec3,
+    direction: Vec3,
+    reach: f32,
+) -> Option<RaycastHit> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return None;
+    }
+
+    let mut pos = origin.floor().as_ivec3();
+    let mut step = IVec3::ZERO;
+    // distance along the ray to the next boundary, and between two boundaries, per axis
+    let mut next = Vec3::INFINITY;
+    let mut delta = Vec3::INFINITY;
+
+    for axis in 0..3 {
+        if direction[axis] > 0.0 {
+            step[axis] = 1;
+            delta[axis] = 1.0 / direction[axis];
+            next[axis] = (pos[axis] as f32 + 1.0 - origin[axis]) * delta[axis];
+        } else if direction[axis] < 0.0 {
+            step[axis] = -1;
+            delta[axis] = -1.0 / direction[axis];
+            next[axis] = (origin[axis] - pos[axis] as f32) * delta[axis];
+        }
+    }
+
+    // starting inside a block hits the face we look away from
+    let mut face = BlockFace::from_normal(-direction);
+    let mut distance = 0.0;
+
+    loop {
+        if let Some(block) = world.block_at(pos).filter(is_targetable) {
+            return Some(RaycastHit {
+                pos,
+                block,
+                face,
+                point: origin + direction * distance,
+                distance,
+            });
+        }
+
+        let axis = if next.x < next.y && next.x < next.z {
+            0
+        } else if next.y < next.z {
+            1
+        } else {
+            2
+        };
+
+        distance = next[axis];
+        if distance > reach {
+            return None;
+        }
+
+        pos[axis] += step[axis];
+        next[axis] += delta[axis];
+
+        let mut normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+        face = BlockFace::from_normal(normal.as_vec3());
+    }
+}
+
+fn update_target(
+    player_q: Query<&Transform, With<Player>>,
+    world_data: Res<WorldChunkData>,
+    game_mode: Res<GameMode>,
+    mut target: ResMut<TargetBlock>,
+) {
+    let Ok(transform) = player_q.get_single() else {
+        return;
+    };
+
+    // spectators look through blocks
+    let hit = if *game_mode == GameMode::Spectator {
+        None
+    } else {
+        // the camera sits at the player's eyes
+        raycast(
+            &world_data,
+            transform.translation,
+            *transform.forward(),
+            game_mode.reach(),
+        )
+    };
+
+    if target.hit != hit {
+        target.hit = hit;
+    }
+}
+
+fn update_outline(
+    target: Res<TargetBlock>,
+    mut outline_q: Query<(&mut Transform, &mut Visibility), With<BlockOutline>>,
+) {
+    if !target.is_changed() {
+        return;
+    }
+
+    let Ok((mut transform, mut visibility)) = outline_q.get_single_mut() else {
+        return;
+    };
+
+    match target.hit {
+        Some(hit) => {
+            transform.translation = hit.pos.as_vec3() + 0.5;
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+/// The twelve edges of a cube as degenerate triangles, so the wireframe draws no diagonals.
+fn build_outline_mesh() -> Mesh {
+    let half = 0.5 + OUTLINE_GROW;
+    let corners = (0..8)
+        .map(|idx| {
+            Vec3::new(
+                if idx & 1 == 0 { -half } else { half },
+                if idx & 2 == 0 { -half } else { half },
+                if idx & 4 == 0 { -half } else { half },
+            )
+            .to_array()
+        })
+        .collect::<Vec<_>>();
+
+    let mut indices = vec![];
+    for a in 0..8u32 {
+        for bit in [1, 2, 4] {
+            if a & bit == 0 {
+                indices.extend([a, a | bit, a | bit]);
+            }
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 8])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, corners)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn spawn_outline(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    // no material, only the wireframe is drawn
+    commands.spawn((
+        BlockOutline,
+        meshes.add(build_outline_mesh()),
+        SpatialBundle {
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Wireframe,
+        WireframeColor {
+            color: BLACK.into(),
+        },
+        WorldLayer,
+    ));
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut target: ResMut<TargetBlock>,
+    outline_q: Query<Entity, With<BlockOutline>>,
+) {
+    target.hit = None;
+
+    for entity in outline_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,202 @@
+use super::button;
+use super::hud::PlayerStatus;
+use super::player::Teleport;
+use super::world::WorldChunkData;
+use crate::components::MainCamera;
+use crate::message::ClientMessage;
+use crate::plugin::CursorState;
+use crate::state::AppState;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+/// The server sent `Respawn`, the world is gone and comes again from the start.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Respawned;
+
+/// Shown until the first chunks and the player's position arrive, the player doesn't move
+/// meanwhile.
+#[derive(Resource, Debug, Default)]
+pub struct DownloadingTerrain {
+    position_received: bool,
+}
+
+/// Covers the whole screen, the death and terrain screens are these.
+#[derive(Component)]
+struct Overlay;
+
+#[derive(Component)]
+struct DeathScreen;
+
+#[derive(Component)]
+struct RespawnButton;
+
+#[derive(Component)]
+struct TerrainScreen;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<Respawned>()
+        .add_systems(OnEnter(AppState::Playing), start_downloading)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                show_terrain_screen,
+                wait_for_terrain.run_if(resource_exists::<DownloadingTerrain>),
+                show_death_screen,
+                respawn_button,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(mut commands: Commands, overlays: Query<Entity, With<Overlay>>) {
+    commands.remove_resource::<DownloadingTerrain>();
+
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn start_downloading(mut commands: Commands) {
+    commands.insert_resource(DownloadingTerrain::default());
+}
+
+fn overlay<'a>(
+    commands: &'a mut Commands,
+    camera: &Query<Entity, With<MainCamera>>,
+    color: Color,
+) -> EntityCommands<'a> {
+    let mut overlay = commands.spawn((
+        Overlay,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            background_color: color.into(),
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+    ));
+
+    if let Ok(camera) = camera.get_single() {
+        overlay.insert(TargetCamera(camera));
+    }
+
+    overlay
+}
+
+fn show_terrain_screen(
+    mut commands: Commands,
+    downloading: Option<Res<DownloadingTerrain>>,
+    camera: Query<Entity, With<MainCamera>>,
+    screen: Query<Entity, With<TerrainScreen>>,
+) {
+    match (downloading.is_some(), screen.get_single()) {
+        (true, Err(_)) => {
+            overlay(&mut commands, &camera, Color::srgb(0.18, 0.12, 0.08))
+                .insert(TerrainScreen)
+                .with_children(|p| {
+                    p.spawn(TextBundle::from_section(
+                        "Downloading terrain",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                });
+        }
+        (false, Ok(entity)) => commands.entity(entity).despawn_recursive(),
+        _ => {}
+    }
+}
+
+fn wait_for_terrain(
+    mut commands: Commands,
+    mut teleports: EventReader<Teleport>,
+    mut respawns: EventReader<Respawned>,
+    mut downloading: ResMut<DownloadingTerrain>,
+    world_data: Res<WorldChunkData>,
+) {
+    if teleports.read().count() > 0 {
+        downloading.position_received = true;
+    }
+
+    // the old world's columns are only cleared in the next PreUpdate
+    if respawns.read().count() > 0 {
+        return;
+    }
+
+    if downloading.position_received && !world_data.loaded_column.is_empty() {
+        info!("Terrain is ready");
+        commands.remove_resource::<DownloadingTerrain>();
+    }
+}
+
+fn show_death_screen(
+    mut commands: Commands,
+    status: Res<PlayerStatus>,
+    camera: Query<Entity, With<MainCamera>>,
+    screen: Query<Entity, With<DeathScreen>>,
+    mut cursor_state: ResMut<CursorState>,
+) {
+    let dead = status.health <= 0.0;
+
+    match (dead, screen.get_single()) {
+        (true, Err(_)) => {
+            info!("You died!");
+            cursor_state.is_locked = false;
+
+            overlay(&mut commands, &camera, Color::srgba(0.5, 0.0, 0.0, 0.5))
+                .insert(DeathScreen)
+                .with_children(|p| {
+                    p.spawn(TextBundle::from_section(
+                        "You died!",
+                        TextStyle {
+                            font_size: 48.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+
+                    button::spawn(p, "Respawn", 200.0).insert(RespawnButton);
+                });
+        }
+        (false, Ok(entity)) => {
+            commands.entity(entity).despawn_recursive();
+            cursor_state.is_locked = true;
+        }
+        _ => {}
+    }
+}
+
+fn respawn_button(
+    mut button_q: Query<(Ref<Interaction>, &mut BorderColor), With<RespawnButton>>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    for (interaction, mut border_color) in &mut button_q {
+        if !interaction.is_changed() {
+            continue;
+        }
+
+        match *interaction {
+            Interaction::Pressed => {
+                info!("Respawning");
+                border_color.0 = Color::Srgba(bevy::color::palettes::tailwind::GREEN_200);
+                message_writer.send(ClientMessage::Respawn);
+            }
+            Interaction::Hovered => {
+                border_color.0 = Color::Srgba(bevy::color::palettes::tailwind::CYAN_300);
+            }
+            Interaction::None => border_color.0 = Color::BLACK,
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use super::chat_proto::{Span, SpanStyle};
+use super::random::XorShift;
+use bevy::math::{Affine3A, Vec3A};
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::transform::TransformSystem;
+use bevy::window::PrimaryWindow;
+use std::time::Duration;
+
+/// Obfuscated text changes this often.
+const OBFUSCATE_INTERVAL: Duration = Duration::from_millis(50);
+const OBFUSCATED_CHARS: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZabcdeghjmnopqrsuvwxyz0123456789#$%&?";
+/// How far italic glyphs lean, in pixels right per pixel up.
+const ITALIC_SLANT: f32 = 0.2;
+
+/// Formatted text, a section per span. Bevy only draws the colours, the rest is added on top.
+#[derive(Component, Debug, Default)]
+pub struct RichText {
+    /// font, size and the colour of spans without one
+    base: TextStyle,
+    styles: Vec<SpanStyle>,
+    /// what obfuscated sections really say
+    originals: Vec<String>,
+}
+
+/// Copy of the text with only some sections visible. Bold is drawn again a pixel to the right,
+/// italic is slanted, the chat font has no faces for either.
+#[derive(Component, Clone, Copy)]
+enum Overlay {
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+impl Overlay {
+    fn shows(&self, style: &SpanStyle) -> bool {
+        match self {
+            Overlay::Bold => style.bold && !style.italic,
+            Overlay::Italic => style.italic,
+            Overlay::BoldItalic => style.bold && style.italic,
+        }
+    }
+
+    fn slanted(&self) -> bool {
+        matches!(self, Overlay::Italic | Overlay::BoldItalic)
+    }
+}
+
+/// Underline or strikethrough of a run of glyphs.
+#[derive(Component)]
+struct Decoration;
+
+impl RichText {
+    pub fn new(base: TextStyle) -> Self {
+        Self { base, ..default() }
+    }
+
+    /// Shows `spans` in `text`.
+    pub fn set(&mut self, text: &mut Text, spans: &[Span]) {
+        text.sections = spans
+            .iter()
+            .map(|span| TextSection {
+                value: span.text.clone(),
+                style: TextStyle {
+                    // drawn slanted by an overlay instead
+                    color: match span.style.italic {
+                        true => Color::NONE,
+                        false => color_of(&span.style, self.base.color),
+                    },
+                    ..self.base.clone()
+                },
+            })
+            .collect();
+
+        self.styles = spans.iter().map(|span| span.style.clone()).collect();
+        self.originals = spans.iter().map(|span| span.text.clone()).collect();
+    }
+
+    /// A text bundle showing `spans`.
+    pub fn bundle(spans: &[Span], base: TextStyle) -> (TextBundle, RichText) {
+        let mut rich = RichText::new(base);
+        let mut text = Text::default();
+        rich.set(&mut text, spans);
+
+        (TextBundle { text, ..default() }, rich)
+    }
+
+    /// Style of the span drawn at `point`, in physical pixels from the top left of the text.
+    pub fn style_at(&self, layout: &TextLayoutInfo, point: Vec2) -> Option<&SpanStyle> {
+        let glyph = layout
+            .glyphs
+            .iter()
+            .find(|glyph| Rect::from_center_size(glyph.position, glyph.size).contains(point))?;
+
+        self.styles.get(glyph.section_index)
+    }
+}
+
+fn color_of(style: &SpanStyle, default: Color) -> Color {
+    match style.color.and_then(|color| color.rgb()) {
+        Some([r, g, b]) => Color::srgb_u8(r, g, b),
+        None => default,
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (spawn_overlays, obfuscate, update_overlays, decorate).chain(),
+    )
+    .add_systems(
+        PostUpdate,
+        slant_italics.after(TransformSystem::TransformPropagate),
+    );
+}
+
+fn spawn_overlays(mut commands: Commands, added: Query<Entity, Added<RichText>>) {
+    for entity in added.iter() {
+        commands.entity(entity).with_children(|p| {
+            for overlay in [Overlay::Bold, Overlay::Italic, Overlay::BoldItalic] {
+                let left = match overlay {
+                    Overlay::Italic => 0.0,
+                    Overlay::Bold | Overlay::BoldItalic => 1.0,
+                };
+
+                p.spawn((
+                    overlay,
+                    TextBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(left),
+                            top: Val::Px(0.0),
+                            width: Val::Percent(100.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        });
+    }
+}
+
+fn obfuscate(
+    time: Res<Time>,
+    mut texts: Query<(&RichText, &mut Text)>,
+    mut random: Local<XorShift>,
+    mut elapsed: Local<Duration>,
+) {
+    *elapsed += time.delta();
+    if *elapsed < OBFUSCATE_INTERVAL {
+        return;
+    }
+    *elapsed = Duration::ZERO;
+
+    for (rich, mut text) in texts.iter_mut() {
+        if !rich.styles.iter().any(|style| style.obfuscated) {
+            continue;
+        }
+
+        for (i, section) in text.sections.iter_mut().enumerate() {
+            if !rich.styles.get(i).is_some_and(|style| style.obfuscated) {
+                continue;
+            }
+
+            // spaces stay, so words keep their place
+            section.value = rich.originals[i]
+                .chars()
+                .map(|c| match c.is_whitespace() {
+                    true => c,
+                    false => {
+                        let index = random.next_u32() as usize % OBFUSCATED_CHARS.len();
+                        OBFUSCATED_CHARS[index] as char
+                    }
+                })
+                .collect();
+        }
+    }
+}
+
+fn update_overlays(
+    texts: Query<(&RichText, Ref<Text>), Without<Overlay>>,
+    mut overlays: Query<(&Overlay, &Parent, &mut Text)>,
+) {
+    for (overlay, parent, mut overlay_text) in overlays.iter_mut() {
+        let Ok((rich, text)) = texts.get(parent.get()) else {
+            continue;
+        };
+
+        if !text.is_changed() && !overlay_text.is_added() {
+            continue;
+        }
+
+        // the same layout, with everything the overlay doesn't draw see-through
+        overlay_text.sections = text
+            .sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                let mut section = section.clone();
+                section.style.color = match rich.styles.get(i) {
+                    Some(style) if overlay.shows(style) => color_of(style, rich.base.color),
+                    _ => Color::NONE,
+                };
+                section
+            })
+            .collect();
+    }
+}
+
+/// Bevy can't shear text, so italic overlays are slanted once their place is known.
+fn slant_italics(
+    mut overlays: Query<(&Overlay, &Parent, &Transform, &mut GlobalTransform)>,
+    parents: Query<&GlobalTransform, Without<Overlay>>,
+) {
+    // ui y points down, so the top of each glyph moves right. The x axis tilts a hair, bevy
+    // only culls glyphs of untilted nodes and would take a sheared `i` for zero width
+    let slant = Affine3A::from_cols(
+        Vec3A::new(1.0, 1e-4, 0.0),
+        Vec3A::new(-ITALIC_SLANT, 1.0, 0.0),
+        Vec3A::Z,
+        Vec3A::ZERO,
+    );
+
+    for (overlay, parent, transform, mut global) in overlays.iter_mut() {
+        if !overlay.slanted() {
+            continue;
+        }
+
+        let Ok(parent_global) = parents.get(parent.get()) else {
+            continue;
+        };
+
+        // from the parent each time, so the slant never adds up
+        let slanted = parent_global.affine() * transform.compute_affine() * slant;
+        global.set_if_neq(GlobalTransform::from(slanted));
+    }
+}
+
+/// Underlines and strikes through glyphs once bevy has laid them out.
+fn decorate(
+    mut commands: Commands,
+    texts: Query<(Entity, &RichText, &Text, Ref<TextLayoutInfo>)>,
+    children: Query<&Children>,
+    decorations: Query<(), With<Decoration>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+) {
+    let scale = window
+        .get_single()
+        .map_or(1.0, |window| window.scale_factor())
+        * ui_scale.0;
+
+    for (entity, rich, text, layout) in texts.iter() {
+        if !layout.is_changed() {
+            continue;
+        }
+
+        for child in children.iter_descendants(entity) {
+            if decorations.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        // glyphs of a section next to each other on the same line
+        let mut runs: Vec<(usize, Vec2, Vec2)> = vec![];
+        for glyph in &layout.glyphs {
+            let min = (glyph.position - glyph.size / 2.0) / scale;
+            let max = (glyph.position + glyph.size / 2.0) / scale;
+
+            match runs.last_mut() {
+                // a glyph left of the last one is on the next line
+                Some((section, run_min, run_max))
+                    if *section == glyph.section_index && max.x > run_max.x =>
+                {
+                    *run_min = run_min.min(min);
+                    *run_max = run_max.max(max);
+                }
+                _ => runs.push((glyph.section_index, min, max)),
+            }
+        }
+
+        commands.entity(entity).with_children(|p| {
+            for (section, min, max) in runs {
+                let Some(style) = rich.styles.get(section) else {
+                    continue;
+                };
+                // italic sections are see-through in the text itself
+                let color = color_of(style, rich.base.color);
+                let thickness = (text.sections[section].style.font_size / 10.0).max(1.0);
+
+                let mut lines = vec![];
+                if style.underlined {
+                    lines.push(max.y + 1.0);
+                }
+                if style.strikethrough {
+                    lines.push((min.y + max.y) / 2.0);
+                }
+
+                for y in lines {
+                    p.spawn((
+                        Decoration,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(min.x),
+                                top: Val::Px(y - thickness / 2.0),
+                                width: Val::Px(max.x - min.x),
+                                height: Val::Px(thickness),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+        });
+    }
+}
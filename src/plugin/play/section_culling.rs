@@ -0,0 +1,239 @@
+use super::block_builder::BlockFace;
+use super::chunk_builder::SectionPos;
+use crate::plugin::play::player::WorldModelCamera;
+use crate::plugin::play::world::ActivePlayerChunks;
+use crate::state::AppState;
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::render::primitives::{Aabb, Frustum};
+use bevy::utils::{HashMap, HashSet};
+use gyra_proto::distance::ChunkVec2;
+use std::collections::VecDeque;
+
+/// Sections with fewer opaque blocks than this can't wall anything off.
+const MIN_OPAQUE_BLOCKS: usize = 256;
+
+/// Which faces of a section can see each other through the section, like vanilla's visgraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisGraph {
+    // bit `a * 6 + b` is set when face `a` reaches face `b`
+    connections: u64,
+}
+
+impl VisGraph {
+    pub const OPEN: VisGraph = VisGraph {
+        connections: (1 << 36) - 1,
+    };
+
+    /// Flood fills the see-through blocks of a section, `opaque` gets positions inside 0..16.
+    pub fn compute(opaque: impl Fn(IVec3) -> bool) -> Self {
+        let index = |pos: IVec3| (pos.y << 8 | pos.z << 4 | pos.x) as usize;
+        let position =
+            |idx: usize| IVec3::new(idx as i32 & 15, idx as i32 >> 8, (idx as i32 >> 4) & 15);
+
+        let mut blocked = vec![false; 4096];
+        let mut opaque_count = 0;
+
+        for (idx, blocked) in blocked.iter_mut().enumerate() {
+            if opaque(position(idx)) {
+                *blocked = true;
+                opaque_count += 1;
+            }
+        }
+
+        if opaque_count < MIN_OPAQUE_BLOCKS {
+            return Self::OPEN;
+        }
+
+        let mut connections = 0u64;
+        let mut queue = VecDeque::new();
+
+        for start in 0..4096 {
+            let pos = position(start);
+            let on_border = pos.min_element() == 0 || pos.max_element() == 15;
+
+            if !on_border || blocked[start] {
+                continue;
+            }
+
+            // every flood marks what it reached as blocked, so it is only walked once
+            let mut faces = 0u8;
+            blocked[start] = true;
+            queue.push_back(start);
+
+            while let Some(idx) = queue.pop_front() {
+                let pos = position(idx);
+
+                for face in BlockFace::ALL {
+                    let next = pos + face.normal();
+
+                    if next.min_element() < 0 || next.max_element() > 15 {
+                        faces |= 1 << face as u8;
+                        continue;
+                    }
+
+                    let next = index(next);
+                    if !blocked[next] {
+                        blocked[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            for a in BlockFace::ALL {
+                for b in BlockFace::ALL {
+                    if faces & (1 << a as u8) != 0 && faces & (1 << b as u8) != 0 {
+                        connections |= 1 << (a as u64 * 6 + b as u64);
+                    }
+                }
+            }
+        }
+
+        Self { connections }
+    }
+
+    pub fn connects(&self, from: BlockFace, to: BlockFace) -> bool {
+        self.connections & (1 << (from as u64 * 6 + to as u64)) != 0
+    }
+}
+
+/// Visibility graphs of every meshed section, keyed by chunk x, section index and chunk z.
+#[derive(Resource, Debug, Default)]
+pub struct SectionGraphs {
+    graphs: HashMap<IVec3, VisGraph>,
+}
+
+impl SectionGraphs {
+    pub fn insert(&mut self, pos: IVec3, graph: VisGraph) {
+        self.graphs.insert(pos, graph);
+    }
+
+    pub fn remove_column(&mut self, chunk: ChunkVec2) {
+        for section in 0..16 {
+            self.graphs.remove(&IVec3::new(chunk.x, section, chunk.z));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.graphs.clear();
+    }
+
+    // empty and not yet meshed sections hide nothing
+    fn get(&self, pos: IVec3) -> VisGraph {
+        self.graphs.get(&pos).copied().unwrap_or(VisGraph::OPEN)
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(SectionGraphs::default()).add_systems(
+        PostUpdate,
+        cull_sections
+            .after(bevy::render::view::VisibilitySystems::UpdateFrusta)
+            .before(bevy::render::view::VisibilitySystems::CheckVisibility)
+            .run_if(in_state(AppState::Playing)),
+    );
+}
+
+fn section_aabb(pos: IVec3) -> Aabb {
+    let min = (pos * 16).as_vec3();
+    Aabb::from_min_max(min, min + 16.0)
+}
+
+/// Walks outwards from the camera section, only through faces the visgraph connects and
+/// sections inside the frustum, never turning back towards the camera.
+fn cull_sections(
+    camera_q: Query<(&GlobalTransform, &Frustum), With<WorldModelCamera>>,
+    graphs: Res<SectionGraphs>,
+    active: Res<ActivePlayerChunks>,
+    mut sections_q: Query<(&SectionPos, &mut Visibility)>,
+) {
+    let Ok((camera, frustum)) = camera_q.get_single() else {
+        return;
+    };
+
+    let mut start = (camera.translation() / 16.0).floor().as_ivec3();
+    start.y = start.y.clamp(0, 15);
+
+    let mut visible = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    visible.insert(start);
+    queue.push_back((start, None::<BlockFace>, 0u8));
+
+    while let Some((pos, entered, directions)) = queue.pop_front() {
+        let graph = graphs.get(pos);
+
+        for face in BlockFace::ALL {
+            if directions & (1 << face.opposite() as u8) != 0 {
+                continue;
+            }
+
+            if let Some(entered) = entered {
+                if !graph.connects(entered, face) {
+                    continue;
+                }
+            }
+
+            let next = pos + face.normal();
+
+            if !(0..16).contains(&next.y)
+                || visible.contains(&next)
+                || !active
+                    .chunks
+                    .contains_key(&ChunkVec2::new_local(next.x, next.z))
+            {
+                continue;
+            }
+
+            if !frustum.intersects_obb(&section_aabb(next), &Affine3A::IDENTITY, true, false) {
+                continue;
+            }
+
+            visible.insert(next);
+            queue.push_back((next, Some(face.opposite()), directions | 1 << face as u8));
+        }
+    }
+
+    for (section, mut visibility) in sections_q.iter_mut() {
+        let wanted = if visible.contains(&section.0) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        visibility.set_if_neq(wanted);
+    }
+}
+
+#[test]
+fn sparse_sections_connect_every_face() {
+    // a diagonal line is far below the threshold
+    let graph = VisGraph::compute(|pos| pos.x == pos.y && pos.y == pos.z);
+
+    assert_eq!(graph, VisGraph::OPEN);
+}
+
+#[test]
+fn a_floor_splits_up_from_down() {
+    let graph = VisGraph::compute(|pos| pos.y == 8);
+
+    assert!(!graph.connects(BlockFace::Up, BlockFace::Down));
+    assert!(!graph.connects(BlockFace::Down, BlockFace::Up));
+
+    // both halves still reach every side
+    assert!(graph.connects(BlockFace::North, BlockFace::South));
+    assert!(graph.connects(BlockFace::West, BlockFace::East));
+    assert!(graph.connects(BlockFace::Up, BlockFace::North));
+    assert!(graph.connects(BlockFace::Down, BlockFace::East));
+}
+
+#[test]
+fn solid_sections_connect_nothing() {
+    let graph = VisGraph::compute(|_| true);
+
+    for a in BlockFace::ALL {
+        for b in BlockFace::ALL {
+            assert!(!graph.connects(a, b), "{a:?} reaches {b:?}");
+        }
+    }
+}
@@ -0,0 +1,736 @@
+use super::physics::Tick;
+use super::player::{Player, WorldModelCamera};
+use super::random::XorShift;
+use super::weather::Weather;
+use super::world::{ChunkLoadDistance, Dimension};
+use crate::message::ServerMessage;
+use crate::plugin::consts::WorldLayer;
+use crate::resource_pack::ResourcePack;
+use crate::resources::ResourcePackLocation;
+use crate::state::AppState;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::transform::TransformSystem;
+use std::f32::consts::{PI, TAU};
+
+const SUN_TEXTURE: &str = "assets/minecraft/textures/environment/sun.png";
+const MOON_TEXTURE: &str = "assets/minecraft/textures/environment/moon_phases.png";
+
+/// Everything in the sky is drawn inside this, well past the fog.
+const DOME_RADIUS: f32 = 500.0;
+const CELESTIAL_DISTANCE: f32 = 400.0;
+const SUN_SIZE: f32 = 120.0;
+const MOON_SIZE: f32 = 80.0;
+const STAR_COUNT: usize = 1500;
+
+const DAY_ILLUMINANCE: f32 = 3000.0;
+const NIGHT_ILLUMINANCE: f32 = 100.0;
+const DAY_AMBIENT: f32 = 250.0;
+const NIGHT_AMBIENT: f32 = 40.0;
+
+/// Sky and fog of the overworld at noon, from a plains biome.
+const OVERWORLD_SKY: Color = Color::srgb(0.47, 0.65, 1.0);
+const OVERWORLD_FOG: Color = Color::srgb(0.75, 0.85, 1.0);
+
+/// Ticks of the world, from `TimeUpdate`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WorldTime {
+    pub age: i64,
+    /// 0 is sunrise, 6000 noon, negative while the daylight cycle is stopped
+    pub time_of_day: i64,
+}
+
+impl WorldTime {
+    pub fn cycling(&self) -> bool {
+        self.time_of_day >= 0
+    }
+
+    /// How far the sun went around the world, 0 at noon and 0.5 at midnight.
+    pub fn celestial_angle(&self) -> f32 {
+        let time = self.time_of_day.abs() % 24000;
+        let linear = (time as f32 / 24000.0 - 0.25).rem_euclid(1.0);
+
+        // days are a bit longer than nights
+        let eased = 1.0 - ((linear * PI).cos() + 1.0) / 2.0;
+        linear + (eased - linear) / 3.0
+    }
+
+    /// Points at the sun, the moon is on the other side.
+    pub fn sun_direction(&self) -> Vec3 {
+        Quat::from_rotation_z(self.celestial_angle() * TAU) * Vec3::Y
+    }
+
+    /// 8 phases, one per day, starting with the full moon.
+    pub fn moon_phase(&self) -> usize {
+        (self.time_of_day.abs() / 24000 % 8) as usize
+    }
+}
+
+/// What the sky looks like this frame, worked out from the time and the dimension.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    /// straight up
+    pub zenith: Color,
+    /// at the horizon, the fog and clear colour
+    pub horizon: Color,
+    /// red glow on the side of the rising or setting sun, and its strength
+    pub sunrise: Option<(Color, f32)>,
+    /// 0 at night, 1 at day
+    pub daylight: f32,
+    pub stars: f32,
+    /// how bright sun, moon and stars are, 0 hides them
+    pub celestial: f32,
+    /// where the fog starts, as a part of where it ends
+    pub fog_start: f32,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            zenith: OVERWORLD_SKY,
+            horizon: OVERWORLD_FOG,
+            sunrise: None,
+            daylight: 1.0,
+            stars: 0.0,
+            celestial: 1.0,
+            fog_start: 0.75,
+        }
+    }
+}
+
+impl Sky {
+    fn of(dimension: Dimension, time: &WorldTime) -> Self {
+        match dimension {
+            Dimension::Overworld => Self::overworld(time),
+
+            // neither has a day, the nether is a thick red haze
+            Dimension::Nether => Self {
+                zenith: Color::srgb(0.2, 0.03, 0.03),
+                horizon: Color::srgb(0.2, 0.03, 0.03),
+                sunrise: None,
+                daylight: 0.6,
+                stars: 0.0,
+                celestial: 0.0,
+                fog_start: 0.05,
+            },
+
+            Dimension::End => Self {
+                zenith: Color::srgb(0.08, 0.06, 0.1),
+                horizon: Color::srgb(0.04, 0.03, 0.05),
+                sunrise: None,
+                daylight: 0.5,
+                stars: 0.0,
+                celestial: 0.0,
+                fog_start: 0.75,
+            },
+        }
+    }
+
+    fn overworld(time: &WorldTime) -> Self {
+        let cos = (time.celestial_angle() * TAU).cos();
+        let daylight = (cos * 2.0 + 0.5).clamp(0.0, 1.0);
+        let stars = (1.0 - (cos * 2.0 + 0.25).clamp(0.0, 1.0)).powi(2) * 0.5;
+
+        let sky = OVERWORLD_SKY.to_srgba();
+        let zenith = Color::srgb(
+            sky.red * daylight,
+            sky.green * daylight,
+            sky.blue * daylight,
+        );
+        let fog = OVERWORLD_FOG.to_srgba();
+        let horizon = Color::srgb(
+            fog.red * (daylight * 0.94 + 0.06),
+            fog.green * (daylight * 0.94 + 0.06),
+            fog.blue * (daylight * 0.91 + 0.09),
+        );
+
+        // the sun is near the horizon
+        let sunrise = (cos.abs() <= 0.4).then(|| {
+            let t = cos / 0.4 * 0.5 + 0.5;
+            let alpha = (1.0 - (1.0 - (t * PI).sin()) * 0.99).powi(2);
+            (Color::srgb(t * 0.3 + 0.7, t * t * 0.7 + 0.2, 0.2), alpha)
+        });
+
+        Self {
+            zenith,
+            horizon,
+            sunrise,
+            daylight,
+            stars,
+            celestial: 1.0,
+            fog_start: 0.75,
+        }
+    }
+
+    /// Clouds hide the sun and the stars, lightning lights everything up for a moment.
+    fn weathered(mut self, weather: &Weather) -> Self {
+        let rain = weather.rain;
+        let thunder = weather.thunder();
+
+        for (amount, grey) in [(rain, 0.6), (thunder, 0.2)] {
+            if amount > 0.0 {
+                let zenith = self.zenith.to_srgba();
+                let luminance = zenith.red * 0.3 + zenith.green * 0.59 + zenith.blue * 0.11;
+                let overcast = Color::srgb(luminance * grey, luminance * grey, luminance * grey);
+                self.zenith = self.zenith.mix(&overcast, amount * 0.75);
+
+                let horizon = self.horizon.to_srgba();
+                self.horizon = Color::srgb(
+                    horizon.red * (1.0 - amount * 0.5),
+                    horizon.green * (1.0 - amount * 0.5),
+                    horizon.blue * (1.0 - amount * 0.4),
+                );
+
+                self.daylight *= 1.0 - amount * 5.0 / 16.0;
+            }
+        }
+
+        self.celestial *= 1.0 - rain;
+        self.stars *= 1.0 - rain;
+        self.sunrise = self
+            .sunrise
+            .map(|(color, strength)| (color, strength * (1.0 - rain)));
+
+        if weather.flash > 0 {
+            let flash = Color::srgb(0.8, 0.8, 1.0);
+            self.zenith = self.zenith.mix(&flash, 0.45);
+            self.horizon = self.horizon.mix(&flash, 0.45);
+        }
+
+        self
+    }
+}
+
+/// Follows the player around, everything in the sky hangs from it.
+#[derive(Component)]
+struct SkyRoot;
+
+/// Turns with the time of day, the sun is at +Y and the moon at -Y.
+#[derive(Component)]
+struct Celestial;
+
+#[derive(Component)]
+struct Dome;
+
+/// The sun or the moon, faded by clouds.
+#[derive(Component)]
+struct Body {
+    color: Color,
+}
+
+#[derive(Component)]
+struct Moon;
+
+#[derive(Component)]
+struct Stars;
+
+#[derive(Component)]
+struct SunLight;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<WorldTime>()
+        .init_resource::<Sky>()
+        .add_systems(OnEnter(AppState::Playing), spawn)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                receive_time,
+                advance_time.run_if(on_event::<Tick>()),
+                update_sky,
+                (
+                    update_dome,
+                    update_celestial,
+                    fade_celestial,
+                    update_light,
+                    update_fog,
+                ),
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            PostUpdate,
+            follow_player
+                .before(TransformSystem::TransformPropagate)
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut time: ResMut<WorldTime>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    roots: Query<Entity, With<SkyRoot>>,
+    lights: Query<Entity, With<SunLight>>,
+) {
+    *time = WorldTime::default();
+    clear_color.0 = crate::SKY_COLOR;
+    *ambient = AmbientLight::default();
+
+    for entity in roots.iter().chain(lights.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn read_texture(pack: &mut ResourcePack, name: &str) -> crate::error::Result<Option<Image>> {
+    let data = pack.read(name)?;
+
+    match Image::from_buffer(
+        &data,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::nearest(),
+        RenderAssetUsages::default(),
+    ) {
+        Ok(image) => Ok(Some(image)),
+        Err(e) => {
+            warn!("Skipping texture {name}: {e}");
+            Ok(None)
+        }
+    }
+}
+
+fn load_textures(location: &ResourcePackLocation) -> (Option<Image>, Option<Image>) {
+    let Some(path) = &location.path else {
+        return (None, None);
+    };
+
+    let textures = ResourcePack::open(path).and_then(|mut pack| {
+        Ok((
+            read_texture(&mut pack, SUN_TEXTURE)?,
+            read_texture(&mut pack, MOON_TEXTURE)?,
+        ))
+    });
+
+    textures.unwrap_or_else(|e| {
+        warn!("Could not load the sun and moon from {path:?}: {e}");
+        (None, None)
+    })
+}
+
+/// A square of `size` facing the centre of the sky, `distance` away along Y.
+fn celestial_quad(size: f32, distance: f32, uv: Rect) -> Mesh {
+    let half = size / 2.0;
+    let positions = vec![
+        [-half, distance, -half],
+        [half, distance, -half],
+        [half, distance, half],
+        [-half, distance, half],
+    ];
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, -1.0, 0.0]; 4])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, quad_uvs(uv))
+    .with_inserted_indices(Indices::U32(vec![0, 1, 2, 0, 2, 3]))
+}
+
+fn quad_uvs(uv: Rect) -> Vec<[f32; 2]> {
+    vec![
+        [uv.min.x, uv.min.y],
+        [uv.max.x, uv.min.y],
+        [uv.max.x, uv.max.y],
+        [uv.min.x, uv.max.y],
+    ]
+}
+
+/// The moon texture is a 4 by 2 grid of phases.
+fn moon_uv(phase: usize) -> Rect {
+    let x = (phase % 4) as f32 / 4.0;
+    let y = (phase / 4) as f32 / 2.0;
+    Rect::new(x, y, x + 0.25, y + 0.5)
+}
+
+/// Small squares scattered over the sky, always at the same places.
+fn build_stars() -> Mesh {
+    let mut positions = Vec::with_capacity(STAR_COUNT * 4);
+    let mut normals = Vec::with_capacity(STAR_COUNT * 4);
+    let mut indices = Vec::with_capacity(STAR_COUNT * 6);
+
+    // the sky shouldn't change between runs
+    let mut random = XorShift::new(10842);
+
+    while positions.len() < STAR_COUNT * 4 {
+        let direction = Vec3::new(
+            random.range(-1.0, 1.0),
+            random.range(-1.0, 1.0),
+            random.range(-1.0, 1.0),
+        );
+        let size = random.range(0.15, 0.25) * 4.0;
+
+        // only directions inside the unit sphere keep the spread even
+        let length = direction.length_squared();
+        if !(0.01..1.0).contains(&length) {
+            continue;
+        }
+
+        let direction = direction.normalize();
+        let center = direction * CELESTIAL_DISTANCE;
+        let right = direction.any_orthonormal_vector();
+        let up = direction.cross(right);
+
+        let start = positions.len() as u32;
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            positions.push((center + (right * x + up * y) * size).to_array());
+            normals.push((-direction).to_array());
+        }
+
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn sky_material(
+    color: Color,
+    texture: Option<Handle<Image>>,
+    alpha_mode: AlphaMode,
+) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        base_color_texture: texture,
+        alpha_mode,
+        unlit: true,
+        fog_enabled: false,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    }
+}
+
+fn spawn(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    location: Res<ResourcePackLocation>,
+) {
+    let (sun_texture, moon_texture) = load_textures(&location);
+
+    // the vanilla textures are black around the disc, added up it disappears
+    let sun_color = match sun_texture {
+        Some(_) => Color::WHITE,
+        None => Color::srgb(1.0, 0.9, 0.5),
+    };
+    let moon_color = match moon_texture {
+        Some(_) => Color::WHITE,
+        None => Color::srgb(0.6, 0.6, 0.65),
+    };
+
+    let sun_material = sky_material(
+        sun_color,
+        sun_texture.map(|image| images.add(image)),
+        AlphaMode::Add,
+    );
+    let moon_material = sky_material(
+        moon_color,
+        moon_texture.map(|image| images.add(image)),
+        AlphaMode::Add,
+    );
+
+    // the sky is seen from inside and coloured per vertex
+    let mut dome = Sphere::new(DOME_RADIUS).mesh().uv(32, 16);
+    let vertex_count = dome.count_vertices();
+    dome.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; vertex_count]);
+
+    commands
+        .spawn((SkyRoot, SpatialBundle::default()))
+        .with_children(|p| {
+            p.spawn((
+                Dome,
+                WorldLayer,
+                NotShadowCaster,
+                PbrBundle {
+                    mesh: meshes.add(dome),
+                    material: materials.add(sky_material(Color::WHITE, None, AlphaMode::Opaque)),
+                    ..default()
+                },
+            ));
+
+            p.spawn((Celestial, SpatialBundle::default()))
+                .with_children(|p| {
+                    p.spawn((
+                        Body { color: sun_color },
+                        WorldLayer,
+                        NotShadowCaster,
+                        PbrBundle {
+                            mesh: meshes.add(celestial_quad(
+                                SUN_SIZE,
+                                CELESTIAL_DISTANCE,
+                                Rect::new(0.0, 0.0, 1.0, 1.0),
+                            )),
+                            material: materials.add(sun_material),
+                            ..default()
+                        },
+                    ));
+
+                    p.spawn((
+                        Moon,
+                        Body { color: moon_color },
+                        WorldLayer,
+                        NotShadowCaster,
+                        PbrBundle {
+                            mesh: meshes.add(celestial_quad(
+                                MOON_SIZE,
+                                -CELESTIAL_DISTANCE,
+                                moon_uv(0),
+                            )),
+                            material: materials.add(moon_material),
+                            ..default()
+                        },
+                    ));
+
+                    p.spawn((
+                        Stars,
+                        WorldLayer,
+                        NotShadowCaster,
+                        PbrBundle {
+                            mesh: meshes.add(build_stars()),
+                            material: materials.add(sky_material(
+                                Color::WHITE,
+                                None,
+                                AlphaMode::Opaque,
+                            )),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+
+    commands.spawn((
+        SunLight,
+        WorldLayer,
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: DAY_ILLUMINANCE,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn follow_player(
+    player: Query<&Transform, (With<Player>, Without<SkyRoot>)>,
+    mut root: Query<&mut Transform, With<SkyRoot>>,
+) {
+    let (Ok(player), Ok(mut root)) = (player.get_single(), root.get_single_mut()) else {
+        return;
+    };
+
+    root.translation = player.translation;
+}
+
+fn receive_time(mut server_reader: EventReader<ServerMessage>, mut world_time: ResMut<WorldTime>) {
+    for message in server_reader.read() {
+        if let ServerMessage::TimeUpdate { age, time_of_day } = message {
+            world_time.age = *age;
+            world_time.time_of_day = *time_of_day;
+        }
+    }
+}
+
+/// The server only sends the time every second, it goes on by itself meanwhile.
+fn advance_time(mut ticks: EventReader<Tick>, mut world_time: ResMut<WorldTime>) {
+    let ticks = ticks.read().count() as i64;
+
+    world_time.age += ticks;
+    if world_time.cycling() {
+        world_time.time_of_day += ticks;
+    }
+}
+
+fn update_sky(
+    dimension: Res<Dimension>,
+    world_time: Res<WorldTime>,
+    weather: Res<Weather>,
+    mut sky: ResMut<Sky>,
+) {
+    if dimension.is_changed() {
+        info!("Now in the {:?}", *dimension);
+    }
+
+    let new = Sky::of(*dimension, &world_time).weathered(&weather);
+    if *sky != new {
+        *sky = new;
+    }
+}
+
+fn update_dome(
+    sky: Res<Sky>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    dome: Query<&Handle<Mesh>, With<Dome>>,
+    world_time: Res<WorldTime>,
+) {
+    if !sky.is_changed() {
+        return;
+    }
+
+    let Some(mesh) = dome.get_single().ok().and_then(|mesh| meshes.get_mut(mesh)) else {
+        return;
+    };
+
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return;
+    };
+
+    // the glow is on the side the sun is rising or setting at
+    let sunrise_side = world_time.sun_direction().x.signum();
+
+    let zenith = sky.zenith.to_linear();
+    let horizon = sky.horizon.to_linear();
+
+    let colors: Vec<[f32; 4]> = positions
+        .iter()
+        .map(|position| {
+            let direction = Vec3::from_array(*position) / DOME_RADIUS;
+            let height = direction.y.max(0.0).sqrt();
+            let mut color = horizon.mix(&zenith, height);
+
+            if let Some((glow, strength)) = sky.sunrise {
+                let facing = (direction.x * sunrise_side).max(0.0) * (1.0 - direction.y.abs());
+                color = color.mix(&glow.to_linear(), facing * strength);
+            }
+
+            color.to_f32_array()
+        })
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+fn update_celestial(
+    sky: Res<Sky>,
+    world_time: Res<WorldTime>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut celestial: Query<(&mut Transform, &mut Visibility), With<Celestial>>,
+    moon: Query<&Handle<Mesh>, With<Moon>>,
+    mut phase: Local<Option<usize>>,
+) {
+    for (mut transform, mut visibility) in celestial.iter_mut() {
+        transform.rotation = Quat::from_rotation_z(world_time.celestial_angle() * TAU);
+        *visibility = if sky.celestial > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let moon_phase = world_time.moon_phase();
+    if *phase != Some(moon_phase) {
+        for handle in moon.iter() {
+            if let Some(mesh) = meshes.get_mut(handle) {
+                mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, quad_uvs(moon_uv(moon_phase)));
+                *phase = Some(moon_phase);
+            }
+        }
+    }
+}
+
+fn fade_celestial(
+    sky: Res<Sky>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    bodies: Query<(&Handle<StandardMaterial>, &Body)>,
+    mut stars: Query<(&Handle<StandardMaterial>, &mut Visibility), With<Stars>>,
+) {
+    if !sky.is_changed() {
+        return;
+    }
+
+    // added on top of the sky, darker is more see-through
+    for (material, body) in bodies.iter() {
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = Color::BLACK.mix(&body.color, sky.celestial);
+        }
+    }
+
+    // stars fade in over the sky colour
+    for (material, mut visibility) in stars.iter_mut() {
+        *visibility = if sky.stars > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = sky.zenith.mix(&Color::WHITE, sky.stars * 2.0);
+        }
+    }
+}
+
+fn update_light(
+    sky: Res<Sky>,
+    mut ambient: ResMut<AmbientLight>,
+    world_time: Res<WorldTime>,
+    mut light: Query<(&mut Transform, &mut DirectionalLight), With<SunLight>>,
+) {
+    let Ok((mut transform, mut light)) = light.get_single_mut() else {
+        return;
+    };
+
+    ambient.brightness = NIGHT_AMBIENT.lerp(DAY_AMBIENT, sky.daylight);
+
+    if sky.celestial <= 0.0 {
+        light.illuminance = 0.0;
+        return;
+    }
+
+    // the moon lights the night, a lot less
+    let sun = world_time.sun_direction();
+    let source = if sun.y >= 0.0 { sun } else { -sun };
+
+    light.illuminance = NIGHT_ILLUMINANCE.lerp(DAY_ILLUMINANCE, sky.daylight);
+    *transform = Transform::default().looking_to(-source, Vec3::Z);
+}
+
+fn update_fog(
+    mut commands: Commands,
+    sky: Res<Sky>,
+    distance: Res<ChunkLoadDistance>,
+    mut clear_color: ResMut<ClearColor>,
+    mut fogs: Query<&mut FogSettings, With<WorldModelCamera>>,
+    spawned: Query<Entity, (With<WorldModelCamera>, Without<FogSettings>)>,
+) {
+    if !sky.is_changed() && !distance.is_changed() && spawned.is_empty() {
+        return;
+    }
+
+    clear_color.0 = sky.horizon;
+
+    // nothing past the loaded chunks can be seen
+    let end = distance.0 as f32 * 16.0;
+    let falloff = FogFalloff::Linear {
+        start: end * sky.fog_start,
+        end,
+    };
+
+    for mut fog in fogs.iter_mut() {
+        fog.color = sky.horizon;
+        fog.falloff = falloff.clone();
+    }
+
+    for entity in spawned.iter() {
+        commands.entity(entity).insert(FogSettings {
+            color: sky.horizon,
+            falloff: falloff.clone(),
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,121 @@
+use super::physics::{self, PlayerBody, Tick};
+use super::player::{self, minecraft_rotation, Player, Teleport};
+use crate::message::ClientMessage;
+use crate::state::AppState;
+use bevy::math::DVec3;
+use bevy::prelude::*;
+
+// moving less than this (squared) isn't worth a packet
+const MIN_MOVEMENT_SQUARED: f64 = 9.0e-4;
+// the position is sent at least once a second
+const POSITION_INTERVAL: u8 = 20;
+
+/// What the server last heard about the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reported {
+    pub position: DVec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Keeps the server up to date with the player's movement, like vanilla does every tick.
+#[derive(Resource, Debug, Default)]
+pub struct MovementSync {
+    /// none until the server places the player
+    pub last: Option<Reported>,
+    ticks_since_position: u8,
+}
+
+impl MovementSync {
+    /// Remembers what the server was told some other way, like a teleport.
+    pub fn reported(&mut self, reported: Reported) {
+        self.last = Some(reported);
+        self.ticks_since_position = 0;
+    }
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<MovementSync>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                acknowledge_teleports,
+                send_movement.run_if(on_event::<Tick>()),
+            )
+                .chain()
+                .after(player::apply_teleport)
+                .after(player::movement)
+                .after(physics::simulate)
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(mut sync: ResMut<MovementSync>) {
+    *sync = MovementSync::default();
+}
+
+/// Vanilla answers every teleport with where it ended up.
+fn acknowledge_teleports(
+    mut teleports: EventReader<Teleport>,
+    mut sync: ResMut<MovementSync>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    for teleport in teleports.read() {
+        message_writer.send(ClientMessage::Movement {
+            position: Some(teleport.position),
+            look: Some((teleport.yaw, teleport.pitch)),
+            on_ground: false,
+        });
+
+        sync.reported(Reported {
+            position: teleport.position,
+            yaw: teleport.yaw,
+            pitch: teleport.pitch,
+        });
+    }
+}
+
+/// Sends one of Player, PlayerPosition, PlayerLook or PlayerPositionAndLook every tick.
+fn send_movement(
+    mut ticks: EventReader<Tick>,
+    mut sync: ResMut<MovementSync>,
+    player_q: Query<(&Transform, &PlayerBody), With<Player>>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    let ticks = ticks.read().count();
+
+    let Ok((transform, body)) = player_q.get_single() else {
+        return;
+    };
+
+    // the server counts a packet per tick, so a hitch still sends as many as were simulated
+    for _ in 0..ticks {
+        let Some(last) = sync.last else {
+            return;
+        };
+
+        let (yaw, pitch) = minecraft_rotation(transform.rotation);
+
+        sync.ticks_since_position += 1;
+        let moved = body.position.distance_squared(last.position) > MIN_MOVEMENT_SQUARED
+            || sync.ticks_since_position >= POSITION_INTERVAL;
+        let rotated = yaw != last.yaw || pitch != last.pitch;
+
+        message_writer.send(ClientMessage::Movement {
+            position: moved.then_some(body.position),
+            look: rotated.then_some((yaw, pitch)),
+            on_ground: body.on_ground,
+        });
+
+        if moved {
+            sync.ticks_since_position = 0;
+        }
+
+        sync.last = Some(Reported {
+            position: if moved { body.position } else { last.position },
+            yaw,
+            pitch,
+        });
+    }
+}
@@ -0,0 +1,249 @@
+use super::chat::{self, ChatInputText, ChatInputUI};
+use super::raycast::TargetBlock;
+use crate::message::{ClientMessage, ServerMessage};
+use crate::state::AppState;
+use bevy::prelude::*;
+use bevy_cosmic_edit::{BufferExtras, CosmicEditor, Edit};
+
+/// Suggestions shown at once, the list scrolls with the selection.
+const SHOWN_MATCHES: usize = 10;
+
+/// What Tab offered for the word being typed.
+#[derive(Resource, Debug, Default)]
+struct TabCompletion {
+    /// the input up to the word being completed
+    base: String,
+    matches: Vec<String>,
+    selected: usize,
+    /// the input after the last completion, typing over it starts again
+    applied: String,
+}
+
+impl TabCompletion {
+    fn current(&self) -> String {
+        format!("{}{}", self.base, self.matches[self.selected])
+    }
+
+    /// Goes to the next match, back to the first after the last.
+    fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.matches.len();
+        self.applied = self.current();
+    }
+}
+
+/// Where the word being completed starts, after the last space.
+fn word_start(text: &str) -> usize {
+    text.rfind(' ').map_or(0, |space| space + 1)
+}
+
+#[derive(Component)]
+struct Suggestions;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<TabCompletion>()
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                spawn_suggestions,
+                forget_edited,
+                request.run_if(chat::chat_open),
+                receive,
+                show_suggestions,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn cleanup(mut completion: ResMut<TabCompletion>) {
+    *completion = TabCompletion::default();
+}
+
+fn input_text(editor: &Query<&mut CosmicEditor, With<ChatInputText>>) -> Option<String> {
+    let editor = editor.get_single().ok()?;
+    Some(
+        editor
+            .with_buffer(|buffer| buffer.get_text())
+            .replace('\t', ""),
+    )
+}
+
+fn set_input_text(editor: &mut Query<&mut CosmicEditor, With<ChatInputText>>, text: &str) {
+    for mut editor in editor.iter_mut() {
+        chat::set_input(&mut editor, text);
+    }
+}
+
+/// The list opens above the input, over the messages.
+fn spawn_suggestions(mut commands: Commands, inputs: Query<Entity, Added<ChatInputUI>>) {
+    for input in inputs.iter() {
+        commands.entity(input).with_children(|p| {
+            p.spawn((
+                Suggestions,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Percent(100.0),
+                        left: Val::Px(12.0),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(4.0)),
+                        display: Display::None,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                    z_index: ZIndex::Global(5),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+/// Typing over a completion throws the suggestions away.
+fn forget_edited(
+    editor: Query<&mut CosmicEditor, With<ChatInputText>>,
+    mut completion: ResMut<TabCompletion>,
+) {
+    if completion.matches.is_empty() {
+        return;
+    }
+
+    if input_text(&editor).is_some_and(|text| text != completion.applied) {
+        *completion = TabCompletion::default();
+    }
+}
+
+/// Tab asks the server for completions, or goes to the next one once they are in.
+fn request(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut editor: Query<&mut CosmicEditor, With<ChatInputText>>,
+    mut completion: ResMut<TabCompletion>,
+    target: Res<TargetBlock>,
+    mut message_writer: EventWriter<ClientMessage>,
+) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    if !completion.matches.is_empty() {
+        completion.select_next();
+        set_input_text(&mut editor, &completion.applied);
+        return;
+    }
+
+    let Some(text) = input_text(&editor) else {
+        return;
+    };
+
+    // the editor may have taken the tab as text
+    set_input_text(&mut editor, &text);
+
+    completion.base = text[..word_start(&text)].to_string();
+    completion.applied = text.clone();
+
+    message_writer.send(ClientMessage::TabComplete {
+        text,
+        looked_at: target.hit.map(|hit| hit.pos),
+    });
+}
+
+fn receive(
+    mut server_reader: EventReader<ServerMessage>,
+    mut editor: Query<&mut CosmicEditor, With<ChatInputText>>,
+    mut completion: ResMut<TabCompletion>,
+) {
+    for message in server_reader.read() {
+        let ServerMessage::TabComplete { matches } = message else {
+            continue;
+        };
+
+        // the input changed since it was asked for
+        if input_text(&editor).is_some_and(|text| text != completion.applied) {
+            continue;
+        }
+
+        if matches.is_empty() {
+            continue;
+        }
+
+        completion.matches = matches.clone();
+        completion.selected = 0;
+        completion.applied = completion.current();
+        set_input_text(&mut editor, &completion.applied);
+    }
+}
+
+fn show_suggestions(
+    mut commands: Commands,
+    completion: Res<TabCompletion>,
+    mut suggestions: Query<(Entity, &mut Style), With<Suggestions>>,
+) {
+    if !completion.is_changed() {
+        return;
+    }
+
+    for (entity, mut style) in suggestions.iter_mut() {
+        commands.entity(entity).despawn_descendants();
+
+        // a single match is already in the input
+        if completion.matches.len() < 2 {
+            style.display = Display::None;
+            continue;
+        }
+        style.display = Display::Flex;
+
+        let first = completion
+            .selected
+            .saturating_sub(SHOWN_MATCHES / 2)
+            .min(completion.matches.len().saturating_sub(SHOWN_MATCHES));
+
+        commands.entity(entity).with_children(|p| {
+            for (i, name) in completion
+                .matches
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(SHOWN_MATCHES)
+            {
+                let color = match i == completion.selected {
+                    true => Color::srgb(1.0, 1.0, 0.33),
+                    false => Color::srgb(0.67, 0.67, 0.67),
+                };
+
+                p.spawn(TextBundle::from_section(
+                    name.clone(),
+                    TextStyle {
+                        font_size: 18.0,
+                        color,
+                        ..default()
+                    },
+                ));
+            }
+        });
+    }
+}
+
+#[test]
+fn completes_the_last_word() {
+    assert_eq!(word_start("/gamemode"), 0);
+    assert_eq!(word_start("/gamemode cre"), 10);
+    assert_eq!(word_start("/tp Steve "), 10);
+    assert_eq!(word_start(""), 0);
+}
+
+#[test]
+fn tab_wraps_through_the_matches() {
+    let mut completion = TabCompletion {
+        base: "/tp ".to_string(),
+        matches: vec!["Alex".to_string(), "Steve".to_string()],
+        ..default()
+    };
+
+    completion.select_next();
+    assert_eq!(completion.applied, "/tp Steve");
+
+    completion.select_next();
+    assert_eq!(completion.selected, 0);
+    assert_eq!(completion.applied, "/tp Alex");
+}
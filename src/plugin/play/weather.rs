@@ -0,0 +1,421 @@
+use super::biome::Climate;
+use super::physics::Tick;
+use super::player::{Player, WorldModelCamera};
+use super::random::XorShift;
+use super::world::WorldChunkData;
+use crate::message::ServerMessage;
+use crate::plugin::consts::WorldLayer;
+use crate::resources::ParticleSettings;
+use crate::state::AppState;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::NoFrustumCulling;
+
+/// Drops start falling this far around the player.
+const RADIUS: f32 = 12.0;
+/// Drops tried every tick in the heaviest rain, with every particle on.
+const DROPS_PER_TICK: f32 = 60.0;
+const RAIN_SPEED: f32 = 14.0;
+const SNOW_SPEED: f32 = 1.5;
+
+const RAIN_COLOR: [f32; 4] = [0.45, 0.5, 0.75, 0.6];
+const SNOW_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+
+const BOLT_TICKS: u8 = 8;
+const BOLT_HEIGHT: f32 = 128.0;
+/// Ticks the sky stays lit after a strike.
+const FLASH_TICKS: u8 = 2;
+
+/// Rain and thunder, from `ChangeGameState`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Weather {
+    pub raining: bool,
+    /// from 0 to 1, fades in and out
+    pub rain: f32,
+    /// from 0 to 1, only heard while it rains
+    pub thunder: f32,
+    /// ticks left of the last lightning flash
+    pub flash: u8,
+}
+
+impl Weather {
+    pub fn thunder(&self) -> f32 {
+        self.thunder * self.rain
+    }
+}
+
+/// A thunderbolt hit the ground at `position`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LightningStrike {
+    pub position: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drop {
+    position: Vec3,
+    /// top of the column it falls in
+    ground: f32,
+    snow: bool,
+    /// snow sways from side to side, each flake a bit differently
+    phase: f32,
+}
+
+#[derive(Resource, Default)]
+struct Precipitation {
+    drops: Vec<Drop>,
+    random: XorShift,
+}
+
+/// One mesh with every drop, rebuilt each frame.
+#[derive(Component)]
+struct PrecipitationMesh;
+
+#[derive(Component)]
+struct Bolt {
+    ticks_left: u8,
+}
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Weather>()
+        .init_resource::<Precipitation>()
+        .add_event::<LightningStrike>()
+        .add_systems(OnEnter(AppState::Playing), spawn)
+        .add_systems(OnExit(AppState::Playing), cleanup)
+        .add_systems(
+            Update,
+            (
+                receive,
+                strike,
+                tick_weather.run_if(on_event::<Tick>()),
+                move_precipitation,
+                build_precipitation,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+}
+
+fn receive(
+    mut server_reader: EventReader<ServerMessage>,
+    mut weather: ResMut<Weather>,
+    mut precipitation: ResMut<Precipitation>,
+    mut lightning_writer: EventWriter<LightningStrike>,
+) {
+    for message in server_reader.read() {
+        match message {
+            // the server tells again if it rains in the new world
+            ServerMessage::Respawn { .. } => {
+                *weather = Weather::default();
+                precipitation.drops.clear();
+            }
+
+            ServerMessage::ChangeGameState { reason, value } => match reason {
+                1 => weather.raining = true,
+                2 => weather.raining = false,
+                7 => weather.rain = value.clamp(0.0, 1.0),
+                8 => weather.thunder = value.clamp(0.0, 1.0),
+                _ => {}
+            },
+
+            ServerMessage::Lightning { position } => {
+                lightning_writer.send(LightningStrike {
+                    position: *position,
+                });
+            }
+
+            _ => {}
+        }
+    }
+}
+
+fn cleanup(
+    mut commands: Commands,
+    mut weather: ResMut<Weather>,
+    mut precipitation: ResMut<Precipitation>,
+    meshes: Query<Entity, With<PrecipitationMesh>>,
+    bolts: Query<Entity, With<Bolt>>,
+) {
+    *weather = Weather::default();
+    precipitation.drops.clear();
+
+    for entity in meshes.iter().chain(bolts.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PrecipitationMesh,
+        WorldLayer,
+        NotShadowCaster,
+        // the mesh changes every frame, its bounds would always be stale
+        NoFrustumCulling,
+        PbrBundle {
+            mesh: meshes.add(
+                Mesh::new(
+                    PrimitiveTopology::TriangleList,
+                    RenderAssetUsages::default(),
+                )
+                .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+                .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new())
+                .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new())
+                .with_inserted_indices(Indices::U32(vec![])),
+            ),
+            material: materials.add(StandardMaterial {
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+/// A jagged line of crossed quads going up from the ground.
+fn build_bolt(random: &mut XorShift) -> Mesh {
+    let mut positions = vec![];
+    let mut indices = vec![];
+
+    let segments = 8;
+    let mut bottom = Vec3::ZERO;
+
+    for segment in 1..=segments {
+        let top = Vec3::new(
+            bottom.x + random.range(-3.0, 3.0),
+            BOLT_HEIGHT * segment as f32 / segments as f32,
+            bottom.z + random.range(-3.0, 3.0),
+        );
+
+        for side in [Vec3::X * 0.3, Vec3::Z * 0.3] {
+            let start = positions.len() as u32;
+            positions.extend(
+                [bottom - side, bottom + side, top + side, top - side]
+                    .map(|corner| corner.to_array()),
+            );
+            indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+        }
+
+        bottom = top;
+    }
+
+    let count = positions.len();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; count])
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn strike(
+    mut commands: Commands,
+    mut strikes: EventReader<LightningStrike>,
+    mut weather: ResMut<Weather>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut random: Local<XorShift>,
+) {
+    for strike in strikes.read() {
+        info!("Lightning at {}", strike.position);
+        weather.flash = FLASH_TICKS;
+
+        commands.spawn((
+            Bolt {
+                ticks_left: BOLT_TICKS,
+            },
+            WorldLayer,
+            NotShadowCaster,
+            PbrBundle {
+                mesh: meshes.add(build_bolt(&mut random)),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::srgb(0.6, 0.6, 0.8),
+                    alpha_mode: AlphaMode::Add,
+                    unlit: true,
+                    // seen from far away, like the sky
+                    fog_enabled: false,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..default()
+                }),
+                transform: Transform::from_translation(strike.position),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Drops into a random column around the player, if it rains or snows there.
+fn new_drop(random: &mut XorShift, world_data: &WorldChunkData, player: Vec3) -> Option<Drop> {
+    let x = player.x + random.range(-RADIUS, RADIUS);
+    let z = player.z + random.range(-RADIUS, RADIUS);
+    let y = player.y + random.range(4.0, 14.0);
+
+    let (column_x, column_z) = (x.floor() as i32, z.floor() as i32);
+    let ground = world_data.heightmap_at(column_x, column_z)?;
+    let climate = Climate::of(world_data.biome_at(column_x, column_z)?);
+
+    // under a roof it's dry
+    if !climate.precipitation || y <= ground as f32 {
+        return None;
+    }
+
+    let snow = climate.snows_at(ground);
+
+    // flakes fall slower and stay longer, fewer keep the amount on screen the same
+    if snow && random.next_f32() > SNOW_SPEED / RAIN_SPEED {
+        return None;
+    }
+
+    Some(Drop {
+        position: Vec3::new(x, y, z),
+        ground: ground as f32,
+        snow,
+        phase: random.range(0.0, std::f32::consts::TAU),
+    })
+}
+
+fn tick_weather(
+    mut commands: Commands,
+    settings: Res<ParticleSettings>,
+    world_data: Res<WorldChunkData>,
+    mut weather: ResMut<Weather>,
+    mut precipitation: ResMut<Precipitation>,
+    player: Query<&Transform, With<Player>>,
+    mut bolts: Query<(Entity, &mut Bolt, &mut Visibility)>,
+) {
+    // the server sends the level while it changes, this only fills the gaps
+    let target = if weather.raining { 1.0 } else { 0.0 };
+    let rain = weather.rain + (target - weather.rain).clamp(-0.01, 0.01);
+    if rain != weather.rain {
+        weather.rain = rain;
+    }
+
+    if weather.flash > 0 {
+        weather.flash -= 1;
+    }
+
+    for (entity, mut bolt, mut visibility) in bolts.iter_mut() {
+        bolt.ticks_left = bolt.ticks_left.saturating_sub(1);
+
+        // it flickers before it's gone
+        *visibility = if bolt.ticks_left % 3 == 1 {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        if bolt.ticks_left == 0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let attempts = (DROPS_PER_TICK * weather.rain * settings.density).round() as usize;
+    let precipitation = &mut *precipitation;
+    for _ in 0..attempts {
+        if let Some(drop) = new_drop(&mut precipitation.random, &world_data, player.translation) {
+            precipitation.drops.push(drop);
+        }
+    }
+}
+
+fn move_precipitation(
+    time: Res<Time>,
+    mut precipitation: ResMut<Precipitation>,
+    player: Query<&Transform, With<Player>>,
+) {
+    if precipitation.drops.is_empty() {
+        return;
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let delta = time.delta_seconds();
+    let elapsed = time.elapsed_seconds();
+
+    precipitation.drops.retain_mut(|drop| {
+        if drop.snow {
+            drop.position.y -= SNOW_SPEED * delta;
+            drop.position.x += (elapsed * 1.5 + drop.phase).sin() * 0.3 * delta;
+        } else {
+            drop.position.y -= RAIN_SPEED * delta;
+        }
+
+        // drops left behind by a running player go too
+        let away = (drop.position.xz() - player.translation.xz()).abs();
+        drop.position.y > drop.ground && away.max_element() < RADIUS + 4.0
+    });
+}
+
+fn build_precipitation(
+    precipitation: Res<Precipitation>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh_q: Query<(&Handle<Mesh>, &mut Visibility), With<PrecipitationMesh>>,
+    camera: Query<&GlobalTransform, With<WorldModelCamera>>,
+) {
+    let Ok((handle, mut visibility)) = mesh_q.get_single_mut() else {
+        return;
+    };
+
+    // an empty mesh is never drawn
+    if precipitation.drops.is_empty() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    }
+
+    let (Ok(camera), Some(mesh)) = (camera.get_single(), meshes.get_mut(handle)) else {
+        return;
+    };
+
+    *visibility = Visibility::Inherited;
+
+    let count = precipitation.drops.len();
+    let mut positions = Vec::with_capacity(count * 4);
+    let mut colors = Vec::with_capacity(count * 4);
+    let mut indices = Vec::with_capacity(count * 6);
+
+    let eye = camera.translation();
+    let camera_right = camera.right().as_vec3();
+    let camera_up = camera.up().as_vec3();
+
+    for drop in &precipitation.drops {
+        // rain streaks stay upright and turn towards the camera, flakes face it
+        let (right, up, color) = if drop.snow {
+            (camera_right * 0.06, camera_up * 0.06, SNOW_COLOR)
+        } else {
+            let facing = (eye - drop.position).with_y(0.0).normalize_or_zero();
+            (Vec3::Y.cross(facing) * 0.03, Vec3::Y * 0.5, RAIN_COLOR)
+        };
+
+        let start = positions.len() as u32;
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            positions.push((drop.position + right * x + up * y).to_array());
+            colors.push(color);
+        }
+
+        indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
@@ -1,9 +1,15 @@
 use crate::plugin::play::player;
+use crate::state::AppState;
 use bevy::prelude::*;
 use bevy::utils::{HashMap, HashSet};
 use gyra_proto::distance::ChunkVec2;
-use gyra_proto::smp;
+use gyra_proto::smp::{self, ChunkSection, NetworkBlock};
+use std::sync::Arc;
 
+/// Blocks exist for 0 <= y < WORLD_HEIGHT.
+pub const WORLD_HEIGHT: i32 = 256;
+
+/// Columns within view distance, their sections are shared with `WorldChunkData`.
 #[derive(Resource, Default)]
 pub struct ActivePlayerChunks {
     pub chunks: HashMap<ChunkVec2, smp::ChunkColumn>,
@@ -21,13 +27,202 @@ pub struct OnGround;
 #[derive(Resource)]
 pub struct ChunkLoadDistance(pub u32);
 
+/// The world the player is in, from `JoinGame` and `Respawn`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dimension {
+    Nether,
+    #[default]
+    Overworld,
+    End,
+}
+
+impl Dimension {
+    pub fn from_id(id: i32) -> Self {
+        match id {
+            -1 => Dimension::Nether,
+            1 => Dimension::End,
+            _ => Dimension::Overworld,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Block;
 
+/// Light levels of a block, both are 0..=15.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Light {
+    pub sky: u8,
+    pub block: u8,
+}
+
+impl Light {
+    /// above the world and inside sections the server left out
+    pub const SKY: Light = Light { sky: 15, block: 0 };
+    /// below the world
+    pub const DARK: Light = Light { sky: 0, block: 0 };
+}
+
+fn local(pos: IVec3) -> (u16, u16, u16) {
+    ((pos.x & 15) as u16, pos.y as u16, (pos.z & 15) as u16)
+}
+
 #[derive(Resource, Default)]
 pub struct WorldChunkData {
     // X-Z -> Section
     pub loaded_column: HashMap<ChunkVec2, smp::ChunkColumn>,
+    // chunk x, section index, chunk z -> bumped whenever blocks or light of the section change
+    generations: HashMap<IVec3, u64>,
+    next_generation: u64,
+}
+
+impl WorldChunkData {
+    /// Stores a column from the server, its neighbours are remeshed so their borders line up.
+    pub fn insert_column(&mut self, column: smp::ChunkColumn) {
+        let pos = ChunkVec2::new_local(column.x, column.z);
+        self.loaded_column.insert(pos, column);
+
+        for (x, z) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+            for section in 0..16 {
+                self.mark_dirty(IVec3::new(pos.x + x, section, pos.z + z));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.loaded_column.clear();
+        self.generations.clear();
+    }
+
+    /// Invalidates the cached mesh of a section.
+    pub fn mark_dirty(&mut self, section: IVec3) {
+        self.next_generation += 1;
+        self.generations.insert(section, self.next_generation);
+    }
+
+    pub fn generation(&self, section: IVec3) -> u64 {
+        self.generations.get(&section).copied().unwrap_or_default()
+    }
+
+    fn column_at(&self, x: i32, z: i32) -> Option<&smp::ChunkColumn> {
+        self.loaded_column.get(&ChunkVec2::new_global(x, z))
+    }
+
+    /// Block at a world position, none if its column isn't loaded.
+    /// Above and below the world there is only air.
+    pub fn block_at(&self, pos: IVec3) -> Option<NetworkBlock> {
+        let column = self.column_at(pos.x, pos.z)?;
+
+        if !(0..WORLD_HEIGHT).contains(&pos.y) {
+            return Some(NetworkBlock::AIR);
+        }
+
+        let (x, y, z) = local(pos);
+        Some(column.block(x, y, z))
+    }
+
+    /// Replaces a block and remeshes what it touches, returning the previous block.
+    /// Unloaded columns and positions outside the world are left alone and give none.
+    pub fn set_block(&mut self, pos: IVec3, block: NetworkBlock) -> Option<NetworkBlock> {
+        if !(0..WORLD_HEIGHT).contains(&pos.y) {
+            return None;
+        }
+
+        let chunk = ChunkVec2::new_global(pos.x, pos.z);
+        let column = self.loaded_column.get_mut(&chunk)?;
+
+        // sections the server didn't send are air under the open sky
+        let section = column.sections[(pos.y >> 4) as usize]
+            .get_or_insert_with(|| Arc::new(ChunkSection::with_skylight(15)));
+
+        let (x, y, z) = local(pos);
+        let old = Arc::make_mut(section).set_block(x, y, z, block);
+
+        if old == block {
+            return Some(old);
+        }
+
+        let section = IVec3::new(chunk.x, pos.y >> 4, chunk.z);
+        let inside = IVec3::new(pos.x & 15, pos.y & 15, pos.z & 15);
+        self.mark_dirty(section);
+
+        // blocks on a border are also drawn by the section next to it
+        for offset in [
+            IVec3::X,
+            IVec3::NEG_X,
+            IVec3::Y,
+            IVec3::NEG_Y,
+            IVec3::Z,
+            IVec3::NEG_Z,
+        ] {
+            let next = inside + offset;
+            if next.min_element() < 0 || next.max_element() > 15 {
+                self.mark_dirty(section + offset);
+            }
+        }
+
+        Some(old)
+    }
+
+    /// Light at a world position, none if its column isn't loaded.
+    pub fn light_at(&self, pos: IVec3) -> Option<Light> {
+        let column = self.column_at(pos.x, pos.z)?;
+
+        if pos.y >= WORLD_HEIGHT {
+            return Some(Light::SKY);
+        } else if pos.y < 0 {
+            return Some(Light::DARK);
+        }
+
+        let (x, y, z) = local(pos);
+        let light = column.section_of(y).map_or(Light::SKY, |section| Light {
+            sky: section.skylight(x, y, z),
+            block: section.blocklight(x, y, z),
+        });
+
+        Some(light)
+    }
+
+    /// Biome id of a world column, none if it isn't loaded.
+    pub fn biome_at(&self, x: i32, z: i32) -> Option<u8> {
+        let column = self.column_at(x, z)?;
+        Some(column.biome((x & 15) as u16, (z & 15) as u16))
+    }
+
+    /// Y right above the highest non-air block of a world column, 0 if it is all air.
+    /// None if the column isn't loaded.
+    pub fn heightmap_at(&self, x: i32, z: i32) -> Option<i32> {
+        let column = self.column_at(x, z)?;
+        let (x, z) = ((x & 15) as u16, (z & 15) as u16);
+
+        for (idx, section) in column.sections.iter().enumerate().rev() {
+            let Some(section) = section.as_ref().filter(|section| section.count > 0) else {
+                continue;
+            };
+
+            if let Some(y) = (0..16).rev().find(|y| section.block_id(x, *y, z) != 0) {
+                return Some(idx as i32 * 16 + y as i32 + 1);
+            }
+        }
+
+        Some(0)
+    }
+
+    /// Every loaded block with `min <= pos <= max`, unloaded columns and y outside the world
+    /// are skipped.
+    pub fn blocks_in(
+        &self,
+        min: IVec3,
+        max: IVec3,
+    ) -> impl Iterator<Item = (IVec3, NetworkBlock)> + '_ {
+        let (min_y, max_y) = (min.y.max(0), max.y.min(WORLD_HEIGHT - 1));
+
+        (min.x..=max.x)
+            .flat_map(move |x| {
+                (min.z..=max.z).flat_map(move |z| (min_y..=max_y).map(move |y| IVec3::new(x, y, z)))
+            })
+            .filter_map(|pos| Some((pos, self.block_at(pos)?)))
+    }
 }
 
 pub(crate) fn plugin(app: &mut App) {
@@ -37,7 +232,13 @@ pub(crate) fn plugin(app: &mut App) {
         /* 16 chunk column */
         /* Make this memory dependent? Like use 1/2 of memory for rendering... */
         .insert_resource(ChunkLoadDistance(4))
-        .add_systems(PreUpdate, update_active_chunks);
+        .init_resource::<Dimension>()
+        .add_systems(PreUpdate, update_active_chunks)
+        .add_systems(OnExit(AppState::Playing), cleanup_dimension);
+}
+
+fn cleanup_dimension(mut dimension: ResMut<Dimension>) {
+    *dimension = Dimension::default();
 }
 
 fn is_chunk_within_view_distance(
@@ -51,7 +252,7 @@ fn is_chunk_within_view_distance(
     distance_x <= view_distance && distance_z <= view_distance
 }
 
-fn update_active_chunks(
+pub(crate) fn update_active_chunks(
     player_q: Query<&Transform, (With<player::Player>, Changed<Transform>)>,
     mut active: ResMut<ActivePlayerChunks>,
     world_data: Res<WorldChunkData>,
@@ -79,6 +280,7 @@ fn update_active_chunks(
 
         for (chunk_pos, chunk) in &world_data.loaded_column {
             if is_chunk_within_view_distance(chunk_pos, &player_distance, view_distance.0 as _) {
+                // cheap, only the section handles are cloned
                 columns.insert(*chunk_pos, chunk.clone());
             }
         }
@@ -1,4 +1,5 @@
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
     window::WindowCloseRequested,
 };
@@ -10,7 +11,10 @@ use std::{
     path::PathBuf,
 };
 
-use crate::resources::{CurrentServerAddress, GamePaths, PlayerAccount};
+use crate::resources::{
+    ChatSettings, CurrentServerAddress, GamePaths, Language, ParticleSettings, PlayerAccount,
+    ResourcePackLocation,
+};
 
 pub struct SettingsPlugin;
 
@@ -18,6 +22,32 @@ pub struct SettingsPlugin;
 struct SettingsProto {
     pub server_address: String,
     pub username: String,
+    #[serde(default)]
+    pub resource_pack: Option<PathBuf>,
+    #[serde(default = "default_particle_density")]
+    pub particle_density: f32,
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(default = "default_chat_history_size")]
+    pub chat_history_size: usize,
+    #[serde(default = "default_chat_messages_per_second")]
+    pub chat_messages_per_second: f32,
+}
+
+fn default_particle_density() -> f32 {
+    ParticleSettings::default().density
+}
+
+fn default_language() -> String {
+    Language::default().locale
+}
+
+fn default_chat_history_size() -> usize {
+    ChatSettings::default().history_size
+}
+
+fn default_chat_messages_per_second() -> f32 {
+    ChatSettings::default().messages_per_second
 }
 
 fn guess_root() -> PathBuf {
@@ -45,6 +75,10 @@ impl Plugin for SettingsPlugin {
         .insert_resource(PlayerAccount {
             username: "GyraPlayer".to_string(),
         })
+        .insert_resource(ResourcePackLocation::default())
+        .insert_resource(ParticleSettings::default())
+        .insert_resource(Language::default())
+        .insert_resource(ChatSettings::default())
         .add_systems(PreStartup, startup)
         .add_systems(PreUpdate, shutdown);
     }
@@ -64,6 +98,10 @@ fn startup(
     paths: Res<GamePaths>,
     mut current_server: ResMut<CurrentServerAddress>,
     mut account: ResMut<PlayerAccount>,
+    mut resource_pack: ResMut<ResourcePackLocation>,
+    mut particles: ResMut<ParticleSettings>,
+    mut language: ResMut<Language>,
+    mut chat: ResMut<ChatSettings>,
 ) {
     let GamePaths {
         root,
@@ -85,6 +123,11 @@ fn startup(
         Ok(settings) => {
             current_server.address = settings.server_address;
             account.username = settings.username;
+            resource_pack.path = settings.resource_pack;
+            particles.density = settings.particle_density.clamp(0.0, 1.0);
+            language.locale = settings.language;
+            chat.history_size = settings.chat_history_size;
+            chat.messages_per_second = settings.chat_messages_per_second.max(0.0);
         }
         Err(e) => {
             error!("Could not read settings: {e:?}");
@@ -95,21 +138,40 @@ fn startup(
 
 use bevy::log;
 
+/// Everything that is written back to the settings file.
+#[derive(SystemParam)]
+struct StoredSettings<'w> {
+    current_server: Res<'w, CurrentServerAddress>,
+    account: Res<'w, PlayerAccount>,
+    resource_pack: Res<'w, ResourcePackLocation>,
+    particles: Res<'w, ParticleSettings>,
+    language: Res<'w, Language>,
+    chat: Res<'w, ChatSettings>,
+}
+
+impl StoredSettings<'_> {
+    fn proto(&self) -> SettingsProto {
+        SettingsProto {
+            server_address: self.current_server.address.clone(),
+            username: self.account.username.clone(),
+            resource_pack: self.resource_pack.path.clone(),
+            particle_density: self.particles.density,
+            language: self.language.locale.clone(),
+            chat_history_size: self.chat.history_size,
+            chat_messages_per_second: self.chat.messages_per_second,
+        }
+    }
+}
+
 fn shutdown(
     mut exits: EventReader<AppExit>,
     paths: Res<GamePaths>,
-    current_server: Res<CurrentServerAddress>,
-    account: Res<PlayerAccount>,
+    settings: StoredSettings,
     mut closed_events: EventReader<WindowCloseRequested>,
 ) {
     let should_save = closed_events.read().count() > 0 || exits.read().count() > 0;
     if should_save {
-        let proto = SettingsProto {
-            server_address: current_server.address.clone(),
-            username: account.username.clone(),
-        };
-
-        if let Err(e) = store_settings(paths.settings_path.clone(), proto) {
+        if let Err(e) = store_settings(paths.settings_path.clone(), settings.proto()) {
             error!("Could not store settings: {e:?}");
         }
 
@@ -0,0 +1,95 @@
+use bevy::log::{info, trace};
+use std::fs::{read, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+use crate::error::{Error, Result};
+
+/// A vanilla client jar or a resource pack, either zipped or extracted into a folder.
+/// Every path is relative to the pack root, like `assets/minecraft/textures/blocks/stone.png`.
+pub enum ResourcePack {
+    Directory(PathBuf),
+    Archive(ZipArchive<File>),
+}
+
+impl ResourcePack {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.is_dir() {
+            info!("Using resource pack directory: {path:?}");
+            return Ok(Self::Directory(path.to_path_buf()));
+        }
+
+        // .jar and .zip are both plain zip files
+        info!("Using resource pack archive: {path:?}");
+        let archive = ZipArchive::new(File::open(path)?)?;
+
+        Ok(Self::Archive(archive))
+    }
+
+    pub fn read(&mut self, name: &str) -> Result<Vec<u8>> {
+        trace!("Reading {name} from resource pack");
+
+        match self {
+            Self::Directory(root) => {
+                let path = root.join(name);
+                if !path.is_file() {
+                    return Err(Error::ResourceNotFound(name.to_string()));
+                }
+
+                Ok(read(path)?)
+            }
+
+            Self::Archive(archive) => {
+                let mut file = match archive.by_name(name) {
+                    Ok(file) => file,
+                    Err(zip::result::ZipError::FileNotFound) => {
+                        return Err(Error::ResourceNotFound(name.to_string()))
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+
+                let mut data = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+
+    /// Lists the files directly inside `folder`, returning their file names.
+    pub fn list(&mut self, folder: &str) -> Result<Vec<String>> {
+        let folder = folder.trim_end_matches('/');
+
+        match self {
+            Self::Directory(root) => {
+                let mut names = vec![];
+
+                for entry in std::fs::read_dir(root.join(folder))? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_file() {
+                        names.push(entry.file_name().to_string_lossy().into_owned());
+                    }
+                }
+
+                names.sort();
+                Ok(names)
+            }
+
+            Self::Archive(archive) => {
+                let prefix = format!("{folder}/");
+
+                let mut names = archive
+                    .file_names()
+                    .filter_map(|name| name.strip_prefix(&prefix))
+                    .filter(|name| !name.is_empty() && !name.contains('/'))
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+
+                names.sort();
+                Ok(names)
+            }
+        }
+    }
+}
@@ -22,3 +22,52 @@ pub struct CurrentServerAddress {
 pub struct PlayerAccount {
     pub username: String,
 }
+
+#[derive(Resource, Debug, Default)]
+pub struct ResourcePackLocation {
+    /// A 1.8 client jar, a zipped resource pack or an extracted pack folder.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Resource, Debug)]
+pub struct ParticleSettings {
+    /// Share of weather particles that are spawned, 0 turns them off and 1 is all of them.
+    pub density: f32,
+}
+
+impl Default for ParticleSettings {
+    fn default() -> Self {
+        Self { density: 1.0 }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Language {
+    /// Name of the lang file without the extension, like `en_US`.
+    pub locale: String,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self {
+            locale: "en_US".to_string(),
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct ChatSettings {
+    /// How many sent messages are remembered for each server.
+    pub history_size: usize,
+    /// How many messages are sent each second at most, 0 for no limit.
+    pub messages_per_second: f32,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            history_size: 100,
+            messages_per_second: 1.0,
+        }
+    }
+}